@@ -1,52 +1,78 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 
 use rbx_types::Variant;
 
-use crate::datatype::{Datatype, StaticLookup, evaluate_construct};
+use crate::datatype::palette::{NO_PALETTES, Palettes};
+use crate::datatype::{
+    Datatype, StaticLookup, evaluate_construct, evaluate_each_values, paired_transparency_property,
+};
 use crate::lexer::Token;
+pub use crate::macro_registry::{BindingFrame, BoundArg};
 use crate::macro_registry::{
-    MacroDefinition, MacroKey, MacroRegistry, collect_macro_def_arg_names, macro_return_context,
+    MacroCallArgs, MacroDefinition, MacroKey, MacroRegistry, bind_macro_args,
+    collect_macro_call_args, collect_macros, find_macro, splice_rest_args,
 };
 use crate::parser::types::{Construct, Delimited, MacroBodyContent, Node, SelectorNode};
 use crate::parser::{ParsedRsml, RsmlParser};
+use crate::selector::{ScopedSelector, build_compound_selector_string, build_selector_string};
 
-mod selector;
+mod specificity;
 pub mod tree_node;
 
-use selector::build_selector_string;
 use tree_node::*;
 
 pub struct RsmlCompiler<'a> {
     pub parsed: ParsedRsml<'a>,
 }
 
-#[derive(Clone, Copy)]
-pub struct BoundArg<'a> {
-    pub construct: &'a Construct<'a>,
-    pub scope_depth: usize,
-}
-
-pub type BindingFrame<'a> = HashMap<String, BoundArg<'a>>;
-
 pub struct MacroContext<'a> {
     pub local: MacroRegistry<'a>,
     pub bindings: Vec<BindingFrame<'a>>,
     pub active_expansions: HashSet<MacroKey<'a>>,
+    /// The value each enclosing `@each` loop has bound its variable to,
+    /// innermost last.
+    pub each_bindings: Vec<(&'a str, Datatype)>,
     pub nobuiltins: bool,
+    pub palettes: &'a Palettes,
 }
 
 impl<'a> RsmlCompiler<'a> {
     pub fn new(parsed: ParsedRsml<'a>) -> CompiledRsml {
+        Self::with_derived(parsed, &[])
+    }
+
+    /// Compiles `parsed` with the macros of the stylesheets it derives from in
+    /// scope. A macro defined more than once is a `DuplicateMacro` error in the
+    /// typechecker; calls resolve to the same definition it keeps, which is the
+    /// file's own, or else the first derived one.
+    pub fn with_derived(parsed: ParsedRsml<'a>, derived: &[&ParsedRsml]) -> CompiledRsml {
+        Self::with_palettes(parsed, derived, &NO_PALETTES)
+    }
+
+    /// Compiles `parsed` like [`RsmlCompiler::with_derived`], resolving
+    /// colours like `brand:primary:500` against `palettes`.
+    pub fn with_palettes(
+        parsed: ParsedRsml<'a>,
+        derived: &[&ParsedRsml],
+        palettes: &'a Palettes,
+    ) -> CompiledRsml {
         let compiler = Self { parsed };
         let mut tree_nodes = CompiledRsml::new();
         let mut current_idx = TreeNodeType::Root;
 
-        let local = collect_user_macros(&compiler.parsed.ast);
+        let mut local = collect_macros(&compiler.parsed.ast);
+        for sheet in derived {
+            for (key, definition) in collect_macros(&sheet.ast) {
+                local.entry(key).or_insert(definition);
+            }
+        }
         let mut macro_ctx = MacroContext {
             local,
-            bindings: vec![HashMap::new()],
+            bindings: vec![BindingFrame::default()],
             active_expansions: HashSet::new(),
+            each_bindings: vec![],
             nobuiltins: compiler.parsed.directives.nobuiltins,
+            palettes,
         };
 
         for construct in &compiler.parsed.ast {
@@ -61,35 +87,14 @@ impl<'a> RsmlCompiler<'a> {
     }
 }
 
-fn collect_user_macros<'a>(ast: &'a [Construct<'a>]) -> MacroRegistry<'a> {
-    let mut registry = MacroRegistry::new();
-    for construct in ast {
-        if let Construct::Macro {
-            name: Some(name_node),
-            args,
-            body,
-            return_type,
-            ..
-        } = construct
-        {
-            if let Token::Identifier(name_str) = name_node.token.value() {
-                let arg_names = collect_macro_def_arg_names(args);
-
-                registry.insert(
-                    MacroKey {
-                        name: *name_str,
-                        arity: arg_names.len(),
-                    },
-                    MacroDefinition {
-                        arg_names,
-                        body: body.as_ref().map(|b| &b.content),
-                        return_context: macro_return_context(return_type),
-                    },
-                );
-            }
-        }
-    }
-    registry
+/// A `-> Datatype` macro being expanded while a value is evaluated. The
+/// `MacroContext` is borrowed for the whole evaluation, so these frames chain
+/// off the lookup instead of being pushed onto its bindings.
+struct DatatypeExpansion<'a> {
+    key: MacroKey<'a>,
+    frame: BindingFrame<'a>,
+    scope_depth: usize,
+    parent: Option<&'a DatatypeExpansion<'a>>,
 }
 
 struct CompilerLookup<'a> {
@@ -97,10 +102,18 @@ struct CompilerLookup<'a> {
     idx: TreeNodeType,
     macro_ctx: Option<&'a MacroContext<'a>>,
     active_scope_depth: usize,
+    expansion: Option<&'a DatatypeExpansion<'a>>,
 }
 
 impl<'a> StaticLookup for CompilerLookup<'a> {
     fn resolve_static(&self, name: &str) -> Datatype {
+        let each_binding = self.macro_ctx.and_then(|ctx| {
+            ctx.each_bindings.iter().rev().find(|(variable, _)| *variable == name)
+        });
+        if let Some((_, value)) = each_binding {
+            return value.clone();
+        }
+
         resolve_static_attribute(name, self.tree_nodes, self.idx)
     }
 
@@ -108,19 +121,96 @@ impl<'a> StaticLookup for CompilerLookup<'a> {
         Datatype::Variant(Variant::String(format!("${}", name)))
     }
 
+    fn palettes(&self) -> &Palettes {
+        self.macro_ctx.map_or(&NO_PALETTES, |ctx| ctx.palettes)
+    }
+
     fn resolve_macro_arg(&self, name: &str, key: Option<&str>) -> Option<Datatype> {
+        let frame = self.frame(self.active_scope_depth)?;
+        let bound = *frame.args.get(name)?;
+
+        self.evaluate_bound_arg(bound, key)
+    }
+
+    fn resolve_macro_rest_arg(&self, name: &str) -> Option<Vec<Datatype>> {
+        let frame = self.frame(self.active_scope_depth)?;
+        let rest = frame.rest.get(name)?;
+
+        Some(rest.iter().filter_map(|bound| self.evaluate_bound_arg(*bound, None)).collect())
+    }
+
+    fn resolve_macro_call(
+        &self,
+        name: &str,
+        body: &Option<Delimited>,
+        key: Option<&str>,
+    ) -> Option<Datatype> {
         let ctx = self.macro_ctx?;
-        let frame = ctx.bindings.get(self.active_scope_depth)?;
-        let bound = *frame.get(name)?;
 
+        let (call_args, spliced) = splice_rest_args(
+            collect_macro_call_args(body),
+            self.frame(self.active_scope_depth),
+        );
+        let (macro_key, definition) = find_macro_definition(name, &call_args, ctx)?;
+
+        let recursive = ctx.active_expansions.contains(&macro_key)
+            || self.expansions().any(|expansion| expansion.key == macro_key);
+        if recursive {
+            return None;
+        }
+
+        let Some(MacroBodyContent::Datatype(Some(datatype))) = definition.body else {
+            return None;
+        };
+        let bound_args = definition.bind_args(&call_args)?;
+
+        let expansion = DatatypeExpansion {
+            key: macro_key,
+            frame: bind_macro_args(bound_args, &spliced, |_| self.active_scope_depth),
+            scope_depth: self
+                .expansion
+                .map_or(ctx.bindings.len(), |expansion| expansion.scope_depth + 1),
+            parent: self.expansion,
+        };
+        let inner_lookup = CompilerLookup {
+            tree_nodes: self.tree_nodes,
+            idx: self.idx,
+            macro_ctx: Some(ctx),
+            active_scope_depth: expansion.scope_depth,
+            expansion: Some(&expansion),
+        };
+
+        evaluate_construct(datatype, key, &inner_lookup)
+    }
+}
+
+impl<'a> CompilerLookup<'a> {
+    fn evaluate_bound_arg(&self, bound: BoundArg, key: Option<&str>) -> Option<Datatype> {
         let inner_lookup = CompilerLookup {
             tree_nodes: self.tree_nodes,
             idx: self.idx,
             macro_ctx: self.macro_ctx,
             active_scope_depth: bound.scope_depth,
+            expansion: self.expansion,
         };
         evaluate_construct(bound.construct, key, &inner_lookup)
     }
+
+    fn expansions(&self) -> impl Iterator<Item = &DatatypeExpansion<'a>> {
+        std::iter::successors(self.expansion, |expansion| expansion.parent)
+    }
+
+    /// The bindings at `scope_depth`, whether pushed by a construct macro or
+    /// by a datatype macro expanded during this evaluation.
+    fn frame(&self, scope_depth: usize) -> Option<&BindingFrame<'a>> {
+        if let Some(frame) = self.macro_ctx?.bindings.get(scope_depth) {
+            return Some(frame);
+        }
+
+        self.expansions()
+            .find(|expansion| expansion.scope_depth == scope_depth)
+            .map(|expansion| &expansion.frame)
+    }
 }
 
 fn current_scope_depth(macro_ctx: &MacroContext) -> usize {
@@ -152,6 +242,7 @@ fn compile_construct<'a>(
                         idx,
                         macro_ctx: Some(&*macro_ctx),
                         active_scope_depth,
+                        expansion: None,
                     };
 
                     if let Some(Datatype::Variant(Variant::Float64(value))) =
@@ -181,6 +272,7 @@ fn compile_construct<'a>(
                 idx,
                 macro_ctx: Some(&*macro_ctx),
                 active_scope_depth,
+                expansion: None,
             };
 
             let Some(datatype) = evaluate_construct(construct, None, &lookup) else {
@@ -200,6 +292,70 @@ fn compile_construct<'a>(
             compile_macro_call(name, body, tree_nodes, current_idx, macro_ctx);
         }
 
+        Construct::If {
+            condition,
+            body,
+            else_body,
+            ..
+        } => {
+            let idx = *current_idx;
+            let active_scope_depth = current_scope_depth(macro_ctx);
+            let lookup = CompilerLookup {
+                tree_nodes,
+                idx,
+                macro_ctx: Some(&*macro_ctx),
+                active_scope_depth,
+                expansion: None,
+            };
+
+            let condition_met = condition
+                .as_deref()
+                .and_then(|condition| evaluate_construct(condition, None, &lookup))
+                .is_some_and(|datatype| is_truthy(&datatype));
+
+            let branch = if condition_met { body } else { else_body };
+            let Some(constructs) = branch.as_ref().and_then(|branch| branch.content.as_ref())
+            else {
+                return;
+            };
+
+            for construct in constructs {
+                compile_construct(construct, tree_nodes, current_idx, macro_ctx);
+            }
+        }
+
+        Construct::Each {
+            variable: Some(variable),
+            iterable: Some(iterable),
+            body: Some(Delimited { content: Some(constructs), .. }),
+            ..
+        } => {
+            let Token::StaticTokenIdentifier(variable) = variable.token.value() else {
+                return;
+            };
+
+            let idx = *current_idx;
+            let active_scope_depth = current_scope_depth(macro_ctx);
+            let lookup = CompilerLookup {
+                tree_nodes,
+                idx,
+                macro_ctx: Some(&*macro_ctx),
+                active_scope_depth,
+                expansion: None,
+            };
+            let values = evaluate_each_values(iterable, &lookup);
+
+            for value in values {
+                macro_ctx.each_bindings.push((*variable, value));
+
+                for construct in constructs {
+                    compile_construct(construct, tree_nodes, current_idx, macro_ctx);
+                }
+
+                macro_ctx.each_bindings.pop();
+            }
+        }
+
         Construct::Derive { .. } | Construct::Macro { .. } => {}
 
         _ => {}
@@ -213,20 +369,61 @@ fn compile_rule<'a>(
     current_idx: &mut TreeNodeType,
     macro_ctx: &mut MacroContext<'a>,
 ) {
+    let mut parent_idx = *current_idx;
+
     let selector_string = selectors.as_ref().map(|s| {
+        let bindings_len = macro_ctx.bindings.len();
         let expanded = expand_selector_macros(s, macro_ctx);
-        build_selector_string(&expanded)
+
+        let selector_string = {
+            let lookups: Vec<CompilerLookup> = (0..macro_ctx.bindings.len())
+                .map(|active_scope_depth| CompilerLookup {
+                    tree_nodes: &*tree_nodes,
+                    idx: *current_idx,
+                    macro_ctx: Some(&*macro_ctx),
+                    active_scope_depth,
+                    expansion: None,
+                })
+                .collect();
+            let scoped: Vec<ScopedSelector> = expanded
+                .iter()
+                .map(|&(node, scope_depth)| (node, &lookups[scope_depth] as &dyn StaticLookup))
+                .collect();
+
+            // Rules joined onto their parent's selector with `&` match the
+            // same instances as the parent, so they sit beside it rather than
+            // in it.
+            let compound = match tree_nodes.get(*current_idx) {
+                AnyTreeNode::Node(Some(parent)) => parent.selector.as_ref().and_then(|selector| {
+                    build_compound_selector_string(&scoped, selector)
+                        .map(|compound| (parent.parent, compound))
+                }),
+                _ => None,
+            };
+
+            match compound {
+                Some((grandparent_idx, compound)) => {
+                    parent_idx = grandparent_idx;
+                    compound
+                }
+                None => build_selector_string(&scoped),
+            }
+        };
+
+        macro_ctx.bindings.truncate(bindings_len);
+        selector_string
     });
 
     let new_node_idx = tree_nodes.nodes_len();
     let new_node_idx_type = TreeNodeType::Node(new_node_idx);
 
-    match tree_nodes.get_node_mut(*current_idx) {
+    match tree_nodes.get_node_mut(parent_idx) {
         AnyTreeNodeMut::Root(node) => node.unwrap().child_rules.push(new_node_idx),
         AnyTreeNodeMut::Node(node) => node.unwrap().child_rules.push(new_node_idx),
     }
 
-    let new_node = TreeNode::new(*current_idx, selector_string);
+    let mut new_node = TreeNode::new(parent_idx, selector_string);
+    new_node.scope = *current_idx;
     tree_nodes.add_node(new_node);
 
     if let Some(body) = body {
@@ -258,16 +455,36 @@ fn compile_assignment<'a>(
         idx,
         macro_ctx: Some(&*macro_ctx),
         active_scope_depth,
+        expansion: None,
     };
 
     match left.token.value() {
         Token::Identifier(prop_name) => {
             if let TreeNodeType::Node(node_idx) = idx {
                 let datatype = evaluate_construct(right, Some(prop_name), &lookup);
+
+                // A translucent colour also sets the property its transparency
+                // lives in.
+                if let Some(Datatype::Translucent(_, transparency)) = &datatype
+                    && let Some(transparency_name) = paired_transparency_property(prop_name)
+                    && let Some(node) = tree_nodes[node_idx].as_mut()
+                {
+                    node.properties.insert(transparency_name, Variant::Float64(*transparency));
+                }
+
+                if let Some(Datatype::StringTemplate(parts)) = datatype {
+                    if let Some(node) = tree_nodes[node_idx].as_mut() {
+                        node.properties.remove(*prop_name);
+                        node.property_templates.insert(prop_name.to_string(), parts);
+                    }
+                    return;
+                }
+
                 let variant = datatype.and_then(|d| d.coerce_to_variant(Some(prop_name)));
 
                 if let Some(variant) = variant {
                     if let Some(node) = tree_nodes[node_idx].as_mut() {
+                        node.property_templates.remove(*prop_name);
                         node.properties.insert(prop_name.to_string(), variant);
                     }
                 }
@@ -276,19 +493,36 @@ fn compile_assignment<'a>(
 
         Token::TokenIdentifier(attr_name) => {
             let datatype = evaluate_construct(right, Some(attr_name), &lookup);
+
+            if let Some(Datatype::StringTemplate(parts)) = datatype {
+                let (attributes, templates) = match tree_nodes.get_node_mut(idx) {
+                    AnyTreeNodeMut::Root(node) => {
+                        let node = node.unwrap();
+                        (&mut node.attributes, &mut node.attribute_templates)
+                    }
+                    AnyTreeNodeMut::Node(node) => {
+                        let node = node.unwrap();
+                        (&mut node.attributes, &mut node.attribute_templates)
+                    }
+                };
+                attributes.remove(*attr_name);
+                templates.insert(attr_name.to_string(), parts);
+                return;
+            }
+
             let variant = datatype.and_then(|d| d.coerce_to_variant(Some(attr_name)));
 
             if let Some(variant) = variant {
                 match tree_nodes.get_node_mut(idx) {
                     AnyTreeNodeMut::Root(node) => {
-                        node.unwrap()
-                            .attributes
-                            .insert(attr_name.to_string(), variant);
+                        let node = node.unwrap();
+                        node.attribute_templates.remove(*attr_name);
+                        node.attributes.insert(attr_name.to_string(), variant);
                     }
                     AnyTreeNodeMut::Node(node) => {
-                        node.unwrap()
-                            .attributes
-                            .insert(attr_name.to_string(), variant);
+                        let node = node.unwrap();
+                        node.attribute_templates.remove(*attr_name);
+                        node.attributes.insert(attr_name.to_string(), variant);
                     }
                 }
             }
@@ -329,58 +563,27 @@ fn compile_macro_call<'a>(
         return;
     };
 
-    let macro_name_str = *macro_name;
-    let call_args = collect_call_args(call_body);
-    let arg_count = call_args.len();
-    let key = MacroKey {
-        name: macro_name_str,
-        arity: arg_count,
+    let (call_args, spliced) =
+        splice_rest_args(collect_macro_call_args(call_body), macro_ctx.bindings.last());
+    let Some((key, definition)) = find_macro_definition(macro_name, &call_args, macro_ctx) else {
+        return;
     };
 
     if macro_ctx.active_expansions.contains(&key) {
         return;
     }
 
-    let (arg_names, body): (Vec<String>, &MacroBodyContent<'a>) = {
-        let from_local = macro_ctx.local.get(&key).and_then(|def| {
-            def.body
-                .map(|b| (def.arg_names.iter().map(|s| s.to_string()).collect(), b))
-        });
-
-        if let Some(pair) = from_local {
-            pair
-        } else if !macro_ctx.nobuiltins
-            && let Some(pair) = crate::builtins::BUILTINS
-                .registry
-                .get(&key)
-                .and_then(|def| {
-                    def.body
-                        .map(|b| (def.arg_names.iter().map(|s| s.to_string()).collect(), b))
-                })
-        {
-            pair
-        } else {
-            return;
-        }
+    let Some(MacroBodyContent::Construct(Some(constructs))) = definition.body else {
+        return;
     };
-
-    let MacroBodyContent::Construct(Some(constructs)) = body else {
+    let Some(bound_args) = definition.bind_args(&call_args) else {
         return;
     };
 
+    // Defaults can't refer to other macro arguments, so they are evaluated in
+    // the caller's scope just like the arguments that were passed.
     let caller_scope = current_scope_depth(macro_ctx);
-    let mut new_frame: BindingFrame<'a> = HashMap::new();
-
-    for (arg_name, arg_value) in arg_names.iter().zip(call_args.iter()) {
-        new_frame.insert(
-            arg_name.clone(),
-            BoundArg {
-                construct: *arg_value,
-                scope_depth: caller_scope,
-            },
-        );
-    }
-
+    let new_frame = bind_macro_args(bound_args, &spliced, |_| caller_scope);
     macro_ctx.bindings.push(new_frame);
     macro_ctx.active_expansions.insert(key);
 
@@ -392,18 +595,37 @@ fn compile_macro_call<'a>(
     macro_ctx.bindings.pop();
 }
 
+/// Resolves a macro call against the file's own macros, then the builtins.
+fn find_macro_definition<'r, 'a>(
+    name: &str,
+    call_args: &MacroCallArgs<'a>,
+    macro_ctx: &'r MacroContext<'a>,
+) -> Option<(MacroKey<'a>, &'r MacroDefinition<'a>)> {
+    find_macro(&macro_ctx.local, name, call_args).or_else(|| {
+        if macro_ctx.nobuiltins {
+            return None;
+        }
+
+        find_macro(&crate::builtins::BUILTINS.registry, name, call_args)
+    })
+}
+
 fn is_selector_comma(node: &SelectorNode) -> bool {
     matches!(node, SelectorNode::Token(n) if matches!(n.token.value(), Token::Comma))
 }
 
+/// Splices the bodies of selector macros into `selectors`, pairing every node
+/// with the scope depth its macro arguments resolve at. Each expansion pushes
+/// a frame of bindings which the caller truncates once the selector is built.
 fn expand_selector_macros<'a>(
     selectors: &'a [SelectorNode<'a>],
     macro_ctx: &mut MacroContext<'a>,
-) -> Vec<&'a SelectorNode<'a>> {
-    let mut out: Vec<&'a SelectorNode<'a>> = Vec::with_capacity(selectors.len());
+) -> Vec<(&'a SelectorNode<'a>, usize)> {
+    let mut out = Vec::with_capacity(selectors.len());
     let mut last_was_comma = true;
-    expand_selectors_into(selectors, macro_ctx, &mut out, &mut last_was_comma);
-    if out.last().is_some_and(|n| is_selector_comma(n)) {
+    let scope_depth = current_scope_depth(macro_ctx);
+    expand_selectors_into(selectors, scope_depth, macro_ctx, &mut out, &mut last_was_comma);
+    if out.last().is_some_and(|(n, _)| is_selector_comma(n)) {
         out.pop();
     }
     out
@@ -411,8 +633,9 @@ fn expand_selector_macros<'a>(
 
 fn expand_selectors_into<'a>(
     selectors: &'a [SelectorNode<'a>],
+    scope_depth: usize,
     macro_ctx: &mut MacroContext<'a>,
-    out: &mut Vec<&'a SelectorNode<'a>>,
+    out: &mut Vec<(&'a SelectorNode<'a>, usize)>,
     last_was_comma: &mut bool,
 ) {
     for selector_node in selectors {
@@ -420,38 +643,29 @@ fn expand_selectors_into<'a>(
             let Token::MacroCallIdentifier(Some(macro_name)) = name.token.value() else {
                 continue;
             };
-            let macro_name_str: &'a str = *macro_name;
-            let arg_count = collect_call_args(body).len();
-            let key = MacroKey {
-                name: macro_name_str,
-                arity: arg_count,
+            let caller_frame = macro_ctx.bindings.get(scope_depth);
+            let (call_args, spliced) = splice_rest_args(collect_macro_call_args(body), caller_frame);
+            let Some((key, definition)) = find_macro_definition(macro_name, &call_args, macro_ctx)
+            else {
+                continue;
             };
 
             if macro_ctx.active_expansions.contains(&key) {
                 continue;
             }
 
-            let matched_body: Option<&'a MacroBodyContent<'a>> = macro_ctx
-                .local
-                .get(&key)
-                .and_then(|def| def.body)
-                .or_else(|| {
-                    if macro_ctx.nobuiltins {
-                        return None;
-                    }
-
-                    crate::builtins::BUILTINS
-                        .registry
-                        .get(&key)
-                        .and_then(|def| def.body)
-                });
-
-            let Some(MacroBodyContent::Selector(Some(inner))) = matched_body else {
+            let Some(MacroBodyContent::Selector(Some(inner))) = definition.body else {
+                continue;
+            };
+            let Some(bound_args) = definition.bind_args(&call_args) else {
                 continue;
             };
 
+            let new_frame = bind_macro_args(bound_args, &spliced, |_| scope_depth);
+            let inner_depth = macro_ctx.bindings.len();
+            macro_ctx.bindings.push(new_frame);
             macro_ctx.active_expansions.insert(key);
-            expand_selectors_into(inner, macro_ctx, out, last_was_comma);
+            expand_selectors_into(inner, inner_depth, macro_ctx, out, last_was_comma);
             macro_ctx.active_expansions.remove(&key);
             continue;
         }
@@ -464,26 +678,14 @@ fn expand_selectors_into<'a>(
         } else {
             *last_was_comma = false;
         }
-        out.push(selector_node);
+        out.push((selector_node, scope_depth));
     }
 }
 
-fn collect_call_args<'a>(body: &'a Option<Delimited<'a>>) -> Vec<&'a Construct<'a>> {
-    let Some(body) = body else {
-        return Vec::new();
-    };
-    let Some(content) = &body.content else {
-        return Vec::new();
-    };
-    content
-        .iter()
-        .filter(|c| {
-            !matches!(
-                c,
-                Construct::Node { node } if matches!(node.token.value(), Token::Comma)
-            )
-        })
-        .collect()
+/// Whether an `@if` condition holds. Like Luau, only `false` and `nil` are
+/// falsy, which also covers static tokens that were never assigned.
+fn is_truthy(datatype: &Datatype) -> bool {
+    !matches!(datatype, Datatype::None | Datatype::Variant(Variant::Bool(false)))
 }
 
 fn resolve_static_attribute(name: &str, tree_nodes: &CompiledRsml, idx: TreeNodeType) -> Datatype {
@@ -498,7 +700,7 @@ fn resolve_static_attribute(name: &str, tree_nodes: &CompiledRsml, idx: TreeNode
                 if let Some(val) = node.static_attributes.get(name) {
                     return val.clone();
                 }
-                resolve_static_attribute(name, tree_nodes, node.parent)
+                resolve_static_attribute(name, tree_nodes, node.scope)
             } else {
                 Datatype::None
             }
@@ -0,0 +1,133 @@
+use crate::specificity::{
+    RuleConflict, RuleSummary, SelectorTarget, analyze_conflicts, parse_selector,
+};
+
+use super::tree_node::{CompiledRsml, TreeNodeType};
+
+impl CompiledRsml {
+    /// The selector alternatives a node matches, including its parents'.
+    pub fn selector_targets(&self, idx: usize) -> Vec<SelectorTarget> {
+        let Some(node) = &self[idx] else {
+            return vec![];
+        };
+
+        let own = node
+            .selector
+            .as_deref()
+            .map(parse_selector)
+            .unwrap_or_else(|| vec![SelectorTarget::default()]);
+
+        let TreeNodeType::Node(parent_idx) = node.parent else {
+            return own;
+        };
+
+        let parents = self.selector_targets(parent_idx);
+        if parents.is_empty() {
+            return own;
+        }
+
+        parents
+            .iter()
+            .flat_map(|parent| own.iter().map(move |target| target.nested_in(parent)))
+            .collect()
+    }
+
+    /// Reports every pair of rules that can match the same instance and set the
+    /// same property to different values, where the winner is decided only by
+    /// declaration order or the later rule silently loses. Conflicts name
+    /// rules by their node index.
+    pub fn analyze_conflicts(&self) -> Vec<RuleConflict> {
+        let rules: Vec<RuleSummary> = (0..self.nodes_len())
+            .filter_map(|idx| {
+                let node = self[idx].as_ref()?;
+                Some(RuleSummary {
+                    idx,
+                    priority: node.priority.unwrap_or(0),
+                    targets: self.selector_targets(idx),
+                    properties: node
+                        .properties
+                        .iter()
+                        .map(|(name, value)| (name.as_str(), value))
+                        .collect(),
+                    branches: vec![],
+                })
+            })
+            .collect();
+
+        analyze_conflicts(&rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::RsmlCompiler;
+    use crate::specificity::ConflictKind;
+
+    #[test]
+    fn equal_rules_are_ambiguous() {
+        let compiled = RsmlCompiler::from_source(
+            "TextLabel { TextColor3 = #fff; }\nTextLabel { TextColor3 = #000; }",
+        );
+        let conflicts = compiled.analyze_conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, ConflictKind::Ambiguous);
+        assert_eq!(conflicts[0].winner, 1);
+    }
+
+    #[test]
+    fn more_specific_earlier_rule_overrides_later() {
+        let compiled = RsmlCompiler::from_source(
+            "TextLabel .title { TextColor3 = #fff; }\nTextLabel { TextColor3 = #000; }",
+        );
+        let conflicts = compiled.analyze_conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, ConflictKind::Overridden);
+        assert_eq!(conflicts[0].winner, 0);
+    }
+
+    #[test]
+    fn priority_outweighs_specificity() {
+        let compiled = RsmlCompiler::from_source(
+            "TextLabel { @priority 5; TextColor3 = #fff; }\n#Title { TextColor3 = #000; }",
+        );
+        let conflicts = compiled.analyze_conflicts();
+        assert_eq!(conflicts[0].kind, ConflictKind::Overridden);
+        assert_eq!(conflicts[0].winner, 0);
+    }
+
+    #[test]
+    fn later_stronger_rule_is_not_reported() {
+        let compiled = RsmlCompiler::from_source(
+            "TextLabel { TextColor3 = #fff; }\nTextLabel .title { TextColor3 = #000; }",
+        );
+        assert!(compiled.analyze_conflicts().is_empty());
+    }
+
+    #[test]
+    fn disjoint_classes_and_states_do_not_conflict() {
+        let compiled = RsmlCompiler::from_source(
+            "TextLabel { TextColor3 = #fff; }\nTextButton { TextColor3 = #000; }\n\
+             Frame:Hover { BackgroundColor3 = #fff; }\nFrame:Press { BackgroundColor3 = #000; }",
+        );
+        assert!(compiled.analyze_conflicts().is_empty());
+    }
+
+    #[test]
+    fn pseudo_instances_of_disjoint_parents_do_not_conflict() {
+        let compiled = RsmlCompiler::from_source(
+            "Frame { ::UIPadding { PaddingTop = 4px; } }\n\
+             TextLabel { ::UIPadding { PaddingTop = 8px; } }",
+        );
+        assert!(compiled.analyze_conflicts().is_empty());
+    }
+
+    #[test]
+    fn nested_rules_inherit_parent_selector() {
+        let compiled = RsmlCompiler::from_source(
+            "TextLabel { :Hover { TextColor3 = #fff; } }\nTextLabel { TextColor3 = #000; }",
+        );
+        let conflicts = compiled.analyze_conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, ConflictKind::Overridden);
+    }
+}
@@ -1,10 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::mem;
 use std::ops::{Index, IndexMut};
 
 use rbx_types::Attributes;
 
-use crate::datatype::Datatype;
+use crate::datatype::{Datatype, TemplatePart};
 
 #[derive(Clone, PartialEq, Copy, Eq, Debug, Hash)]
 pub enum TreeNodeType {
@@ -15,6 +15,9 @@ pub enum TreeNodeType {
 #[derive(Debug)]
 pub struct RootTreeNode {
     pub attributes: Attributes,
+    /// Tokens set to strings joined from dynamic tokens, which the runtime
+    /// builds once those tokens have values.
+    pub attribute_templates: BTreeMap<String, Vec<TemplatePart>>,
     pub static_attributes: HashMap<String, Datatype>,
     pub child_rules: Vec<usize>,
 }
@@ -23,6 +26,7 @@ impl RootTreeNode {
     pub fn new() -> Self {
         Self {
             attributes: Attributes::new(),
+            attribute_templates: BTreeMap::new(),
             static_attributes: HashMap::new(),
             child_rules: vec![],
         }
@@ -34,24 +38,36 @@ pub struct TreeNode {
     pub selector: Option<String>,
     pub priority: Option<i32>,
     pub attributes: Attributes,
+    /// Tokens set to strings joined from dynamic tokens, which the runtime
+    /// builds once those tokens have values.
+    pub attribute_templates: BTreeMap<String, Vec<TemplatePart>>,
     pub static_attributes: HashMap<String, Datatype>,
     pub properties: Attributes,
+    /// Properties set to strings joined from dynamic tokens, built by the
+    /// runtime the same as `attribute_templates`.
+    pub property_templates: BTreeMap<String, Vec<TemplatePart>>,
     pub tweens: Attributes,
     pub child_rules: Vec<usize>,
     pub parent: TreeNodeType,
+    /// The rule whose static tokens are visible from this one. Usually the
+    /// parent, but rules joined onto their parent with `&` sit beside it.
+    pub scope: TreeNodeType,
 }
 
 impl TreeNode {
     pub fn new(parent: TreeNodeType, selector: Option<String>) -> Self {
         Self {
             attributes: Attributes::new(),
+            attribute_templates: BTreeMap::new(),
             static_attributes: HashMap::new(),
             properties: Attributes::new(),
+            property_templates: BTreeMap::new(),
             tweens: Attributes::new(),
             child_rules: vec![],
             priority: None,
             selector,
             parent,
+            scope: parent,
         }
     }
 }
@@ -5,12 +5,17 @@ use rbx_types::{
 use rbx_types_ops::BasicOperations;
 
 use crate::lexer::Token;
+use crate::macro_registry::rest_arg_splice;
 use crate::parser::types::{Construct, Delimited, Node};
+use crate::string_escape::unescape;
 
 use crate::datatype::colors::{BRICK_COLORS, CSS_COLORS, SKIN_COLORS, TAILWIND_COLORS};
+use crate::datatype::interpolation::{
+    Reference, StringPart, TemplatePart, interpolation_parts, tag_references,
+};
 use crate::datatype::lookup::StaticLookup;
 use crate::datatype::tuple;
-use crate::datatype::types::Datatype;
+use crate::datatype::types::{Datatype, Unit};
 use crate::datatype::variants::EnumItemFromNameAndValueName;
 
 pub fn evaluate_construct(
@@ -35,6 +40,33 @@ pub fn evaluate_construct(
                 return Some(left_val);
             };
 
+            let equality = match operators.first().map(|op| op.token.value()) {
+                Some(Token::OpEq) => Some(left_val == right_val),
+                Some(Token::OpNotEq) => Some(left_val != right_val),
+                _ => None,
+            };
+            if let Some(equality) = equality {
+                return Some(Datatype::Variant(Variant::Bool(equality)));
+            }
+
+            if operators.first().is_some_and(|op| matches!(op.token.value(), Token::OpConcat)) {
+                let is_content = [&left_val, &right_val]
+                    .into_iter()
+                    .any(|value| matches!(value, Datatype::Variant(Variant::Content(_))));
+
+                let mut parts = template_parts(left, left_val, lookup)?;
+                parts.extend(template_parts(right.as_deref()?, right_val, lookup)?);
+
+                return Some(join_template(parts, is_content));
+            }
+
+            // A unit carries through arithmetic with plain numbers, but two
+            // different units can't be combined.
+            let unit = match (left_val.unit(), right_val.unit()) {
+                (Some(left_unit), Some(right_unit)) if left_unit != right_unit => return None,
+                (left_unit, right_unit) => left_unit.or(right_unit),
+            };
+
             let left_variant = left_val.coerce_to_variant(key)?;
             let right_variant = right_val.coerce_to_variant(key)?;
 
@@ -44,13 +76,14 @@ pub fn evaluate_construct(
                 None
             };
 
-            result.map(Datatype::Variant)
+            result.map(|variant| with_unit(variant, unit))
         }
 
         Construct::UnaryMinus { operand, .. } => {
             let val = evaluate_construct(operand, key, lookup)?;
+            let unit = val.unit();
             let variant = val.coerce_to_variant(key)?;
-            negate_variant(&variant).map(Datatype::Variant)
+            negate_variant(&variant).map(|variant| with_unit(variant, unit))
         }
 
         Construct::Table { body } => {
@@ -93,6 +126,11 @@ pub fn evaluate_construct(
             }
         }
 
+        Construct::MacroCall { name, body, .. } => match name.token.value() {
+            Token::MacroCallIdentifier(Some(name)) => lookup.resolve_macro_call(name, body, key),
+            _ => None,
+        },
+
         Construct::Assignment { right, .. } => right
             .as_ref()
             .and_then(|r| evaluate_construct(r, key, lookup)),
@@ -137,7 +175,37 @@ fn evaluate_token(node: &Node, key: Option<&str>, lookup: &dyn StaticLookup) ->
             Some(Datatype::Variant(Variant::UDim(UDim::new(scale as f32, 0))))
         }
 
-        Token::StringSingle(s) => Some(Datatype::Variant(Variant::String(s.to_string()))),
+        Token::NumberAngle(s) => {
+            let degrees = match s.strip_suffix("rad") {
+                Some(radians) => parse_number_str(radians)?.to_degrees(),
+                None => parse_number_str(s.strip_suffix("deg").unwrap_or(s))?,
+            };
+            Some(Datatype::Measure(degrees, Unit::Angle))
+        }
+
+        Token::NumberDuration(s) => {
+            let seconds = match s.strip_suffix("ms") {
+                Some(millis) => parse_number_str(millis)? / 1000.0,
+                None => parse_number_str(s.strip_suffix('s').unwrap_or(s))?,
+            };
+            Some(Datatype::Measure(seconds, Unit::Duration))
+        }
+
+        Token::NumberRem(s) => {
+            let amount = parse_number_str(s.strip_suffix("rem").unwrap_or(s))?;
+            scale_by_root_size(amount, lookup.resolve_static(ROOT_SIZE_TOKEN))
+        }
+
+        // Byte escapes can leave a string that isn't valid UTF-8, which only
+        // fits in a binary string.
+        Token::StringSingle(s) => Some(Datatype::Variant(
+            match String::from_utf8(unescape(s).into_owned()) {
+                Ok(string) => Variant::String(string),
+                Err(err) => Variant::BinaryString(err.into_bytes().into()),
+            },
+        )),
+
+        Token::StringInterpolated(content) => interpolate(content, key, lookup),
 
         Token::StringMulti(multi) => Some(Datatype::Variant(Variant::String(
             multi.content.to_string(),
@@ -182,6 +250,8 @@ fn evaluate_token(node: &Node, key: Option<&str>, lookup: &dyn StaticLookup) ->
             .get(&slice.to_lowercase())
             .map(|color| Datatype::Oklab(***color)),
 
+        Token::ColorPalette(slice) => lookup.palettes().color(slice).map(Datatype::Oklab),
+
         Token::TokenIdentifier(attr_name) => Some(lookup.resolve_dynamic(attr_name)),
 
         Token::StaticTokenIdentifier(static_name) => Some(lookup.resolve_static(static_name)),
@@ -203,6 +273,94 @@ fn evaluate_token(node: &Node, key: Option<&str>, lookup: &dyn StaticLookup) ->
     }
 }
 
+/// The values an `@each` loop binds its variable to, in order. Tables and
+/// `&...name` rest arguments are iterated item by item, anything else is a
+/// single value.
+pub fn evaluate_each_values(iterable: &Construct, lookup: &dyn StaticLookup) -> Vec<Datatype> {
+    if let Construct::Table { body } = iterable {
+        return evaluate_delimited_to_vec(body, lookup);
+    }
+
+    if let Some(name) = rest_arg_splice(iterable) {
+        return lookup.resolve_macro_rest_arg(name).unwrap_or_default();
+    }
+
+    if let Some(scale) = evaluate_palette_scale(iterable, lookup) {
+        return scale;
+    }
+
+    match evaluate_construct(iterable, None, lookup) {
+        Some(Datatype::TupleData(items)) => items,
+        None | Some(Datatype::None) => vec![],
+        Some(datatype) => vec![datatype],
+    }
+}
+
+/// A palette family written without a shade, such as `tw:red`, iterates over
+/// the colours of each of its shades, lightest first.
+fn evaluate_palette_scale(iterable: &Construct, lookup: &dyn StaticLookup) -> Option<Vec<Datatype>> {
+    let Construct::Node { node } = iterable else { return None };
+    let (Token::ColorTailwind(slice) | Token::ColorSkin(slice)) = node.token.value() else {
+        return None;
+    };
+
+    let family_key = slice.to_lowercase();
+    let (namespace, family) = family_key.split_once(':')?;
+    if family.contains(':') {
+        return None;
+    }
+
+    let colors = match namespace {
+        "tw" => &TAILWIND_COLORS,
+        _ => &SKIN_COLORS,
+    };
+    let scale = lookup
+        .palettes()
+        .shades(namespace, family)
+        .into_iter()
+        .filter_map(|shade| colors.get(&format!("{family_key}:{shade}")))
+        .map(|color| Datatype::Oklab(***color))
+        .collect::<Vec<_>>();
+
+    (!scale.is_empty()).then_some(scale)
+}
+
+/// Splices the static tokens and macro arguments referenced in a selector tag
+/// into its text, so `p-$!size` becomes `p-2` while `$!size` is `2`.
+pub fn interpolate_tag(tag: &str, lookup: &dyn StaticLookup) -> Option<String> {
+    let mut result = String::with_capacity(tag.len());
+    let mut cursor = 0;
+
+    for (reference, (start, end)) in tag_references(tag) {
+        result.push_str(&tag[cursor..start]);
+
+        let value = match reference {
+            Reference::StaticToken(name) => lookup.resolve_static(name),
+            Reference::MacroArg(name) => lookup.resolve_macro_arg(name, None)?,
+            Reference::Token(_) => return None,
+        };
+        result.push_str(&variant_to_text(&value.coerce_to_variant(None)?)?);
+
+        cursor = end;
+    }
+
+    result.push_str(&tag[cursor..]);
+    Some(result)
+}
+
+/// Joins the values collected by the rest argument `name` into a comma
+/// separated selector list, so `&...tags` spliced into a selector can become
+/// `.Primary, .Large`.
+pub fn interpolate_rest_selectors(name: &str, lookup: &dyn StaticLookup) -> Option<String> {
+    let values = lookup.resolve_macro_rest_arg(name)?;
+    let selectors = values
+        .into_iter()
+        .map(|value| variant_to_text(&value.coerce_to_variant(None)?))
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(selectors.join(", "))
+}
+
 fn evaluate_delimited_to_vec(delimited: &Delimited, lookup: &dyn StaticLookup) -> Vec<Datatype> {
     let Some(content) = &delimited.content else {
         return vec![];
@@ -210,14 +368,17 @@ fn evaluate_delimited_to_vec(delimited: &Delimited, lookup: &dyn StaticLookup) -
 
     content
         .iter()
-        .filter_map(|c| evaluate_construct(c, None, lookup))
+        .flat_map(|c| match rest_arg_splice(c) {
+            Some(name) => lookup.resolve_macro_rest_arg(name).unwrap_or_default(),
+            None => evaluate_construct(c, None, lookup).into_iter().collect(),
+        })
         .collect()
 }
 
 fn coerce_tuple_data(datatypes: Vec<Datatype>, name: Option<&str>) -> Option<Datatype> {
     let mut t = tuple::Tuple::new(name.map(|s| s.to_string()));
     for d in datatypes {
-        t.push(d);
+        t.push(d.without_unit());
     }
     let result = t.coerce_to_datatype();
     match result {
@@ -226,6 +387,36 @@ fn coerce_tuple_data(datatypes: Vec<Datatype>, name: Option<&str>) -> Option<Dat
     }
 }
 
+/// The static token `rem` measurements are relative to.
+const ROOT_SIZE_TOKEN: &str = "RootSize";
+
+/// Used for `rem` when `$!RootSize` hasn't been declared, matching the CSS
+/// default font size.
+const DEFAULT_ROOT_SIZE: f64 = 16.0;
+
+/// `rem` is always an offset, the same as `px`, whichever way the root size
+/// is written.
+fn scale_by_root_size(amount: f64, root_size: Datatype) -> Option<Datatype> {
+    let (scale, offset) = match root_size {
+        Datatype::None => (0.0, DEFAULT_ROOT_SIZE),
+        Datatype::Variant(Variant::Float64(size)) => (0.0, size),
+        Datatype::Variant(Variant::UDim(size)) => (size.scale, size.offset as f64),
+        _ => return None,
+    };
+
+    Some(Datatype::Variant(Variant::UDim(UDim::new(
+        scale * amount as f32,
+        (offset * amount).round() as i32,
+    ))))
+}
+
+fn with_unit(variant: Variant, unit: Option<Unit>) -> Datatype {
+    match (variant, unit) {
+        (Variant::Float64(value), Some(unit)) => Datatype::Measure(value, unit),
+        (variant, _) => Datatype::Variant(variant),
+    }
+}
+
 fn negate_variant(variant: &Variant) -> Option<Variant> {
     match variant {
         Variant::Float64(n) => Some(Variant::Float64(-n)),
@@ -257,6 +448,104 @@ fn apply_operator(op_node: &Node, left: &Variant, right: &Variant) -> Option<Var
     }
 }
 
+fn interpolate(content: &str, key: Option<&str>, lookup: &dyn StaticLookup) -> Option<Datatype> {
+    let mut parts = vec![];
+
+    for part in interpolation_parts(content) {
+        match part {
+            StringPart::Literal(text) => parts.push(TemplatePart::Text(text.to_string())),
+
+            StringPart::Placeholder { reference: Reference::Token(name), .. }
+                if !lookup.resolves_dynamic_values() =>
+            {
+                parts.push(TemplatePart::Token(name.to_string()))
+            }
+
+            StringPart::Placeholder { reference, .. } => {
+                let datatype = match reference {
+                    Reference::StaticToken(name) => lookup.resolve_static(name),
+                    Reference::MacroArg(name) => lookup.resolve_macro_arg(name, key)?,
+                    Reference::Token(name) => lookup.resolve_dynamic(name),
+                };
+
+                parts.extend(value_parts(datatype)?);
+            }
+        }
+    }
+
+    Some(join_template(parts, false))
+}
+
+/// The pieces `construct`, which evaluated to `value`, adds to a string
+/// joined with `..`. Dynamic tokens are left for the runtime to fill in
+/// unless the lookup knows their values.
+fn template_parts(
+    construct: &Construct,
+    value: Datatype,
+    lookup: &dyn StaticLookup,
+) -> Option<Vec<TemplatePart>> {
+    if let Construct::Node { node } = construct
+        && let Token::TokenIdentifier(name) = node.token.value()
+        && !lookup.resolves_dynamic_values()
+    {
+        return Some(vec![TemplatePart::Token(name.to_string())]);
+    }
+
+    value_parts(value)
+}
+
+fn value_parts(value: Datatype) -> Option<Vec<TemplatePart>> {
+    match value {
+        Datatype::StringTemplate(parts) => Some(parts),
+        value => {
+            let text = variant_to_text(&value.coerce_to_variant(None)?)?;
+            Some(vec![TemplatePart::Text(text)])
+        }
+    }
+}
+
+/// Joins the pieces of a string. It stays a template if any dynamic token is
+/// left in it, and otherwise becomes `Content` when `is_content` is set or a
+/// plain string when it isn't.
+fn join_template(parts: Vec<TemplatePart>, is_content: bool) -> Datatype {
+    let mut joined: Vec<TemplatePart> = Vec::with_capacity(parts.len());
+
+    for part in parts {
+        match (joined.last_mut(), part) {
+            (Some(TemplatePart::Text(text)), TemplatePart::Text(next)) => text.push_str(&next),
+            (_, part) => joined.push(part),
+        }
+    }
+
+    if joined.iter().any(|part| matches!(part, TemplatePart::Token(_))) {
+        return Datatype::StringTemplate(joined);
+    }
+
+    let text = match joined.pop() {
+        Some(TemplatePart::Text(text)) => text,
+        _ => String::new(),
+    };
+
+    if is_content {
+        Datatype::Variant(Variant::Content(Content::from(text)))
+    } else {
+        Datatype::Variant(Variant::String(text))
+    }
+}
+
+fn variant_to_text(variant: &Variant) -> Option<String> {
+    match variant {
+        Variant::String(s) => Some(s.clone()),
+        Variant::Content(content) => content.as_uri().map(str::to_string),
+        Variant::Float32(n) => Some(n.to_string()),
+        Variant::Float64(n) => Some(n.to_string()),
+        Variant::Int32(n) => Some(n.to_string()),
+        Variant::Int64(n) => Some(n.to_string()),
+        Variant::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
 fn normalize_hex(hex: &str) -> String {
     let hex = hex.trim_start_matches('#');
     match hex.len() {
@@ -278,7 +567,18 @@ pub(crate) fn shorthand_rebind<'a>(key: &'a str) -> &'a str {
 
 fn parse_number_str(s: &str) -> Option<f64> {
     let cleaned: String = s.chars().filter(|c| *c != '_').collect();
-    cleaned.parse::<f64>().ok()
+
+    if let Some(digits) = strip_radix_prefix(&cleaned, 'x') {
+        u64::from_str_radix(digits, 16).ok().map(|n| n as f64)
+    } else if let Some(digits) = strip_radix_prefix(&cleaned, 'b') {
+        u64::from_str_radix(digits, 2).ok().map(|n| n as f64)
+    } else {
+        cleaned.parse::<f64>().ok()
+    }
+}
+
+fn strip_radix_prefix(s: &str, marker: char) -> Option<&str> {
+    s.strip_prefix('0')?.strip_prefix([marker, marker.to_ascii_uppercase()])
 }
 
 fn is_comma(construct: &Construct) -> bool {
@@ -0,0 +1,136 @@
+use palette::convert::IntoColorUnclamped;
+use palette::{Clamp, IntoColor, Oklab, Oklch, Srgb};
+
+/// Just-noticeable difference in OKLab, below which a clipped colour is
+/// considered indistinguishable from the one it was clipped from.
+const JND: f32 = 0.02;
+const EPSILON: f32 = 0.0001;
+
+/// Channels produced by float conversions land a hair outside `0..1` even for
+/// colours which are really in gamut.
+const CHANNEL_TOLERANCE: f32 = 0.000_1;
+
+pub fn in_srgb_gamut(color: Oklch) -> bool {
+    // `into_color` would clamp the channels, hiding whether they overflowed.
+    channels_in_range(color.into_color_unclamped())
+}
+
+fn channels_in_range(rgb: Srgb<f32>) -> bool {
+    [rgb.red, rgb.green, rgb.blue]
+        .iter()
+        .all(|channel| (-CHANNEL_TOLERANCE..=1.0 + CHANNEL_TOLERANCE).contains(channel))
+}
+
+fn delta_eok(a: Oklch, b: Oklch) -> f32 {
+    let a: Oklab = a.into_color();
+    let b: Oklab = b.into_color();
+
+    ((a.l - b.l).powi(2) + (a.a - b.a).powi(2) + (a.b - b.b).powi(2)).sqrt()
+}
+
+fn clip(color: Oklch) -> Srgb<f32> {
+    let rgb: Srgb<f32> = color.into_color_unclamped();
+    rgb.clamp()
+}
+
+/// Maps `color` into sRGB following the CSS Color 4 gamut mapping algorithm:
+/// chroma is reduced by binary search, holding lightness and hue, until
+/// clipping the result is no longer noticeable. Colours already in gamut are
+/// converted directly, without a detour through OKLCH.
+pub fn gamut_map<C>(color: C) -> Srgb<f32>
+where
+    C: IntoColor<Oklch> + IntoColorUnclamped<Srgb<f32>> + Copy,
+{
+    let rgb: Srgb<f32> = color.into_color_unclamped();
+    if channels_in_range(rgb) {
+        return rgb.clamp();
+    }
+
+    let color: Oklch = color.into_color();
+
+    if color.l >= 1.0 {
+        return Srgb::new(1.0, 1.0, 1.0);
+    }
+
+    if color.l <= 0.0 {
+        return Srgb::new(0.0, 0.0, 0.0);
+    }
+
+    let mut current = color;
+    let mut clipped = clip(current);
+
+    if delta_eok(clipped.into_color(), current) < JND {
+        return clipped;
+    }
+
+    let mut min = 0.0;
+    let mut max = color.chroma;
+    let mut min_in_gamut = true;
+
+    while max - min > EPSILON {
+        let chroma = (min + max) / 2.0;
+        current = Oklch::new(color.l, chroma, color.hue);
+
+        if min_in_gamut && in_srgb_gamut(current) {
+            min = chroma;
+            continue;
+        }
+
+        clipped = clip(current);
+        let error = delta_eok(clipped.into_color(), current);
+
+        if error < JND {
+            if JND - error < EPSILON {
+                return clipped;
+            }
+
+            min_in_gamut = false;
+            min = chroma;
+        } else {
+            max = chroma;
+        }
+    }
+
+    clipped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_in_range(rgb: Srgb<f32>) {
+        for channel in [rgb.red, rgb.green, rgb.blue] {
+            assert!((0.0..=1.0).contains(&channel), "channel out of range: {:?}", rgb);
+        }
+    }
+
+    #[test]
+    fn in_gamut_colour_is_unchanged() {
+        let color: Oklch = Srgb::new(0.2f32, 0.4, 0.6).into_color();
+        let mapped = gamut_map(color);
+        assert!((mapped.red - 0.2).abs() < 1e-3);
+        assert!((mapped.green - 0.4).abs() < 1e-3);
+        assert!((mapped.blue - 0.6).abs() < 1e-3);
+    }
+
+    #[test]
+    fn out_of_gamut_colour_is_mapped_into_range() {
+        let color = Oklch::new(0.7, 0.4, 150.0);
+        assert!(!in_srgb_gamut(color));
+        assert_in_range(gamut_map(color));
+    }
+
+    #[test]
+    fn mapping_preserves_hue() {
+        let color = Oklch::new(0.6, 0.35, 260.0);
+        let mapped: Oklch = gamut_map(color).into_color();
+        let drift = (mapped.hue.into_positive_degrees() - 260.0).abs();
+        assert!(drift < 5.0, "hue drifted by {}", drift);
+    }
+
+    #[test]
+    fn extreme_lightness_maps_to_white_and_black() {
+        assert_eq!(gamut_map(Oklch::new(1.2, 0.1, 0.0)), Srgb::new(1.0, 1.0, 1.0));
+        assert_eq!(gamut_map(Oklch::new(-0.1, 0.1, 0.0)), Srgb::new(0.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,152 @@
+/// A piece of an interpolated (backtick) string.
+#[derive(Debug, PartialEq)]
+pub enum StringPart<'a> {
+    Literal(&'a str),
+
+    /// A `{$Name}`, `{$!Name}` or `{&arg}` placeholder. `span` is relative to
+    /// the start of the string's content and covers the braces.
+    Placeholder { reference: Reference<'a>, span: (usize, usize) },
+}
+
+/// A piece of a string that's built at runtime, because it's joined from one
+/// or more dynamic tokens.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TemplatePart {
+    Text(String),
+    /// The value of the dynamic token with this name, once it has one.
+    Token(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Reference<'a> {
+    Token(&'a str),
+    StaticToken(&'a str),
+    MacroArg(&'a str),
+}
+
+fn parse_reference<'a>(inner: &'a str) -> Option<Reference<'a>> {
+    let inner = inner.trim();
+
+    let (reference, name): (fn(&'a str) -> Reference<'a>, &'a str) =
+        if let Some(name) = inner.strip_prefix("$!") {
+            (Reference::StaticToken, name)
+        } else if let Some(name) = inner.strip_prefix('$') {
+            (Reference::Token, name)
+        } else {
+            (Reference::MacroArg, inner.strip_prefix('&')?)
+        };
+
+    let mut chars = name.chars();
+    let starts_ident = chars.next().is_some_and(|c| c == '_' || c.is_ascii_alphabetic());
+    let is_ident = starts_ident && chars.all(|c| c == '_' || c == '-' || c.is_ascii_alphanumeric());
+
+    is_ident.then(|| reference(name))
+}
+
+/// Splits the content of an interpolated string into literal text and
+/// placeholders. Braces which don't wrap a token or macro argument are kept
+/// as literal text.
+pub fn interpolation_parts(content: &str) -> Vec<StringPart<'_>> {
+    let mut parts = vec![];
+    let mut literal_start = 0;
+    let mut cursor = 0;
+
+    while let Some(open) = content[cursor..].find('{').map(|idx| cursor + idx) {
+        let Some(close) = content[open..].find('}').map(|idx| open + idx) else { break };
+
+        let Some(reference) = parse_reference(&content[open + 1..close]) else {
+            cursor = open + 1;
+            continue;
+        };
+
+        if literal_start < open {
+            parts.push(StringPart::Literal(&content[literal_start..open]));
+        }
+        parts.push(StringPart::Placeholder { reference, span: (open, close + 1) });
+
+        literal_start = close + 1;
+        cursor = close + 1;
+    }
+
+    if literal_start < content.len() {
+        parts.push(StringPart::Literal(&content[literal_start..]));
+    }
+
+    parts
+}
+
+/// The `$!Name` and `&arg` references spliced into a selector tag such as
+/// `p-$!size`, with their spans within the tag. A name ends at the next `-`,
+/// so `$!size-lg` refers to `$!size`.
+pub fn tag_references<'a>(tag: &'a str) -> Vec<(Reference<'a>, (usize, usize))> {
+    let mut refs = vec![];
+    let mut cursor = 0;
+
+    while let Some(start) = tag[cursor..].find(['$', '&']).map(|idx| cursor + idx) {
+        let (reference, name_start): (fn(&'a str) -> Reference<'a>, usize) =
+            if tag[start..].starts_with("$!") {
+                (Reference::StaticToken, start + 2)
+            } else if tag[start..].starts_with('&') {
+                (Reference::MacroArg, start + 1)
+            } else {
+                cursor = start + 1;
+                continue;
+            };
+
+        let name_end = tag[name_start..]
+            .find(|c: char| c != '_' && !c.is_ascii_alphanumeric())
+            .map(|idx| name_start + idx)
+            .unwrap_or(tag.len());
+
+        refs.push((reference(&tag[name_start..name_end]), (start, name_end)));
+        cursor = name_end;
+    }
+
+    refs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_placeholders_from_text() {
+        assert_eq!(
+            interpolation_parts("Hello {$Name}, {$!Count} left"),
+            vec![
+                StringPart::Literal("Hello "),
+                StringPart::Placeholder { reference: Reference::Token("Name"), span: (6, 13) },
+                StringPart::Literal(", "),
+                StringPart::Placeholder {
+                    reference: Reference::StaticToken("Count"),
+                    span: (15, 24),
+                },
+                StringPart::Literal(" left"),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_references_in_tags() {
+        assert_eq!(
+            tag_references("p-$!size-lg"),
+            vec![(Reference::StaticToken("size"), (2, 8))]
+        );
+        assert_eq!(
+            tag_references("$!a&b"),
+            vec![(Reference::StaticToken("a"), (0, 3)), (Reference::MacroArg("b"), (3, 5))]
+        );
+        assert_eq!(tag_references("Primary"), vec![]);
+    }
+
+    #[test]
+    fn keeps_unrecognised_braces_literal() {
+        assert_eq!(
+            interpolation_parts("{not a token} {&arg}"),
+            vec![
+                StringPart::Literal("{not a token} "),
+                StringPart::Placeholder { reference: Reference::MacroArg("arg"), span: (14, 20) },
+            ]
+        );
+    }
+}
@@ -1,4 +1,6 @@
 use crate::datatype::Datatype;
+use crate::datatype::palette::{NO_PALETTES, Palettes};
+use crate::parser::types::Delimited;
 
 pub trait StaticLookup {
     fn resolve_static(&self, name: &str) -> Datatype;
@@ -6,4 +8,27 @@ pub trait StaticLookup {
     fn resolve_macro_arg(&self, _name: &str, _key: Option<&str>) -> Option<Datatype> {
         None
     }
+    /// Whether `resolve_dynamic` gives the value a dynamic token will have.
+    /// When it doesn't, strings joined from dynamic tokens are left as
+    /// templates for the runtime to build.
+    fn resolves_dynamic_values(&self) -> bool {
+        false
+    }
+    /// The user palettes colours like `brand:primary:500` are looked up in.
+    fn palettes(&self) -> &Palettes {
+        &NO_PALETTES
+    }
+    /// Evaluates every value collected by the `&...name` argument.
+    fn resolve_macro_rest_arg(&self, _name: &str) -> Option<Vec<Datatype>> {
+        None
+    }
+    /// Expands a call to a `-> Datatype` macro and evaluates its body.
+    fn resolve_macro_call(
+        &self,
+        _name: &str,
+        _body: &Option<Delimited>,
+        _key: Option<&str>,
+    ) -> Option<Datatype> {
+        None
+    }
 }
@@ -1,14 +1,25 @@
 mod colors;
 mod evaluate;
+mod gamut;
+mod interpolation;
 mod lookup;
 pub mod palette;
 mod tuple;
 mod types;
 mod variants;
 
-pub use evaluate::evaluate_construct;
+pub use evaluate::{
+    evaluate_construct, evaluate_each_values, interpolate_rest_selectors, interpolate_tag,
+};
 #[cfg(feature = "typechecker")]
 pub(crate) use evaluate::shorthand_rebind;
+pub(crate) use gamut::gamut_map;
+#[cfg(feature = "typechecker")]
+pub(crate) use gamut::in_srgb_gamut;
+pub use interpolation::TemplatePart;
+pub(crate) use interpolation::{Reference, tag_references};
+#[cfg(feature = "typechecker")]
+pub(crate) use interpolation::{StringPart, interpolation_parts};
 pub use lookup::StaticLookup;
-pub use types::{Datatype, variant_type_name};
+pub use types::{Datatype, Unit, paired_transparency_property, variant_type_name};
 pub use variants::EnumItemFromNameAndValueName;
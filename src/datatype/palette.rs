@@ -1,13 +1,48 @@
-use std::collections::BTreeMap;
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
 use std::sync::LazyLock;
 
 use crate::datatype::colors::{BRICK_COLORS, CSS_COLORS, SKIN_COLORS, TAILWIND_COLORS};
 
 /// Groups color-palette keys like `"tw:red:500"` into `("red", "500")` pairs
 /// so autocomplete can surface families and shades independently.
-struct TwoLevelIndex {
-    families: Vec<&'static str>,
-    shades_by_family: BTreeMap<&'static str, Vec<&'static str>>,
+#[derive(Clone, Debug)]
+struct TwoLevelIndex<S = &'static str> {
+    families: Vec<S>,
+    shades_by_family: BTreeMap<S, Vec<S>>,
+}
+
+impl<S> Default for TwoLevelIndex<S> {
+    fn default() -> Self {
+        Self { families: Vec::new(), shades_by_family: BTreeMap::new() }
+    }
+}
+
+impl<S: Ord + Clone + Borrow<str>> TwoLevelIndex<S> {
+    fn insert(&mut self, family: S, shade: Option<S>) {
+        if !self.shades_by_family.contains_key(family.borrow()) {
+            self.families.push(family.clone());
+            self.families.sort();
+        }
+
+        let shades = self.shades_by_family.entry(family).or_default();
+
+        if let Some(shade) = shade
+            && !shades.contains(&shade)
+        {
+            shades.push(shade);
+            shades.sort_by_key(|shade| shade.borrow().parse::<u32>().unwrap_or(u32::MAX));
+        }
+    }
+
+    fn shades(&self, family: &str) -> &[S] {
+        self.shades_by_family
+            .get(family)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
 }
 
 impl TwoLevelIndex {
@@ -15,7 +50,7 @@ impl TwoLevelIndex {
     where
         I: Iterator<Item = &'static &'static str>,
     {
-        let mut shades_by_family: BTreeMap<&'static str, Vec<&'static str>> = BTreeMap::new();
+        let mut index = Self::default();
 
         for key in keys {
             let Some(rest) = key.strip_prefix(prefix) else {
@@ -27,21 +62,10 @@ impl TwoLevelIndex {
                 continue;
             };
 
-            let entry = shades_by_family.entry(family).or_default();
-
-            if let Some(shade) = parts.next() {
-                entry.push(shade);
-            }
-        }
-
-        for shades in shades_by_family.values_mut() {
-            shades.sort_by_key(|shade| shade.parse::<u32>().unwrap_or(u32::MAX));
-            shades.dedup();
+            index.insert(family, parts.next());
         }
 
-        let families = shades_by_family.keys().copied().collect();
-
-        Self { families, shades_by_family }
+        index
     }
 }
 
@@ -82,11 +106,7 @@ pub fn tailwind_families() -> &'static [&'static str] {
 }
 
 pub fn tailwind_shades(family: &str) -> &'static [&'static str] {
-    TAILWIND_INDEX
-        .shades_by_family
-        .get(family)
-        .map(Vec::as_slice)
-        .unwrap_or(&[])
+    TAILWIND_INDEX.shades(family)
 }
 
 pub fn skin_families() -> &'static [&'static str] {
@@ -94,11 +114,7 @@ pub fn skin_families() -> &'static [&'static str] {
 }
 
 pub fn skin_shades(family: &str) -> &'static [&'static str] {
-    SKIN_INDEX
-        .shades_by_family
-        .get(family)
-        .map(Vec::as_slice)
-        .unwrap_or(&[])
+    SKIN_INDEX.shades(family)
 }
 
 pub fn brick_names() -> &'static [&'static str] {
@@ -109,6 +125,180 @@ pub fn css_names() -> &'static [&'static str] {
     &CSS_INDEX.names
 }
 
+/// Namespaces compiled into the crate, which user palettes may not shadow.
+const BUILTIN_NAMESPACES: [&str; 4] = ["tw", "skin", "bc", "css"];
+
+#[derive(Debug)]
+pub enum PaletteError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    /// The file wasn't an object of `"namespace:family:shade": [l, a, b]` entries.
+    InvalidEntry { key: String },
+    ReservedNamespace { namespace: String },
+}
+
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "Failed to read palette: {}", err),
+            Self::Json(err) => write!(f, "Failed to parse palette: {}", err),
+            Self::InvalidEntry { key } => write!(
+                f,
+                "Palette entry \"{}\" must be keyed `namespace:family:shade` and hold an [l, a, b] OKLab array",
+                key
+            ),
+            Self::ReservedNamespace { namespace } => {
+                write!(f, "The \"{}\" palette namespace is built in", namespace)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PaletteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct UserPalette {
+    colors: HashMap<String, ::palette::Oklab>,
+    index: TwoLevelIndex<String>,
+}
+
+/// Colour palettes a project brings alongside the built in ones, keyed by
+/// namespace. They're handed to the typechecker through a luaurc and to the
+/// compiler directly, so each run only sees the palettes it was given.
+#[derive(Clone, Debug, Default)]
+pub struct Palettes {
+    namespaces: BTreeMap<String, UserPalette>,
+}
+
+/// Used when no palettes are given.
+pub(crate) static NO_PALETTES: Palettes = Palettes::new();
+
+fn parse_palette_key(key: &str) -> Option<(&str, &str, &str)> {
+    let mut parts = key.splitn(3, ':');
+    let (namespace, family, shade) = (parts.next()?, parts.next()?, parts.next()?);
+
+    let is_ident = |part: &str| {
+        part.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+            && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+
+    let is_shade = !shade.is_empty() && shade.chars().all(|c| c.is_ascii_digit());
+
+    (is_ident(namespace) && is_ident(family) && is_shade).then_some((namespace, family, shade))
+}
+
+impl Palettes {
+    pub const fn new() -> Self {
+        Self { namespaces: BTreeMap::new() }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, PaletteError> {
+        let mut palettes = Self::new();
+        palettes.add_json(json)?;
+        Ok(palettes)
+    }
+
+    /// Adds every colour in a JSON palette shaped like `colors/tailwind.json`,
+    /// e.g. `{ "brand:primary:500": [0.62, 0.19, -0.05] }`. Colours in a
+    /// namespace which is already loaded are merged into it, replacing any
+    /// with the same key. Nothing is added if any entry is invalid.
+    pub fn add_json(&mut self, json: &str) -> Result<(), PaletteError> {
+        let parsed = serde_json::from_str(json).map_err(PaletteError::Json)?;
+        self.add_entries(parsed)
+    }
+
+    pub fn add_file(&mut self, path: &Path) -> Result<(), PaletteError> {
+        let contents = std::fs::read_to_string(path).map_err(PaletteError::Io)?;
+        self.add_json(&contents)
+    }
+
+    pub(crate) fn add_entries(
+        &mut self,
+        entries: BTreeMap<String, [f32; 3]>,
+    ) -> Result<(), PaletteError> {
+        let mut parsed = Vec::with_capacity(entries.len());
+
+        for (key, [l, a, b]) in entries {
+            let key = key.to_lowercase();
+            let Some((namespace, family, shade)) = parse_palette_key(&key) else {
+                return Err(PaletteError::InvalidEntry { key });
+            };
+
+            if BUILTIN_NAMESPACES.contains(&namespace) {
+                return Err(PaletteError::ReservedNamespace { namespace: namespace.to_string() });
+            }
+
+            parsed.push((
+                namespace.to_string(),
+                family.to_string(),
+                shade.to_string(),
+                key.clone(),
+                ::palette::Oklab::new(l, a, b),
+            ));
+        }
+
+        for (namespace, family, shade, key, color) in parsed {
+            let palette = self.namespaces.entry(namespace).or_default();
+            palette.colors.insert(key, color);
+            palette.index.insert(family, Some(shade));
+        }
+
+        Ok(())
+    }
+
+    pub fn remove(&mut self, namespace: &str) {
+        self.namespaces.remove(&namespace.to_lowercase());
+    }
+
+    /// Looks up a colour like `brand:primary:500`.
+    pub fn color(&self, key: &str) -> Option<::palette::Oklab> {
+        let key = key.to_lowercase();
+        let (namespace, ..) = parse_palette_key(&key)?;
+
+        self.namespaces.get(namespace)?.colors.get(&key).copied()
+    }
+
+    /// The namespaces of the loaded palettes, not including the built in ones.
+    pub fn namespaces(&self) -> impl Iterator<Item = &str> {
+        self.namespaces.keys().map(String::as_str)
+    }
+
+    /// The families in a namespace, which may be a built in one with families
+    /// such as `tw` or `skin`.
+    pub fn families(&self, namespace: &str) -> Vec<&str> {
+        match namespace {
+            "tw" => tailwind_families().to_vec(),
+            "skin" => skin_families().to_vec(),
+            namespace => self
+                .namespaces
+                .get(namespace)
+                .map(|palette| palette.index.families.iter().map(String::as_str).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The shades of a family, lightest first.
+    pub fn shades(&self, namespace: &str, family: &str) -> Vec<&str> {
+        match namespace {
+            "tw" => tailwind_shades(family).to_vec(),
+            "skin" => skin_shades(family).to_vec(),
+            namespace => self
+                .namespaces
+                .get(namespace)
+                .map(|palette| palette.index.shades(family).iter().map(String::as_str).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -142,4 +332,38 @@ mod tests {
     fn brick_has_white() {
         assert!(brick_names().contains(&"white"));
     }
+
+    #[test]
+    fn user_palette_is_indexed_and_resolvable() {
+        let palettes = Palettes::from_json(
+            r#"{ "acme:primary:500": [0.6, 0.1, -0.1], "acme:primary:100": [0.9, 0.02, -0.02] }"#,
+        )
+        .unwrap();
+
+        assert_eq!(palettes.namespaces().collect::<Vec<_>>(), vec!["acme"]);
+        assert_eq!(palettes.families("acme"), vec!["primary"]);
+        assert_eq!(palettes.shades("acme", "primary"), vec!["100", "500"]);
+        assert!(palettes.color("ACME:Primary:500").is_some());
+        assert!(palettes.color("acme:primary:900").is_none());
+        assert!(Palettes::new().color("acme:primary:500").is_none());
+    }
+
+    #[test]
+    fn builtin_families_are_indexed_alongside_user_palettes() {
+        let palettes = Palettes::new();
+        assert!(palettes.families("tw").contains(&"red"));
+        assert!(palettes.shades("tw", "red").contains(&"500"));
+    }
+
+    #[test]
+    fn user_palette_rejects_builtin_namespace() {
+        let result = Palettes::from_json(r#"{ "tw:brand:500": [0.6, 0.1, -0.1] }"#);
+        assert!(matches!(result, Err(PaletteError::ReservedNamespace { .. })));
+    }
+
+    #[test]
+    fn user_palette_rejects_key_without_shade() {
+        let result = Palettes::from_json(r#"{ "acme2:primary": [0.6, 0.1, -0.1] }"#);
+        assert!(matches!(result, Err(PaletteError::InvalidEntry { .. })));
+    }
 }
@@ -1,7 +1,7 @@
-use palette::{FromColor, Srgb};
+use palette::Srgb;
 use rbx_types::{Color3, Color3uint8, Variant};
 
-use crate::datatype::Datatype;
+use crate::datatype::{Datatype, gamut_map};
 
 use crate::datatype::tuple::tuple_annotations::coerce_datatype_to_f64;
 
@@ -13,10 +13,10 @@ pub fn color3_annotation(datatypes: &Vec<Datatype>) -> Datatype {
     } else if let Some(Datatype::Variant(Variant::Color3uint8(color))) = first {
         Datatype::Variant(Variant::Color3((*color).into()))
     } else if let Some(Datatype::Oklab(color)) = first {
-        let color: Srgb<f32> = Srgb::from_color(*color);
+        let color = gamut_map(*color);
         Datatype::Variant(Variant::Color3(Color3::new(color.red, color.green, color.blue)))
     } else if let Some(Datatype::Oklch(color)) = first {
-        let color: Srgb<f32> = Srgb::from_color(*color);
+        let color = gamut_map(*color);
         Datatype::Variant(Variant::Color3(Color3::new(color.red, color.green, color.blue)))
     } else {
         let red = coerce_datatype_to_f64(first, 0.0);
@@ -39,12 +39,12 @@ pub fn rgb_annotation(datatypes: &Vec<Datatype>) -> Datatype {
     } else if let Some(Datatype::Variant(Variant::Color3uint8(color))) = first {
         Datatype::Variant(Variant::Color3((*color).into()))
     } else if let Some(Datatype::Oklab(color)) = first {
-        let color: Srgb<u8> = Srgb::from_color(*color).into();
+        let color: Srgb<u8> = gamut_map(*color).into_format();
         Datatype::Variant(Variant::Color3(
             Color3uint8::new(color.red, color.green, color.blue).into(),
         ))
     } else if let Some(Datatype::Oklch(color)) = first {
-        let color: Srgb<u8> = Srgb::from_color(*color).into();
+        let color: Srgb<u8> = gamut_map(*color).into_format();
         Datatype::Variant(Variant::Color3(
             Color3uint8::new(color.red, color.green, color.blue).into(),
         ))
@@ -0,0 +1,135 @@
+use palette::{IntoColor, Mix, Oklch, Srgb};
+use rbx_types::Variant;
+
+use crate::datatype::Datatype;
+use crate::datatype::tuple::tuple_annotations::coerce_datatype_to_f64;
+
+/// Chroma rarely exceeds 0.4 for displayable colours, so percentages passed to
+/// `saturate`/`desaturate` are scaled against that rather than against 1.
+const MAX_CHROMA: f64 = 0.4;
+
+fn datatype_to_oklch(datatype: Option<&Datatype>) -> Option<Oklch> {
+    match datatype? {
+        Datatype::Oklch(color) => Some(*color),
+        Datatype::Oklab(color) => Some((*color).into_color()),
+        Datatype::Variant(Variant::Color3(color)) => {
+            Some(Srgb::new(color.r, color.g, color.b).into_color())
+        }
+        Datatype::Variant(Variant::Color3uint8(color)) => Some(
+            Srgb::new(color.r as f32 / 255.0, color.g as f32 / 255.0, color.b as f32 / 255.0)
+                .into_color(),
+        ),
+        Datatype::Variant(Variant::BrickColor(brick_color)) => {
+            let color = brick_color.to_color3uint8();
+            datatype_to_oklch(Some(&Datatype::Variant(Variant::Color3uint8(color))))
+        }
+        Datatype::Translucent(color, _) => datatype_to_oklch(Some(color)),
+        _ => None,
+    }
+}
+
+/// Keeps the transparency of a translucent `source` colour on its adjusted
+/// `color`.
+fn keep_transparency(source: Option<&Datatype>, color: Datatype) -> Datatype {
+    match source {
+        Some(Datatype::Translucent(_, transparency)) => {
+            Datatype::Translucent(Box::new(color), *transparency)
+        }
+        _ => color,
+    }
+}
+
+/// Reads an amount given either as a number or a percentage, scaling
+/// percentages to `full`.
+fn coerce_amount(datatype: Option<&Datatype>, full: f64) -> f64 {
+    match datatype {
+        Some(Datatype::Variant(Variant::UDim(udim))) => udim.scale as f64 * full,
+        _ => coerce_datatype_to_f64(datatype, 0.0),
+    }
+}
+
+fn adjust(
+    datatypes: &Vec<Datatype>,
+    f: impl FnOnce(Oklch, &Vec<Datatype>) -> Oklch,
+) -> Datatype {
+    match datatype_to_oklch(datatypes.get(0)) {
+        Some(color) => keep_transparency(datatypes.get(0), Datatype::Oklch(f(color, datatypes))),
+        None => Datatype::None,
+    }
+}
+
+fn with_lightness(color: Oklch, lightness: f64) -> Oklch {
+    Oklch::new(lightness.clamp(0.0, 1.0) as f32, color.chroma, color.hue)
+}
+
+fn with_chroma(color: Oklch, chroma: f64) -> Oklch {
+    Oklch::new(color.l, chroma.max(0.0) as f32, color.hue)
+}
+
+fn with_hue_shift(color: Oklch, degrees: f64) -> Oklch {
+    let hue = color.hue.into_degrees() as f64 + degrees;
+    Oklch::new(color.l, color.chroma, hue as f32)
+}
+
+pub fn lighten_annotation(datatypes: &Vec<Datatype>) -> Datatype {
+    adjust(datatypes, |color, args| {
+        with_lightness(color, color.l as f64 + coerce_amount(args.get(1), 1.0))
+    })
+}
+
+pub fn darken_annotation(datatypes: &Vec<Datatype>) -> Datatype {
+    adjust(datatypes, |color, args| {
+        with_lightness(color, color.l as f64 - coerce_amount(args.get(1), 1.0))
+    })
+}
+
+pub fn saturate_annotation(datatypes: &Vec<Datatype>) -> Datatype {
+    adjust(datatypes, |color, args| {
+        with_chroma(color, color.chroma as f64 + coerce_amount(args.get(1), MAX_CHROMA))
+    })
+}
+
+pub fn desaturate_annotation(datatypes: &Vec<Datatype>) -> Datatype {
+    adjust(datatypes, |color, args| {
+        with_chroma(color, color.chroma as f64 - coerce_amount(args.get(1), MAX_CHROMA))
+    })
+}
+
+pub fn hue_rotate_annotation(datatypes: &Vec<Datatype>) -> Datatype {
+    adjust(datatypes, |color, args| {
+        with_hue_shift(color, coerce_datatype_to_f64(args.get(1), 0.0))
+    })
+}
+
+pub fn complement_annotation(datatypes: &Vec<Datatype>) -> Datatype {
+    adjust(datatypes, |color, _| with_hue_shift(color, 180.0))
+}
+
+pub fn mix_annotation(datatypes: &Vec<Datatype>) -> Datatype {
+    let Some(from) = datatype_to_oklch(datatypes.get(0)) else {
+        return Datatype::None;
+    };
+    let Some(to) = datatype_to_oklch(datatypes.get(1)) else {
+        return keep_transparency(datatypes.get(0), Datatype::Oklch(from));
+    };
+    let time = match datatypes.get(2) {
+        Some(time) => coerce_amount(Some(time), 1.0),
+        None => 0.5,
+    };
+
+    let mixed = from.mix(to, time.clamp(0.0, 1.0) as f32);
+    keep_transparency(datatypes.get(0), Datatype::Oklch(mixed))
+}
+
+/// `alpha(color, opacity)` makes a colour translucent, so assigning it to a
+/// colour property also sets the paired transparency property.
+pub fn alpha_annotation(datatypes: &Vec<Datatype>) -> Datatype {
+    let color = match datatypes.get(0) {
+        Some(Datatype::Translucent(color, _)) => (**color).clone(),
+        Some(color) if datatype_to_oklch(Some(color)).is_some() => color.clone(),
+        _ => return Datatype::None,
+    };
+    let opacity = coerce_amount(datatypes.get(1), 1.0).clamp(0.0, 1.0);
+
+    Datatype::Translucent(Box::new(color), 1.0 - opacity)
+}
@@ -0,0 +1,52 @@
+use palette::{FromColor, Hsl, Hsv, Srgb};
+use rbx_types::{Color3, Variant};
+
+use crate::datatype::Datatype;
+use crate::datatype::tuple::tuple_annotations::coerce_datatype_to_f64;
+
+/// Saturation, lightness and value may be written as either `50%` or `0.5`.
+fn coerce_unit(datatype: Option<&Datatype>) -> f32 {
+    match datatype {
+        Some(Datatype::Variant(Variant::UDim(udim))) => udim.scale,
+        _ => coerce_datatype_to_f64(datatype, 0.0) as f32,
+    }
+    .clamp(0.0, 1.0)
+}
+
+fn to_color3(color: Srgb<f32>) -> Datatype {
+    Datatype::Variant(Variant::Color3(Color3::new(color.red, color.green, color.blue)))
+}
+
+/// Pairs a colour with the transparency derived from an alpha argument, as
+/// Roblox keeps the two in separate properties.
+fn with_transparency(color: Datatype, alpha: Option<&Datatype>) -> Datatype {
+    Datatype::Translucent(Box::new(color), 1.0 - coerce_unit(alpha) as f64)
+}
+
+fn hsl_color(datatypes: &Vec<Datatype>) -> Datatype {
+    let hue = coerce_datatype_to_f64(datatypes.get(0), 0.0) as f32;
+    let color = Hsl::new(hue, coerce_unit(datatypes.get(1)), coerce_unit(datatypes.get(2)));
+    to_color3(Srgb::from_color(color))
+}
+
+fn hsv_color(datatypes: &Vec<Datatype>) -> Datatype {
+    let hue = coerce_datatype_to_f64(datatypes.get(0), 0.0) as f32;
+    let color = Hsv::new(hue, coerce_unit(datatypes.get(1)), coerce_unit(datatypes.get(2)));
+    to_color3(Srgb::from_color(color))
+}
+
+pub fn hsl_annotation(datatypes: &Vec<Datatype>) -> Datatype {
+    hsl_color(datatypes)
+}
+
+pub fn hsla_annotation(datatypes: &Vec<Datatype>) -> Datatype {
+    with_transparency(hsl_color(datatypes), datatypes.get(3))
+}
+
+pub fn hsv_annotation(datatypes: &Vec<Datatype>) -> Datatype {
+    hsv_color(datatypes)
+}
+
+pub fn hsva_annotation(datatypes: &Vec<Datatype>) -> Datatype {
+    with_transparency(hsv_color(datatypes), datatypes.get(3))
+}
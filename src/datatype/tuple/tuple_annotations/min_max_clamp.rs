@@ -0,0 +1,50 @@
+use crate::datatype::Datatype;
+use crate::datatype::tuple::tuple_annotations::zip_components;
+
+fn fold_variants(datatypes: &Vec<Datatype>, f: fn(f64, f64) -> f64) -> Datatype {
+    let mut datatypes = datatypes.iter();
+
+    let Some(Datatype::Variant(first)) = datatypes.next() else {
+        return Datatype::None;
+    };
+
+    let mut result = first.clone();
+
+    for datatype in datatypes {
+        let Datatype::Variant(variant) = datatype else {
+            return Datatype::None;
+        };
+
+        let Some(combined) = zip_components(&result, variant, f) else {
+            return Datatype::None;
+        };
+
+        result = combined;
+    }
+
+    Datatype::Variant(result)
+}
+
+pub fn min_annotation(datatypes: &Vec<Datatype>) -> Datatype {
+    fold_variants(datatypes, f64::min)
+}
+
+pub fn max_annotation(datatypes: &Vec<Datatype>) -> Datatype {
+    fold_variants(datatypes, f64::max)
+}
+
+pub fn clamp_annotation(datatypes: &Vec<Datatype>) -> Datatype {
+    let (
+        Some(Datatype::Variant(value)),
+        Some(Datatype::Variant(low)),
+        Some(Datatype::Variant(high)),
+    ) = (datatypes.get(0), datatypes.get(1), datatypes.get(2))
+    else {
+        return Datatype::None;
+    };
+
+    zip_components(value, low, f64::max)
+        .and_then(|value| zip_components(&value, high, f64::min))
+        .map(Datatype::Variant)
+        .unwrap_or(Datatype::None)
+}
@@ -1,6 +1,6 @@
 use phf_macros::phf_map;
 use crate::datatype::Datatype;
-use rbx_types::Variant;
+use rbx_types::{UDim, UDim2, Variant, Vector2, Vector3};
 
 mod udim_udim2;
 use udim_udim2::{udim_annotation, udim2_annotation};
@@ -26,6 +26,9 @@ use oklab::oklab_annotation;
 mod oklch;
 use oklch::oklch_annotation;
 
+mod hsl_hsv;
+use hsl_hsv::{hsl_annotation, hsla_annotation, hsv_annotation, hsva_annotation};
+
 mod brickcolor;
 use brickcolor::brickcolor_annotation;
 
@@ -44,12 +47,24 @@ use font::font_annotation;
 mod content;
 use content::content_annotation;
 
+mod color_adjust;
+use color_adjust::{
+    alpha_annotation, complement_annotation, darken_annotation, desaturate_annotation,
+    hue_rotate_annotation, lighten_annotation, mix_annotation, saturate_annotation,
+};
+
 mod lerp;
 use lerp::lerp_annotation;
 
 mod floor_ceil_round_abs;
 use floor_ceil_round_abs::{abs_annotation, ceil_annotation, floor_annotation, round_annotation};
 
+mod min_max_clamp;
+use min_max_clamp::{clamp_annotation, max_annotation, min_annotation};
+
+mod sqrt_sign_mod;
+use sqrt_sign_mod::{mod_annotation, sign_annotation, sqrt_annotation};
+
 pub(crate) fn extract_datatype_f64(datatype: Option<&Datatype>) -> Option<f64> {
     match datatype {
         Some(Datatype::Variant(Variant::Float64(float64))) => Some(*float64),
@@ -77,6 +92,68 @@ pub(crate) fn coerce_datatype_to_i32(datatype: Option<&Datatype>, default: i32)
     default
 }
 
+fn map_udim(udim: UDim, f: &impl Fn(f64) -> f64) -> UDim {
+    UDim::new(f(udim.scale as f64) as f32, f(udim.offset as f64).round() as i32)
+}
+
+/// Applies `f` to every numeric component of `variant`, returning `None` for
+/// variants which aren't numbers, `UDim`s, `UDim2`s or vectors.
+pub(crate) fn map_components(variant: &Variant, f: impl Fn(f64) -> f64) -> Option<Variant> {
+    Some(match variant {
+        Variant::Float64(n) => Variant::Float64(f(*n)),
+        Variant::UDim(udim) => Variant::UDim(map_udim(*udim, &f)),
+        Variant::UDim2(udim2) => {
+            Variant::UDim2(UDim2::new(map_udim(udim2.x, &f), map_udim(udim2.y, &f)))
+        }
+        Variant::Vector2(vec) => Variant::Vector2(Vector2::new(
+            f(vec.x as f64) as f32,
+            f(vec.y as f64) as f32,
+        )),
+        Variant::Vector3(vec) => Variant::Vector3(Vector3::new(
+            f(vec.x as f64) as f32,
+            f(vec.y as f64) as f32,
+            f(vec.z as f64) as f32,
+        )),
+        _ => return None,
+    })
+}
+
+fn zip_udim(a: UDim, b: UDim, f: &impl Fn(f64, f64) -> f64) -> UDim {
+    UDim::new(
+        f(a.scale as f64, b.scale as f64) as f32,
+        f(a.offset as f64, b.offset as f64).round() as i32,
+    )
+}
+
+/// Combines two variants component by component with `f`. A number paired with
+/// a composite variant is applied to each of its components.
+pub(crate) fn zip_components(
+    a: &Variant,
+    b: &Variant,
+    f: impl Fn(f64, f64) -> f64,
+) -> Option<Variant> {
+    Some(match (a, b) {
+        (Variant::Float64(a), Variant::Float64(b)) => Variant::Float64(f(*a, *b)),
+        (_, Variant::Float64(b)) => return map_components(a, |a| f(a, *b)),
+        (Variant::Float64(a), _) => return map_components(b, |b| f(*a, b)),
+
+        (Variant::UDim(a), Variant::UDim(b)) => Variant::UDim(zip_udim(*a, *b, &f)),
+        (Variant::UDim2(a), Variant::UDim2(b)) => {
+            Variant::UDim2(UDim2::new(zip_udim(a.x, b.x, &f), zip_udim(a.y, b.y, &f)))
+        }
+        (Variant::Vector2(a), Variant::Vector2(b)) => Variant::Vector2(Vector2::new(
+            f(a.x as f64, b.x as f64) as f32,
+            f(a.y as f64, b.y as f64) as f32,
+        )),
+        (Variant::Vector3(a), Variant::Vector3(b)) => Variant::Vector3(Vector3::new(
+            f(a.x as f64, b.x as f64) as f32,
+            f(a.y as f64, b.y as f64) as f32,
+            f(a.z as f64, b.z as f64) as f32,
+        )),
+        _ => return None,
+    })
+}
+
 pub(crate) struct RemapRange {
     pub start: f64,
     pub end: f64,
@@ -105,6 +182,10 @@ pub const TUPLE_ANNOTATIONS: phf::Map<&'static str, fn(&Vec<Datatype>) -> Dataty
     "rgb" => rgb_annotation,
     "oklab" => oklab_annotation,
     "oklch" => oklch_annotation,
+    "hsl" => hsl_annotation,
+    "hsla" => hsla_annotation,
+    "hsv" => hsv_annotation,
+    "hsva" => hsva_annotation,
     "brickcolor" => brickcolor_annotation,
     "colorseq" => colorseq_annotation,
     "numseq" => numseq_annotation,
@@ -116,4 +197,18 @@ pub const TUPLE_ANNOTATIONS: phf::Map<&'static str, fn(&Vec<Datatype>) -> Dataty
     "ceil" => ceil_annotation,
     "round" => round_annotation,
     "abs" => abs_annotation,
+    "min" => min_annotation,
+    "max" => max_annotation,
+    "clamp" => clamp_annotation,
+    "sqrt" => sqrt_annotation,
+    "sign" => sign_annotation,
+    "mod" => mod_annotation,
+    "lighten" => lighten_annotation,
+    "darken" => darken_annotation,
+    "saturate" => saturate_annotation,
+    "desaturate" => desaturate_annotation,
+    "hue_rotate" => hue_rotate_annotation,
+    "mix" => mix_annotation,
+    "complement" => complement_annotation,
+    "alpha" => alpha_annotation,
 };
@@ -0,0 +1,49 @@
+use crate::datatype::Datatype;
+use crate::datatype::tuple::tuple_annotations::{map_components, zip_components};
+
+pub fn sqrt_annotation(datatypes: &Vec<Datatype>) -> Datatype {
+    let Some(Datatype::Variant(variant)) = datatypes.get(0) else {
+        return Datatype::None;
+    };
+
+    // Negative components would produce NaN, so they're treated as zero.
+    map_components(variant, |n| n.max(0.0).sqrt())
+        .map(Datatype::Variant)
+        .unwrap_or(Datatype::None)
+}
+
+pub fn sign_annotation(datatypes: &Vec<Datatype>) -> Datatype {
+    let Some(Datatype::Variant(variant)) = datatypes.get(0) else {
+        return Datatype::None;
+    };
+
+    let sign = |n: f64| {
+        if n > 0.0 {
+            1.0
+        } else if n < 0.0 {
+            -1.0
+        } else {
+            0.0
+        }
+    };
+
+    map_components(variant, sign)
+        .map(Datatype::Variant)
+        .unwrap_or(Datatype::None)
+}
+
+/// Floored modulo, matching Luau's `%`. Taking a component modulo zero leaves
+/// it unchanged rather than producing NaN.
+pub fn mod_annotation(datatypes: &Vec<Datatype>) -> Datatype {
+    let (Some(Datatype::Variant(value)), Some(Datatype::Variant(divisor))) =
+        (datatypes.get(0), datatypes.get(1))
+    else {
+        return Datatype::None;
+    };
+
+    let floored_mod = |a: f64, b: f64| if b == 0.0 { a } else { a - (a / b).floor() * b };
+
+    zip_components(value, divisor, floored_mod)
+        .map(Datatype::Variant)
+        .unwrap_or(Datatype::None)
+}
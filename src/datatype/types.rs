@@ -1,7 +1,10 @@
-use palette::{IntoColor, Oklab, Oklch, Srgb};
+use palette::{Oklab, Oklch};
 use rbx_types::{Color3, EnumItem, Variant, VariantType};
 
+use crate::datatype::gamut_map;
+use crate::datatype::interpolation::TemplatePart;
 use crate::datatype::variants::EnumItemFromNameAndValueName;
+use crate::types::LanguageMode;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Datatype {
@@ -10,9 +13,49 @@ pub enum Datatype {
     IncompleteEnumShorthand(String),
     Oklab(Oklab),
     Oklch(Oklch),
+    /// A colour along with the transparency its alpha implies. Roblox keeps
+    /// the two in separate properties, so assigning it to a colour property
+    /// also sets the paired transparency property.
+    Translucent(Box<Datatype>, f64),
+    /// A string joined from dynamic tokens, which only have a value at
+    /// runtime. The compiler hands it to the runtime to build rather than
+    /// building it itself.
+    StringTemplate(Vec<TemplatePart>),
+    /// A number written with a unit suffix, kept apart from plain numbers so
+    /// it can be checked against what it's assigned to.
+    Measure(f64, Unit),
     None,
 }
 
+/// What a number written with a unit suffix measures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unit {
+    /// In degrees, from `deg` or `rad`.
+    Angle,
+    /// In seconds, from `s` or `ms`.
+    Duration,
+}
+
+impl Unit {
+    /// The unit a property's value is measured in, going by its name.
+    pub fn of_property(name: &str) -> Option<Unit> {
+        if name.ends_with("Rotation") || name.ends_with("Angle") {
+            Some(Unit::Angle)
+        } else if name.ends_with("Duration") || name.ends_with("Delay") {
+            Some(Unit::Duration)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Unit::Angle => "angle",
+            Unit::Duration => "duration",
+        }
+    }
+}
+
 // Datatype contains floats (via rbx_types::Variant, Oklab, Oklch) that do not
 // legitimately produce NaN from parsed RSML input, so PartialEq is effectively
 // reflexive in practice. Eq is required for rangemap coalescing of Definitions.
@@ -20,12 +63,21 @@ impl Eq for Datatype {}
 
 impl Datatype {
     pub fn coerce_to_variant(self, key: Option<&str>) -> Option<Variant> {
+        self.coerce_to_variant_in(key, LanguageMode::Nonstrict)
+    }
+
+    /// Same as [`Datatype::coerce_to_variant`], except that under
+    /// [`LanguageMode::Strict`] a tuple is never implicitly collapsed to its
+    /// first element.
+    pub fn coerce_to_variant_in(self, key: Option<&str>, mode: LanguageMode) -> Option<Variant> {
         match self {
             Datatype::Variant(variant) => Some(variant),
 
+            Datatype::TupleData(_) if mode == LanguageMode::Strict => None,
+
             Datatype::TupleData(tuple_data) => {
                 if !tuple_data.is_empty() {
-                    tuple_data[0].to_owned().coerce_to_variant(key)
+                    tuple_data[0].to_owned().coerce_to_variant_in(key, mode)
                 } else {
                     None
                 }
@@ -38,7 +90,7 @@ impl Datatype {
             }
 
             Datatype::Oklab(color) => {
-                let color: Srgb<f32> = color.into_color();
+                let color = gamut_map(color);
                 Some(Variant::Color3(Color3::new(
                     color.red,
                     color.green,
@@ -46,7 +98,7 @@ impl Datatype {
                 )))
             }
             Datatype::Oklch(color) => {
-                let color: Srgb<f32> = color.into_color();
+                let color = gamut_map(color);
                 Some(Variant::Color3(Color3::new(
                     color.red,
                     color.green,
@@ -54,7 +106,11 @@ impl Datatype {
                 )))
             }
 
-            Datatype::None => None,
+            Datatype::Translucent(color, _) => color.coerce_to_variant_in(key, mode),
+
+            Datatype::Measure(value, _) => Some(Variant::Float64(value)),
+
+            Datatype::StringTemplate(_) | Datatype::None => None,
         }
     }
 
@@ -70,6 +126,35 @@ impl Datatype {
         }
     }
 
+    pub fn unit(&self) -> Option<Unit> {
+        match self {
+            Datatype::Measure(_, unit) => Some(*unit),
+            _ => None,
+        }
+    }
+
+    /// The same value with its unit dropped, for the places that take plain
+    /// numbers such as annotation arguments.
+    pub fn without_unit(self) -> Datatype {
+        match self {
+            Datatype::Measure(value, _) => Datatype::Variant(Variant::Float64(value)),
+            datatype => datatype,
+        }
+    }
+
+    /// Returns the first `IncompleteEnumShorthand` variant nested inside a
+    /// tuple, where no key is available to pin down its enum.
+    pub fn nested_enum_shorthand(&self) -> Option<&str> {
+        let Datatype::TupleData(items) = self else {
+            return None;
+        };
+
+        items.iter().find_map(|item| match item {
+            Datatype::IncompleteEnumShorthand(variant) => Some(variant.as_str()),
+            other => other.nested_enum_shorthand(),
+        })
+    }
+
     pub fn type_name(&self) -> String {
         match self {
             Datatype::Variant(variant) => variant_type_name(variant.ty()).to_string(),
@@ -80,11 +165,23 @@ impl Datatype {
             Datatype::IncompleteEnumShorthand(_) => "EnumItem".to_string(),
             Datatype::Oklab(_) => "Oklab".to_string(),
             Datatype::Oklch(_) => "Oklch".to_string(),
+            Datatype::Translucent(color, _) => color.type_name(),
+            Datatype::StringTemplate(_) => "string".to_string(),
+            Datatype::Measure(_, unit) => unit.name().to_string(),
             Datatype::None => "unknown".to_string(),
         }
     }
 }
 
+/// The property which holds the transparency of the colour property `name`,
+/// such as `BackgroundTransparency` for `BackgroundColor3`.
+pub fn paired_transparency_property(name: &str) -> Option<String> {
+    match name {
+        "Color" => Some("Transparency".to_string()),
+        _ => name.strip_suffix("Color3").map(|prefix| format!("{}Transparency", prefix)),
+    }
+}
+
 pub fn variant_type_name(ty: VariantType) -> &'static str {
     match ty {
         VariantType::Axes => "Axes",
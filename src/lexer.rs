@@ -42,6 +42,21 @@ impl<'a> Iterator for RsmlLexer<'a> {
                     Some(SpannedToken::new(span.0, token, span.1))
                 }
 
+                // Palette colours are picked out here rather than by a regex,
+                // since logos can't backtrack out of `Frame:Hover` once it
+                // starts matching a `namespace:family:shade` pattern.
+                Token::Identifier(namespace) => {
+                    match palette_color_len(namespace, self.token_stream.remainder()) {
+                        Some(len) => {
+                            self.token_stream.bump(len);
+                            let end = span.end + len;
+                            let color = &self.token_stream.source()[span.start..end];
+                            Some(SpannedToken::new(span.start, Token::ColorPalette(color), end))
+                        }
+                        None => Some(SpannedToken::new(span.start, token, span.end)),
+                    }
+                }
+
                 _ => Some(SpannedToken::new(span.start, token, span.end)),
             },
 
@@ -83,12 +98,36 @@ fn str_to_option(str: &str) -> Option<&str> {
     if str.len() == 0 { None } else { Some(str) }
 }
 
+/// The length of the `:family:shade` which follows `namespace` in
+/// `remainder`, if the two form a palette colour such as `brand:primary:500`.
+fn palette_color_len(namespace: &str, remainder: &str) -> Option<usize> {
+    let is_name = |name: &str| {
+        name.starts_with(|c: char| c.is_ascii_alphabetic())
+            && name.chars().all(|c| c == '_' || c.is_ascii_alphanumeric())
+    };
+    if !is_name(namespace) {
+        return None;
+    }
+
+    let family_len = remainder.strip_prefix(':')?.find(':')?;
+    if !is_name(&remainder[1..=family_len]) {
+        return None;
+    }
+
+    let shade = &remainder[family_len + 2..];
+    let shade_len = shade.find(|c: char| !c.is_ascii_digit()).unwrap_or(shade.len());
+    (shade_len > 0).then_some(family_len + 2 + shade_len)
+}
+
 #[derive(Logos, Clone, Debug, PartialEq, EnumKind)]
 #[enum_kind(TokenKind, derive(Hash))]
 #[logos(skip r"[ \t\n\r\f]+")]
 #[logos(subpattern ident = r"[_A-Za-z][_A-Za-z\d]*|[_A-Za-z]+(-[A-Za-z\d_]+)+")]
+#[logos(subpattern tag = r"(\$!|&)?[_A-Za-z][_A-Za-z\d]*((-|-?\$!|-?&)[_A-Za-z\d]+)*")]
 #[logos(subpattern numsect = r"_*[\d]+_*")]
-#[logos(subpattern num = r"((?&numsect)+\.)?(?&numsect)+|\.(?&numsect)")]
+#[logos(subpattern exponent = r"[eE][+-]?(?&numsect)+")]
+#[logos(subpattern decimal = r"(((?&numsect)+\.)?(?&numsect)+|\.(?&numsect))(?&exponent)?")]
+#[logos(subpattern num = r"(?&decimal)|0[xX][_\da-fA-F]*[\da-fA-F][_\da-fA-F]*|0[bB][_01]*[01][_01]*")]
 pub enum Token<'a> {
     #[regex(r"\-\-!.*", priority = 100, callback = |lex| lex.slice()[3..].trim())]
     Directive(&'a str),
@@ -114,6 +153,15 @@ pub enum Token<'a> {
     #[token("@tween")]
     TweenDeclaration,
 
+    #[token("@if")]
+    IfDeclaration,
+
+    #[token("@else")]
+    ElseDeclaration,
+
+    #[token("@each")]
+    EachDeclaration,
+
     #[regex(r"@(?&ident)", callback = |lex| str_to_option(&lex.slice()[1..]))]
     QuerySelector(&'a str),
 
@@ -126,9 +174,13 @@ pub enum Token<'a> {
     #[regex(r"(?&ident)")]
     Identifier(&'a str),
 
+    /// A bare `&` in a selector refers to the enclosing rule's selector.
     #[regex(r"&(?&ident)?", callback = |lex| str_to_option(&lex.slice()[1..]))]
     MacroArgIdentifier(Option<&'a str>),
 
+    #[regex(r"&\.\.\.(?&ident)?", callback = |lex| str_to_option(&lex.slice()[4..]))]
+    MacroRestArgIdentifier(Option<&'a str>),
+
     #[regex(r"(?&ident)!", callback = |lex| str_to_option(&lex.slice().clip(0, 1)))]
     MacroCallIdentifier(Option<&'a str>),
 
@@ -141,10 +193,13 @@ pub enum Token<'a> {
     #[token(";")]
     SemiColon,
 
-    #[regex(r"#(?&ident)", callback = |lex| str_to_option(&lex.slice()[1..]))]
+    /// Names may splice in static tokens and macro arguments, like tags.
+    #[regex(r"#(?&tag)", callback = |lex| str_to_option(&lex.slice()[1..]))]
     NameSelector(&'a str),
 
-    #[regex(r"\.(?&ident)?", callback = |lex| str_to_option(&lex.slice()[1..]))]
+    /// Tags may splice in static tokens and macro arguments, as in
+    /// `.p-$!size` and `.p-&size`.
+    #[regex(r"\.(?&tag)?", callback = |lex| str_to_option(&lex.slice()[1..]))]
     TagSelectorOrEnumPart(Option<&'a str>),
 
     #[regex(r":(?&ident)?", callback = |lex| str_to_option(&lex.slice()[1..]))]
@@ -153,6 +208,9 @@ pub enum Token<'a> {
     #[regex(r"::(?&ident)", callback = |lex| str_to_option(&lex.slice()[2..]))]
     PseudoSelector(&'a str),
 
+    #[regex(r#"\[[ \t]*(?&ident)[ \t]*(=([^\]\n"']|"([^"\\\n]|\\.)*"|'([^'\\\n]|\\.)*')*)?\]"#, callback = attribute_selector_callback)]
+    AttributeSelector(AttributeSelector<'a>),
+
     #[token("->")]
     ReturnArrow,
 
@@ -195,13 +253,25 @@ pub enum Token<'a> {
     #[token("-")]
     OpSub,
 
+    #[token("..")]
+    OpConcat,
+
+    #[token("==")]
+    OpEq,
+
+    #[token("~=")]
+    OpNotEq,
+
     #[regex(r"\[=*\[", priority = 98, callback = |lex| multiline_string_block_callback(lex, 0))]
     StringMulti(MultilineString<'a>),
 
-    #[regex(r#""[^\"\n\t]*""#, callback = |lex| lex.slice().clip(1, 1))]
-    #[regex(r#"'[^\'\n\t]*'"#, callback = |lex| lex.slice().clip(1, 1))]
+    #[regex(r#""([^"\\\n\t]|\\z\s*|\\(.|\n))*""#, callback = |lex| lex.slice().clip(1, 1))]
+    #[regex(r#"'([^'\\\n\t]|\\z\s*|\\(.|\n))*'"#, callback = |lex| lex.slice().clip(1, 1))]
     StringSingle(&'a str),
 
+    #[regex(r"`[^`\n]*`", callback = |lex| lex.slice().clip(1, 1))]
+    StringInterpolated(&'a str),
+
     #[regex(r"(?&num)", priority = 99)]
     Number(&'a str),
 
@@ -211,6 +281,15 @@ pub enum Token<'a> {
     #[regex(r"(?&num)px", priority = 99)]
     NumberOffset(&'a str),
 
+    #[regex(r"(?&decimal)(deg|rad)", priority = 99)]
+    NumberAngle(&'a str),
+
+    #[regex(r"(?&decimal)m?s", priority = 99)]
+    NumberDuration(&'a str),
+
+    #[regex(r"(?&decimal)rem", priority = 99)]
+    NumberRem(&'a str),
+
     #[token("true")]
     #[token("false")]
     Boolean(&'a str),
@@ -230,6 +309,10 @@ pub enum Token<'a> {
     #[regex(r"(?i)css:[a-z]+")]
     ColorCss(&'a str),
 
+    /// A colour from a user palette, such as `brand:primary:500`. Produced by
+    /// [`RsmlLexer`] rather than logos.
+    ColorPalette(&'a str),
+
     #[regex(r"#[\da-fA-F]+", priority = 99)]
     ColorHex(&'a str),
 
@@ -269,6 +352,23 @@ impl TokenKind {
     }
 }
 
+/// An attribute selector such as `[Disabled]` or `[Variant = "primary"]`.
+/// The value is kept exactly as written and evaluated later on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttributeSelector<'a> {
+    pub name: &'a str,
+    pub value: Option<&'a str>,
+}
+
+fn attribute_selector_callback<'a>(lexer: &mut LogosLexer<'a, Token<'a>>) -> AttributeSelector<'a> {
+    let inner = lexer.slice().clip(1, 1);
+
+    match inner.split_once('=') {
+        Some((name, value)) => AttributeSelector { name: name.trim(), value: Some(value.trim()) },
+        None => AttributeSelector { name: inner.trim(), value: None },
+    }
+}
+
 #[derive(Logos, Debug, PartialEq, Clone)]
 #[logos(skip r"[ \t\n\r\f]+")]
 enum MultilineStringToken {
@@ -338,7 +438,10 @@ pub const TOKEN_KIND_CONSTRUCT_DELIMITERS: LazyLock<HashSet<TokenKind>> = lazy_c
     TokenKind::DeriveDeclaration,
     TokenKind::MacroDeclaration,
     TokenKind::PriorityDeclaration,
-    TokenKind::TweenDeclaration
+    TokenKind::TweenDeclaration,
+    TokenKind::IfDeclaration,
+    TokenKind::ElseDeclaration,
+    TokenKind::EachDeclaration
 };
 
 pub const TOKEN_KIND_MACRO_CALL_DELIMITERS: LazyLock<HashSet<TokenKind>> = lazy_collection! {
@@ -350,23 +453,27 @@ pub const TOKEN_KIND_MACRO_CALL_DELIMITERS: LazyLock<HashSet<TokenKind>> = lazy_
     TokenKind::DeriveDeclaration,
     TokenKind::MacroDeclaration,
     TokenKind::PriorityDeclaration,
-    TokenKind::TweenDeclaration
+    TokenKind::TweenDeclaration,
+    TokenKind::IfDeclaration,
+    TokenKind::ElseDeclaration,
+    TokenKind::EachDeclaration
 };
 
 pub const TOKEN_KIND_INSIDE_PARENS_CONSTRUCT_DELIMITERS: LazyLock<HashSet<TokenKind>> = lazy_collection! {
     TokenKind::ParensClose,
 };
 
-pub const TOKEN_KIND_ADD_SUB_PRECEDENCE: usize = 0;
+pub const TOKEN_KIND_ADD_SUB_PRECEDENCE: usize = 1;
 
 pub const TOKEN_KIND_OPERATOR_PRECEDENCE: LazyLock<HashMap<TokenKind, usize>> = lazy_collection! {
-    TokenKind::OpDiv => 1,
-    TokenKind::OpFloorDiv => 1,
-    TokenKind::OpMod => 1,
-    TokenKind::OpMult => 1,
-    TokenKind::OpPow => 1,
+    TokenKind::OpDiv => 2,
+    TokenKind::OpFloorDiv => 2,
+    TokenKind::OpMod => 2,
+    TokenKind::OpMult => 2,
+    TokenKind::OpPow => 2,
     TokenKind::OpAdd => TOKEN_KIND_ADD_SUB_PRECEDENCE,
     TokenKind::OpSub => TOKEN_KIND_ADD_SUB_PRECEDENCE,
+    TokenKind::OpConcat => 0,
 };
 
 const TOKEN_KIND_STRING_MAP: LazyLock<HashMap<TokenKind, &'static str>> = lazy_collection! {
@@ -377,9 +484,13 @@ const TOKEN_KIND_STRING_MAP: LazyLock<HashMap<TokenKind, &'static str>> = lazy_c
     TokenKind::MacroDeclaration => "\"@macro\"",
     TokenKind::PriorityDeclaration => "\"@priority\"",
     TokenKind::TweenDeclaration => "\"@tween\"",
+    TokenKind::IfDeclaration => "\"@if\"",
+    TokenKind::ElseDeclaration => "\"@else\"",
+    TokenKind::EachDeclaration => "\"@each\"",
     TokenKind::QuerySelector => "`query selector`",
     TokenKind::Identifier => "`identifer`",
     TokenKind::MacroArgIdentifier => "`macro argument`",
+    TokenKind::MacroRestArgIdentifier => "`macro rest argument`",
     TokenKind::MacroCallIdentifier => "`macro call`",
     TokenKind::Equals => "\"=\"",
     TokenKind::Comma => "\",\"",
@@ -388,6 +499,7 @@ const TOKEN_KIND_STRING_MAP: LazyLock<HashMap<TokenKind, &'static str>> = lazy_c
     TokenKind::TagSelectorOrEnumPart => "`tag selector`",
     TokenKind::StateSelectorOrEnumPart => "`state selector`",
     TokenKind::PseudoSelector => "`pseudo selector`",
+    TokenKind::AttributeSelector => "`attribute selector`",
     TokenKind::ReturnArrow => "\"->\"",
     TokenKind::ChildrenSelector => "\">\"",
     TokenKind::DescendantsSelector => "\">>\"",
@@ -397,11 +509,16 @@ const TOKEN_KIND_STRING_MAP: LazyLock<HashMap<TokenKind, &'static str>> = lazy_c
     TokenKind::ParensClose => "\")\"",
     TokenKind::StringMulti => "`string`",
     TokenKind::StringSingle => "`string`",
+    TokenKind::StringInterpolated => "`string`",
     TokenKind::Number => "`number`",
     TokenKind::NumberScale => "`udim scale`",
     TokenKind::NumberOffset => "`udim offset`",
+    TokenKind::NumberAngle => "`angle`",
+    TokenKind::NumberDuration => "`duration`",
+    TokenKind::NumberRem => "`root-relative size`",
     TokenKind::ColorTailwind => "`tailwind color`",
     TokenKind::ColorBrick => "`brick color`",
     TokenKind::ColorCss => "`css color`",
+    TokenKind::ColorPalette => "`palette color`",
     TokenKind::ColorHex => "`hex color`",
 };
@@ -2,6 +2,7 @@
 #![feature(iter_intersperse)]
 
 pub(crate) mod string_clip;
+pub(crate) mod string_escape;
 pub mod types;
 #[macro_use]
 mod macros;
@@ -10,9 +11,12 @@ pub mod builtins;
 pub mod datatype;
 pub mod lexer;
 pub mod list;
+pub mod macro_expansion;
 pub mod macro_registry;
 pub mod parser;
 pub mod range_from_span;
+pub(crate) mod selector;
+pub mod specificity;
 
 #[cfg(feature = "compiler")]
 pub mod compiler;
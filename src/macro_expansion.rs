@@ -0,0 +1,639 @@
+use std::collections::HashSet;
+
+use crate::builtins::BUILTINS;
+use crate::datatype::{Reference, tag_references};
+use crate::lexer::Token;
+use crate::macro_registry::{
+    BindingFrame, BoundArg, MacroCallArgs, MacroDefinition, MacroKey, MacroRegistry,
+    bind_macro_args, collect_macro_call_args, collect_macros, find_macro, rest_arg_splice,
+    splice_rest_args,
+};
+use crate::parser::{
+    Construct, Delimited, MacroBodyContent, Node, ParsedRsml, SelectorNode, SpanEnd,
+};
+
+/// Expands the macro call whose name is at `offset` into the RSML it stands
+/// for. Arguments are substituted and nested macro calls are expanded in turn,
+/// the same way the compiler expands them. Returns `None` if there is no macro
+/// call at `offset` or it doesn't resolve to a definition.
+pub fn expand_macro_at(parsed: &ParsedRsml, offset: usize) -> Option<String> {
+    expand_macro_at_with_derived(parsed, &[], offset)
+}
+
+/// Like [`expand_macro_at`], with the macros of the stylesheets `parsed`
+/// derives from in scope. Calls resolve the same way the compiler resolves
+/// them.
+pub fn expand_macro_at_with_derived(
+    parsed: &ParsedRsml,
+    derived: &[&ParsedRsml],
+    offset: usize,
+) -> Option<String> {
+    let (name, body) = find_macro_call_at(&parsed.ast, offset)?;
+
+    let source = parsed.rope.to_string();
+    let local = collect_macros(&parsed.ast);
+
+    let derived_sources: Vec<String> = derived.iter().map(|sheet| sheet.rope.to_string()).collect();
+    let derived: Vec<(MacroRegistry, &str)> = derived
+        .iter()
+        .zip(&derived_sources)
+        .map(|(sheet, source)| (collect_macros(&sheet.ast), source.as_str()))
+        .collect();
+
+    let mut expander = MacroExpander {
+        local: &local,
+        derived: &derived,
+        source: &source,
+        nobuiltins: parsed.directives.nobuiltins,
+        frames: vec![Frame {
+            source: &source,
+            bindings: BindingFrame::default(),
+        }],
+        active_expansions: HashSet::new(),
+    };
+
+    expander.expand_call(name, body, 0)
+}
+
+type Replacement = ((usize, usize), String);
+
+/// The arguments bound by one macro expansion. `source` is the text the
+/// expanded body's spans point into.
+struct Frame<'a> {
+    source: &'a str,
+    bindings: BindingFrame<'a>,
+}
+
+struct MacroExpander<'a> {
+    local: &'a MacroRegistry<'a>,
+    /// The macros of each derived stylesheet, with the text they were parsed
+    /// from.
+    derived: &'a [(MacroRegistry<'a>, &'a str)],
+    source: &'a str,
+    nobuiltins: bool,
+    frames: Vec<Frame<'a>>,
+    active_expansions: HashSet<MacroKey<'a>>,
+}
+
+impl<'a> MacroExpander<'a> {
+    fn expand_call(
+        &mut self,
+        name: &Node<'a>,
+        body: &'a Option<Delimited<'a>>,
+        depth: usize,
+    ) -> Option<String> {
+        let Token::MacroCallIdentifier(Some(macro_name)) = name.token.value() else {
+            return None;
+        };
+
+        let (call_args, spliced) = splice_rest_args(
+            collect_macro_call_args(body),
+            Some(&self.frames[depth].bindings),
+        );
+        let (key, definition, source) = self.find_definition(macro_name, &call_args)?;
+
+        if self.active_expansions.contains(&key) {
+            return None;
+        }
+
+        let bound_args = definition.bind_args(&call_args)?;
+        let macro_body = definition.body?;
+
+        // Passed arguments are rendered from the caller's text, while defaults
+        // come from the definition's.
+        let new_depth = self.frames.len();
+        let passed: Vec<&Construct> = call_args
+            .positional
+            .iter()
+            .chain(call_args.named.iter().map(|(_, arg)| arg))
+            .copied()
+            .collect();
+        let frame = Frame {
+            source,
+            bindings: bind_macro_args(bound_args, &spliced, |construct| {
+                if passed.iter().any(|arg| std::ptr::eq(*arg, construct)) {
+                    depth
+                } else {
+                    new_depth
+                }
+            }),
+        };
+
+        self.frames.push(frame);
+        self.active_expansions.insert(key);
+
+        let expanded = match macro_body {
+            MacroBodyContent::Construct(Some(constructs)) => {
+                let mut replacements = vec![];
+                for construct in constructs {
+                    self.collect_replacements(construct, new_depth, &mut replacements);
+                }
+                self.render_items(constructs, new_depth, replacements)
+            }
+
+            MacroBodyContent::Datatype(Some(construct)) => {
+                self.render_construct(construct, new_depth)
+            }
+
+            MacroBodyContent::Selector(Some(selectors)) => {
+                let mut replacements = vec![];
+                self.collect_selector_replacements(selectors, new_depth, &mut replacements);
+                match (selectors.first(), selectors.last()) {
+                    (Some(first), Some(last)) => {
+                        self.render((first.start(), last.end()), new_depth, replacements)
+                    }
+                    _ => String::new(),
+                }
+            }
+
+            _ => String::new(),
+        };
+
+        self.active_expansions.remove(&key);
+        self.frames.pop();
+
+        Some(expanded)
+    }
+
+    /// Resolves a call against the file's own macros, then derived ones, then
+    /// the builtins, along with the source text the definition was parsed
+    /// from.
+    fn find_definition<'c>(
+        &self,
+        name: &str,
+        call_args: &MacroCallArgs<'c>,
+    ) -> Option<(MacroKey<'a>, &'a MacroDefinition<'a>, &'a str)>
+    where
+        'a: 'c,
+    {
+        if let Some((key, definition)) = find_macro(self.local, name, call_args) {
+            return Some((key, definition, self.source));
+        }
+
+        for (registry, source) in self.derived {
+            if let Some((key, definition)) = find_macro(registry, name, call_args) {
+                return Some((key, definition, *source));
+            }
+        }
+
+        if self.nobuiltins {
+            return None;
+        }
+
+        find_macro(&BUILTINS.registry, name, call_args)
+            .map(|(key, definition)| (key, definition, BUILTINS.source))
+    }
+
+    fn rest_values(&self, construct: &Construct, depth: usize) -> Option<&[BoundArg<'a>]> {
+        self.rest_values_named(rest_arg_splice(construct)?, depth)
+    }
+
+    fn rest_values_named(&self, name: &str, depth: usize) -> Option<&[BoundArg<'a>]> {
+        self.frames[depth].bindings.rest.get(name).map(Vec::as_slice)
+    }
+
+    fn collect_replacements(
+        &mut self,
+        construct: &'a Construct<'a>,
+        depth: usize,
+        replacements: &mut Vec<Replacement>,
+    ) {
+        match construct {
+            Construct::MacroCall { name, body, .. } => match self.expand_call(name, body, depth) {
+                Some(expanded) => replacements.push((construct.span(), expanded)),
+                None => self.collect_delimited_replacements(body.as_ref(), depth, replacements),
+            },
+
+            Construct::Node { node } => {
+                if let Token::MacroArgIdentifier(Some(name)) = node.token.value()
+                    && let Some(bound) = self.frames[depth].bindings.args.get(*name).copied()
+                {
+                    let rendered = self.render_construct(bound.construct, bound.scope_depth);
+                    replacements.push((node.token.span(), rendered));
+                }
+
+                if let Some(values) = self.rest_values(construct, depth) {
+                    let rendered = self.render_rest_values(values.to_vec());
+                    replacements.push((node.token.span(), rendered));
+                }
+            }
+
+            Construct::Rule { selectors, body } => {
+                if let Some(selectors) = selectors {
+                    self.collect_selector_replacements(selectors, depth, replacements);
+                }
+                self.collect_delimited_replacements(body.as_ref(), depth, replacements);
+            }
+
+            Construct::If {
+                condition,
+                body,
+                else_body,
+                ..
+            } => {
+                if let Some(condition) = condition {
+                    self.collect_replacements(condition, depth, replacements);
+                }
+                self.collect_delimited_replacements(body.as_ref(), depth, replacements);
+                self.collect_delimited_replacements(else_body.as_ref(), depth, replacements);
+            }
+
+            Construct::Each { iterable, body, .. } => {
+                if let Some(iterable) = iterable {
+                    // A spliced rest argument is iterated as a table of the
+                    // values it collected.
+                    match self.rest_values(iterable, depth) {
+                        Some(values) => {
+                            let rendered = self.render_rest_values(values.to_vec());
+                            replacements.push((iterable.span(), format!("({rendered})")));
+                        }
+                        None => self.collect_replacements(iterable, depth, replacements),
+                    }
+                }
+                self.collect_delimited_replacements(body.as_ref(), depth, replacements);
+            }
+
+            Construct::Assignment {
+                right: Some(inner), ..
+            }
+            | Construct::Priority {
+                body: Some(inner), ..
+            }
+            | Construct::Tween {
+                body: Some(inner), ..
+            }
+            | Construct::UnaryMinus { operand: inner, .. } => {
+                self.collect_replacements(inner, depth, replacements);
+            }
+
+            Construct::MathOperation { left, right, .. } => {
+                self.collect_replacements(left, depth, replacements);
+                if let Some(right) = right {
+                    self.collect_replacements(right, depth, replacements);
+                }
+            }
+
+            Construct::Table { body } => {
+                self.collect_delimited_replacements(Some(body), depth, replacements);
+            }
+
+            Construct::AnnotatedTable { body, .. } => {
+                self.collect_delimited_replacements(body.as_ref(), depth, replacements);
+            }
+
+            _ => (),
+        }
+    }
+
+    fn collect_delimited_replacements(
+        &mut self,
+        delimited: Option<&'a Delimited<'a>>,
+        depth: usize,
+        replacements: &mut Vec<Replacement>,
+    ) {
+        let Some(content) = delimited.and_then(|delimited| delimited.content.as_ref()) else {
+            return;
+        };
+
+        for construct in content {
+            self.collect_replacements(construct, depth, replacements);
+        }
+    }
+
+    fn collect_selector_replacements(
+        &mut self,
+        selectors: &'a [SelectorNode<'a>],
+        depth: usize,
+        replacements: &mut Vec<Replacement>,
+    ) {
+        for selector in selectors {
+            match selector {
+                SelectorNode::MacroCall { name, body } => {
+                    match self.expand_call(name, body, depth) {
+                        Some(expanded) => {
+                            replacements.push(((selector.start(), selector.end()), expanded))
+                        }
+                        None => {
+                            self.collect_delimited_replacements(body.as_ref(), depth, replacements)
+                        }
+                    }
+                }
+
+                SelectorNode::Functional { selectors, .. } => {
+                    self.collect_selector_replacements(selectors, depth, replacements);
+                }
+
+                SelectorNode::Token(node) => match node.token.value() {
+                    Token::MacroArgIdentifier(Some(name)) => {
+                        if let Some(bound) = self.frames[depth].bindings.args.get(*name).copied() {
+                            let rendered = self.render_selector_arg(bound);
+                            replacements.push((node.token.span(), rendered));
+                        }
+                    }
+
+                    Token::MacroRestArgIdentifier(Some(name)) => {
+                        if let Some(values) = self.rest_values_named(name, depth) {
+                            let rendered: Vec<String> = values
+                                .to_vec()
+                                .into_iter()
+                                .map(|bound| self.render_selector_arg(bound))
+                                .collect();
+                            replacements.push((node.token.span(), rendered.join(", ")));
+                        }
+                    }
+
+                    Token::TagSelectorOrEnumPart(Some(tag)) | Token::NameSelector(tag) => {
+                        // Skips the `.` or `#` before the tag.
+                        let tag_start = node.token.start() + 1;
+
+                        for (reference, (start, end)) in tag_references(tag) {
+                            if let Reference::MacroArg(name) = reference
+                                && let Some(bound) = self.frames[depth].bindings.args.get(name).copied()
+                            {
+                                let rendered = self.render_selector_arg(bound);
+                                replacements.push(((tag_start + start, tag_start + end), rendered));
+                            }
+                        }
+                    }
+
+                    _ => (),
+                },
+            }
+        }
+    }
+
+    /// Renders a bound argument as the selector text it stands for, so
+    /// strings are spliced in without their quotes.
+    fn render_selector_arg(&mut self, bound: BoundArg<'a>) -> String {
+        if let Construct::Node { node } = bound.construct
+            && let Token::StringSingle(text) = node.token.value()
+        {
+            return text.to_string();
+        }
+
+        self.render_construct(bound.construct, bound.scope_depth)
+    }
+
+    fn render_rest_values(&mut self, values: Vec<BoundArg<'a>>) -> String {
+        let rendered: Vec<String> = values
+            .into_iter()
+            .map(|bound| self.render_construct(bound.construct, bound.scope_depth))
+            .collect();
+        rendered.join(", ")
+    }
+
+    fn render_construct(&mut self, construct: &'a Construct<'a>, depth: usize) -> String {
+        let mut replacements = vec![];
+        self.collect_replacements(construct, depth, &mut replacements);
+        self.render(construct.span(), depth, replacements)
+    }
+
+    fn render_items(
+        &self,
+        constructs: &[Construct<'a>],
+        depth: usize,
+        replacements: Vec<Replacement>,
+    ) -> String {
+        match (constructs.first(), constructs.last()) {
+            (Some(first), Some(last)) => {
+                self.render((first.start(), last.end()), depth, replacements)
+            }
+            _ => String::new(),
+        }
+    }
+
+    /// The text of `span` in the source of the frame at `depth`, with each
+    /// replacement spliced in.
+    fn render(
+        &self,
+        (start, end): (usize, usize),
+        depth: usize,
+        mut replacements: Vec<Replacement>,
+    ) -> String {
+        let source = self.frames[depth].source;
+        replacements.sort_by_key(|((start, _), _)| *start);
+
+        let mut rendered = String::new();
+        let mut cursor = start;
+
+        for ((replace_start, replace_end), text) in replacements {
+            rendered.push_str(&source[cursor..replace_start]);
+            rendered.push_str(&text);
+            cursor = replace_end;
+        }
+        rendered.push_str(&source[cursor..end]);
+
+        rendered
+    }
+}
+
+fn find_macro_call_at<'a>(
+    constructs: &'a [Construct<'a>],
+    offset: usize,
+) -> Option<(&'a Node<'a>, &'a Option<Delimited<'a>>)> {
+    constructs
+        .iter()
+        .find_map(|construct| find_in_construct(construct, offset))
+}
+
+fn find_in_construct<'a>(
+    construct: &'a Construct<'a>,
+    offset: usize,
+) -> Option<(&'a Node<'a>, &'a Option<Delimited<'a>>)> {
+    match construct {
+        Construct::MacroCall { name, body, .. } => {
+            if (name.token.start()..=name.token.end()).contains(&offset) {
+                return Some((name, body));
+            }
+            find_in_delimited(body.as_ref(), offset)
+        }
+
+        Construct::Macro {
+            body: Some(body), ..
+        } => match &body.content {
+            MacroBodyContent::Construct(Some(constructs)) => find_macro_call_at(constructs, offset),
+            MacroBodyContent::Datatype(Some(construct)) => find_in_construct(construct, offset),
+            MacroBodyContent::Selector(Some(selectors)) => find_in_selectors(selectors, offset),
+            _ => None,
+        },
+
+        Construct::Rule { selectors, body } => selectors
+            .as_deref()
+            .and_then(|selectors| find_in_selectors(selectors, offset))
+            .or_else(|| find_in_delimited(body.as_ref(), offset)),
+
+        Construct::If {
+            condition,
+            body,
+            else_body,
+            ..
+        } => condition
+            .as_deref()
+            .and_then(|condition| find_in_construct(condition, offset))
+            .or_else(|| find_in_delimited(body.as_ref(), offset))
+            .or_else(|| find_in_delimited(else_body.as_ref(), offset)),
+
+        Construct::Each { iterable, body, .. } => iterable
+            .as_deref()
+            .and_then(|iterable| find_in_construct(iterable, offset))
+            .or_else(|| find_in_delimited(body.as_ref(), offset)),
+
+        Construct::Assignment {
+            right: Some(inner), ..
+        }
+        | Construct::Priority {
+            body: Some(inner), ..
+        }
+        | Construct::Tween {
+            body: Some(inner), ..
+        }
+        | Construct::Derive {
+            body: Some(inner), ..
+        }
+        | Construct::UnaryMinus { operand: inner, .. } => find_in_construct(inner, offset),
+
+        Construct::MathOperation { left, right, .. } => {
+            find_in_construct(left, offset).or_else(|| {
+                right
+                    .as_deref()
+                    .and_then(|right| find_in_construct(right, offset))
+            })
+        }
+
+        Construct::Table { body } => find_in_delimited(Some(body), offset),
+        Construct::AnnotatedTable { body, .. } => find_in_delimited(body.as_ref(), offset),
+
+        _ => None,
+    }
+}
+
+fn find_in_delimited<'a>(
+    delimited: Option<&'a Delimited<'a>>,
+    offset: usize,
+) -> Option<(&'a Node<'a>, &'a Option<Delimited<'a>>)> {
+    find_macro_call_at(delimited?.content.as_deref()?, offset)
+}
+
+fn find_in_selectors<'a>(
+    selectors: &'a [SelectorNode<'a>],
+    offset: usize,
+) -> Option<(&'a Node<'a>, &'a Option<Delimited<'a>>)> {
+    selectors.iter().find_map(|selector| {
+        let SelectorNode::MacroCall { name, body } = selector else {
+            return None;
+        };
+
+        if (name.token.start()..=name.token.end()).contains(&offset) {
+            return Some((name, body));
+        }
+        find_in_delimited(body.as_ref(), offset)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RsmlParser;
+
+    fn expand(source: &str, call: &str) -> Option<String> {
+        let parsed = RsmlParser::from_source(source);
+        expand_macro_at(&parsed, source.rfind(call).unwrap())
+    }
+
+    #[test]
+    fn substitutes_arguments() {
+        assert_eq!(
+            expand(
+                "@macro Pad (&all, &side = 2px) { ::UIPadding { PaddingTop = &all; PaddingLeft = &side; } }\n\
+                 Frame { Pad!(4px); }",
+                "Pad!",
+            )
+            .as_deref(),
+            Some("::UIPadding { PaddingTop = 4px; PaddingLeft = 2px; }")
+        );
+    }
+
+    #[test]
+    fn expands_nested_calls() {
+        assert_eq!(
+            expand(
+                "@macro Square (&n) -> Datatype { udim2(0, &n, 0, &n) }\n\
+                 @macro Sized (&...sizes) { Size = Square!(&...sizes); }\n\
+                 @macro Buttons -> Selector { TextButton, ImageButton }\n\
+                 Buttons!() { Sized!(40); }",
+                "Sized!",
+            )
+            .as_deref(),
+            Some("Size = udim2(0, 40, 0, 40);")
+        );
+    }
+
+    #[test]
+    fn expands_selector_macros() {
+        assert_eq!(
+            expand(
+                "@macro Buttons -> Selector { TextButton, ImageButton }\nButtons!() { }",
+                "Buttons!",
+            )
+            .as_deref(),
+            Some("TextButton, ImageButton")
+        );
+    }
+
+    #[test]
+    fn substitutes_selector_macro_arguments() {
+        assert_eq!(
+            expand(
+                "@macro Themed (&tag, &size) -> Selector { .&tag > .p-&size }\n\
+                 Themed!(\"Primary\", 4) { }",
+                "Themed!",
+            )
+            .as_deref(),
+            Some(".Primary > .p-4")
+        );
+    }
+
+    #[test]
+    fn splices_rest_arguments_into_selectors_and_loops() {
+        assert_eq!(
+            expand(
+                "@macro Hide (&...targets) { Frame, &...targets { Visible = false; } }\n\
+                 Hide!(\".Primary\", \"#Close\");",
+                "Hide!",
+            )
+            .as_deref(),
+            Some("Frame, .Primary, #Close { Visible = false; }")
+        );
+
+        assert_eq!(
+            expand(
+                "@macro Pad (&...sizes) { @each $!size in &...sizes { .p-$!size { } } }\n\
+                 Pad!(1, 2);",
+                "Pad!",
+            )
+            .as_deref(),
+            Some("@each $!size in (1, 2) { .p-$!size { } }")
+        );
+    }
+
+    #[test]
+    fn expands_derived_macros() {
+        let base = RsmlParser::from_source(
+            "@macro Round (&radius) { ::UICorner { CornerRadius = &radius; } }",
+        );
+        let source = "Frame { Round!(udim(0, 4)); }";
+        let parsed = RsmlParser::from_source(source);
+
+        assert_eq!(
+            expand_macro_at_with_derived(&parsed, &[&base], source.rfind("Round!").unwrap())
+                .as_deref(),
+            Some("::UICorner { CornerRadius = udim(0, 4); }")
+        );
+    }
+
+    #[test]
+    fn needs_a_macro_call_at_offset() {
+        assert_eq!(expand("Frame { Size = 1; }", "Size"), None);
+        assert_eq!(expand("Frame { Missing!(); }", "Missing!"), None);
+    }
+}
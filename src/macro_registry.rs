@@ -23,10 +23,147 @@ impl MacroReturnContext {
 #[derive(Debug, Clone)]
 pub struct MacroDefinition<'a> {
     pub arg_names: Vec<&'a str>,
+    /// The default of each argument in `arg_names`, if it has one.
+    pub arg_defaults: Vec<Option<&'a Construct<'a>>>,
+    /// The `&...name` argument which collects any extra positional arguments.
+    pub rest_arg: Option<&'a str>,
     pub body: Option<&'a MacroBodyContent<'a>>,
     pub return_context: MacroReturnContext,
 }
 
+impl<'a> MacroDefinition<'a> {
+    /// How many arguments have no default and so must be passed.
+    pub fn required_arity(&self) -> usize {
+        self.arg_defaults.iter().filter(|default| default.is_none()).count()
+    }
+
+    /// Matches the arguments of a call to this macro's arguments. Positional
+    /// arguments are taken in order, named arguments by name, and whatever is
+    /// left over falls back to its default. Extra positional arguments go to
+    /// the rest argument. Returns `None` if the call doesn't fit this
+    /// definition.
+    pub fn bind_args<'c>(&self, call_args: &MacroCallArgs<'c>) -> Option<BoundMacroArgs<'a, 'c>>
+    where
+        'a: 'c,
+    {
+        let fixed_count = call_args.positional.len().min(self.arg_names.len());
+        let (fixed, rest) = call_args.positional.split_at(fixed_count);
+
+        if !rest.is_empty() && self.rest_arg.is_none() {
+            return None;
+        }
+
+        let mut bound: Vec<Option<&'c Construct<'c>>> = fixed
+            .iter()
+            .copied()
+            .map(Some)
+            .chain(std::iter::repeat(None))
+            .take(self.arg_names.len())
+            .collect();
+
+        for (name, value) in &call_args.named {
+            let idx = self.arg_names.iter().position(|arg_name| arg_name == name)?;
+            if bound[idx].replace(*value).is_some() {
+                return None;
+            }
+        }
+
+        let args = self
+            .arg_names
+            .iter()
+            .zip(bound)
+            .zip(&self.arg_defaults)
+            .map(|((name, value), default)| Some((*name, value.or(*default)?)))
+            .collect::<Option<_>>()?;
+
+        Some(BoundMacroArgs {
+            args,
+            rest: self.rest_arg.map(|name| (name, rest.to_vec())),
+        })
+    }
+}
+
+/// The result of matching a call's arguments to a [`MacroDefinition`].
+#[derive(Debug)]
+pub struct BoundMacroArgs<'a, 'c> {
+    pub args: Vec<(&'a str, &'c Construct<'c>)>,
+    pub rest: Option<(&'a str, Vec<&'c Construct<'c>>)>,
+}
+
+/// A value bound to a macro argument, along with the depth of the binding
+/// frame its own macro arguments resolve against.
+#[derive(Debug, Clone, Copy)]
+pub struct BoundArg<'a> {
+    pub construct: &'a Construct<'a>,
+    pub scope_depth: usize,
+}
+
+/// The arguments bound by one macro expansion.
+#[derive(Debug, Default)]
+pub struct BindingFrame<'a> {
+    pub args: HashMap<String, BoundArg<'a>>,
+    /// The values collected by each `&...name` argument.
+    pub rest: HashMap<String, Vec<BoundArg<'a>>>,
+}
+
+/// Binds a call's arguments to the names a definition gives them. Spliced
+/// rest values keep the scope they were originally passed from, while any
+/// other value resolves at the depth `scope_of` gives it.
+pub fn bind_macro_args<'a>(
+    bound_args: BoundMacroArgs<'_, 'a>,
+    spliced: &[BoundArg<'a>],
+    scope_of: impl Fn(&'a Construct<'a>) -> usize,
+) -> BindingFrame<'a> {
+    let bind = |construct: &'a Construct<'a>| {
+        spliced
+            .iter()
+            .find(|bound| std::ptr::eq(bound.construct, construct))
+            .copied()
+            .unwrap_or_else(|| BoundArg {
+                construct,
+                scope_depth: scope_of(construct),
+            })
+    };
+
+    let mut frame = BindingFrame::default();
+    for (arg_name, arg_value) in bound_args.args {
+        frame.args.insert(arg_name.to_string(), bind(arg_value));
+    }
+    if let Some((rest_name, rest_values)) = bound_args.rest {
+        frame.rest.insert(
+            rest_name.to_string(),
+            rest_values.into_iter().map(bind).collect(),
+        );
+    }
+
+    frame
+}
+
+/// Replaces any `&...name` splices among a call's positional arguments with
+/// the values the rest arguments of `frame` collected. Those values are also
+/// returned, since they keep the scope they were originally passed from.
+pub fn splice_rest_args<'a>(
+    call_args: MacroCallArgs<'a>,
+    frame: Option<&BindingFrame<'a>>,
+) -> (MacroCallArgs<'a>, Vec<BoundArg<'a>>) {
+    let mut spliced = vec![];
+
+    let positional = call_args
+        .positional
+        .into_iter()
+        .flat_map(|arg| match rest_arg_splice(arg) {
+            Some(name) => {
+                let values = frame.and_then(|frame| frame.rest.get(name));
+                spliced.extend(values.into_iter().flatten().copied());
+                values.into_iter().flatten().map(|bound| bound.construct).collect()
+            }
+            None => vec![arg],
+        })
+        .collect();
+
+    (MacroCallArgs { positional, ..call_args }, spliced)
+}
+
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
 pub struct MacroKey<'a> {
     pub name: &'a str,
@@ -35,43 +172,162 @@ pub struct MacroKey<'a> {
 
 pub type MacroRegistry<'a> = HashMap<MacroKey<'a>, MacroDefinition<'a>>;
 
-pub fn collect_macro_def_arg_names<'a>(args: &Option<Delimited<'a>>) -> Vec<&'a str> {
-    let Some(args) = args else { return Vec::new() };
-    let Some(content) = &args.content else {
-        return Vec::new();
-    };
-    content
+/// Registers every top-level `@macro` in `ast`. A later definition with the
+/// same name and argument count replaces an earlier one.
+pub fn collect_macros<'a>(ast: &'a [Construct<'a>]) -> MacroRegistry<'a> {
+    let mut registry = MacroRegistry::new();
+
+    for construct in ast {
+        let Construct::Macro { name: Some(name_node), args, body, return_type, .. } = construct
+        else {
+            continue;
+        };
+        let Token::Identifier(name) = name_node.token.value() else { continue };
+
+        let arg_names = collect_macro_def_arg_names(args);
+        registry.insert(
+            MacroKey { name, arity: arg_names.len() },
+            MacroDefinition {
+                arg_names,
+                arg_defaults: collect_macro_def_arg_defaults(args),
+                rest_arg: collect_macro_def_rest_arg(args),
+                body: body.as_ref().map(|body| &body.content),
+                return_context: macro_return_context(return_type),
+            },
+        );
+    }
+
+    registry
+}
+
+/// Finds the definition of `name` which a call with `call_args` resolves to.
+/// A definition taking exactly as many arguments as were passed wins, then
+/// the one with the fewest arguments which the call fits through defaults.
+pub fn find_macro<'r, 'a: 'c, 'c>(
+    registry: &'r MacroRegistry<'a>,
+    name: &str,
+    call_args: &MacroCallArgs<'c>,
+) -> Option<(MacroKey<'a>, &'r MacroDefinition<'a>)> {
+    let mut candidates: Vec<_> = registry
         .iter()
-        .filter_map(|construct| {
-            if let Construct::Node { node } = construct {
-                if let Token::MacroArgIdentifier(Some(name)) = node.token.value() {
-                    return Some(*name);
-                }
-            }
-            None
+        .filter(|(key, definition)| {
+            key.name == name && definition.bind_args(call_args).is_some()
         })
-        .collect()
+        .collect();
+
+    candidates.sort_by_key(|(key, _)| (key.arity != call_args.len(), key.arity));
+    candidates.first().map(|(key, definition)| (**key, *definition))
 }
 
+/// Resolves a macro call against each of `registries` in turn, so earlier
+/// registries shadow later ones.
+pub fn find_macro_in<'r, 'a: 'c, 'c>(
+    registries: &[&'r MacroRegistry<'a>],
+    name: &str,
+    call_args: &MacroCallArgs<'c>,
+) -> Option<(MacroKey<'a>, &'r MacroDefinition<'a>)> {
+    registries.iter().find_map(|registry| find_macro(registry, name, call_args))
+}
+
+/// Every number of arguments `name` can be called with across `registries`,
+/// sorted and without duplicates.
 #[cfg(feature = "typechecker")]
-pub(crate) fn count_macro_call_args(body: &Option<Delimited>) -> usize {
-    let Some(body) = body else { return 0 };
-    let Some(content) = &body.content else {
-        return 0;
-    };
-    if content.is_empty() {
-        return 0;
-    }
-    content
+pub(crate) fn accepted_arg_counts(registries: &[&MacroRegistry], name: &str) -> Vec<usize> {
+    let mut counts: Vec<usize> = registries
         .iter()
-        .filter(|construct| {
-            matches!(
-                construct,
-                Construct::Node { node } if matches!(node.token.value(), Token::Comma)
-            )
+        .flat_map(|registry| registry.iter())
+        .filter(|(key, _)| key.name == name)
+        .flat_map(|(key, definition)| definition.required_arity()..=key.arity)
+        .collect();
+
+    counts.sort();
+    counts.dedup();
+    counts
+}
+
+fn macro_def_args<'c, 'a>(
+    args: &'c Option<Delimited<'a>>,
+) -> impl Iterator<Item = (&'a str, Option<&'c Construct<'a>>)> {
+    args.iter()
+        .flat_map(|args| args.content.iter().flatten())
+        .filter_map(|construct| match construct {
+            Construct::Node { node } => match node.token.value() {
+                Token::MacroArgIdentifier(Some(name)) => Some((*name, None)),
+                _ => None,
+            },
+            Construct::Assignment { left, right, .. } => match left.token.value() {
+                Token::MacroArgIdentifier(Some(name)) => Some((*name, right.as_deref())),
+                _ => None,
+            },
+            _ => None,
         })
-        .count()
-        + 1
+}
+
+pub fn collect_macro_def_arg_names<'a>(args: &Option<Delimited<'a>>) -> Vec<&'a str> {
+    macro_def_args(args).map(|(name, _)| name).collect()
+}
+
+/// The name of the definition's `&...name` argument, if it has one.
+pub fn collect_macro_def_rest_arg<'a>(args: &Option<Delimited<'a>>) -> Option<&'a str> {
+    args.iter()
+        .flat_map(|args| args.content.iter().flatten())
+        .find_map(rest_arg_splice)
+}
+
+pub fn collect_macro_def_arg_defaults<'a>(
+    args: &'a Option<Delimited<'a>>,
+) -> Vec<Option<&'a Construct<'a>>> {
+    macro_def_args(args).map(|(_, default)| default).collect()
+}
+
+/// The name in a `&...name` node, which either declares a rest argument or
+/// splices one.
+pub fn rest_arg_splice<'a>(construct: &Construct<'a>) -> Option<&'a str> {
+    match construct {
+        Construct::Node { node } => match node.token.value() {
+            Token::MacroRestArgIdentifier(Some(name)) => Some(*name),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The arguments passed to a macro call.
+#[derive(Debug, Default)]
+pub struct MacroCallArgs<'a> {
+    pub positional: Vec<&'a Construct<'a>>,
+    /// Arguments passed as `name = datatype`.
+    pub named: Vec<(&'a str, &'a Construct<'a>)>,
+}
+
+impl<'a> MacroCallArgs<'a> {
+    pub fn len(&self) -> usize {
+        self.positional.len() + self.named.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+pub fn collect_macro_call_args<'a>(body: &'a Option<Delimited<'a>>) -> MacroCallArgs<'a> {
+    let mut call_args = MacroCallArgs::default();
+
+    for construct in body.iter().flat_map(|body| body.content.iter().flatten()) {
+        match construct {
+            Construct::Node { node } if matches!(node.token.value(), Token::Comma) => (),
+
+            Construct::Assignment { left, right, .. } => {
+                if let (Token::Identifier(name), Some(value)) = (left.token.value(), right) {
+                    call_args.named.push((*name, &**value));
+                }
+            }
+
+            _ => call_args.positional.push(construct),
+        }
+    }
+
+    call_args
 }
 
 pub fn macro_return_context(return_type: &Option<(Node, Option<Node>)>) -> MacroReturnContext {
@@ -6,6 +6,7 @@ use crate::list::{Stringified, TokenKindList};
 use crate::parser::parse_error::{ParseError, ParseErrorMessage};
 use crate::parser::types::*;
 use crate::parser::RsmlParser;
+use crate::string_escape::unescape_with;
 
 type SymResult<T> = Result<T, T>;
 
@@ -59,14 +60,14 @@ impl<'a> RsmlParser<'a> {
         let node = match self.optional_node_is_kind_else_advance_until(
             node, token_kind_list!("a datatype", [
                 Identifier, ParensOpen,
-                StringMulti, StringSingle,
-                Number, NumberScale, NumberOffset,
+                StringMulti, StringSingle, StringInterpolated,
+                Number, NumberScale, NumberOffset, NumberAngle, NumberDuration, NumberRem,
                 Boolean, Nil,
                 StaticTokenIdentifier, TokenIdentifier,
-                ColorHex, ColorTailwind, ColorCss, ColorBrick, ColorSkin,
+                ColorHex, ColorTailwind, ColorCss, ColorBrick, ColorSkin, ColorPalette,
                 RbxAsset, RbxContent,
                 EnumKeyword, StateSelectorOrEnumPart,
-                MacroCallIdentifier, MacroArgIdentifier,
+                MacroCallIdentifier, MacroArgIdentifier, MacroRestArgIdentifier,
                 OpSub
             ]),
             construct_delimiters
@@ -96,6 +97,21 @@ impl<'a> RsmlParser<'a> {
                 (NodeStatus::Exists, Some(Construct::Node { node }))
             },
 
+            Token::StringSingle(content) => {
+                let content_start = token.start() + 1;
+                let mut invalid = vec![];
+                unescape_with(content, |span| invalid.push(span));
+
+                for (start, end) in invalid {
+                    self.ast_errors.push(
+                        ParseError::InvalidEscape { sequence: content[start..end].to_string() },
+                        self.range_from_span((content_start + start, content_start + end))
+                    );
+                }
+
+                (NodeStatus::Exists, Some(Construct::Node { node }))
+            },
+
             Token::OpSub => {
                 let next_node = self.advance();
                 let (operand_status, operand) = self.parse_datatype_part(next_node, construct_delimiters);
@@ -313,9 +329,43 @@ impl<'a> RsmlParser<'a> {
         }
     }
 
-    pub(crate) fn parse_table_datatype_args(&mut self, mut node: Option<Node<'a>>) -> (Option<Node<'a>>, Option<Vec<Construct<'a>>>) {
-        let (this_node_status, datatype_group) =
-            self.parse_datatype(node, TOKEN_KIND_INSIDE_PARENS_CONSTRUCT_DELIMITERS);
+    /// Parses a single datatype in a table. Macro calls also accept named
+    /// `name = datatype` arguments, which are kept as assignments.
+    fn parse_table_datatype_arg(
+        &mut self, node: Option<Node<'a>>, named_args: bool
+    ) -> (NodeStatus<'a>, Option<Construct<'a>>) {
+        let name_node = match node {
+            Some(node) if named_args && node_token_matches!(node, Identifier(_)) => node,
+            node => return self.parse_datatype(node, TOKEN_KIND_INSIDE_PARENS_CONSTRUCT_DELIMITERS),
+        };
+
+        let equals_node = match self.advance() {
+            Some(node) if node_token_matches!(node, Equals) => node,
+            next_node => {
+                self.pending_node = next_node;
+                return self.parse_datatype(Some(name_node), TOKEN_KIND_INSIDE_PARENS_CONSTRUCT_DELIMITERS);
+            }
+        };
+
+        let value_node = self.advance();
+        let (node_status, value) = self.parse_datatype(value_node, TOKEN_KIND_INSIDE_PARENS_CONSTRUCT_DELIMITERS);
+
+        if value.is_none() {
+            self.ast_errors.push(
+                ParseError::MissingToken { msg: Some(ParseErrorMessage::Expected("a datatype")) },
+                self.range_from_span(clamp_span_to_end(equals_node.token.end()))
+            );
+        }
+
+        (node_status, Some(Construct::Assignment {
+            left: name_node, middle: Some(equals_node), right: value.map(Box::new), terminator: None
+        }))
+    }
+
+    pub(crate) fn parse_table_datatype_args(
+        &mut self, mut node: Option<Node<'a>>, named_args: bool
+    ) -> (Option<Node<'a>>, Option<Vec<Construct<'a>>>) {
+        let (this_node_status, datatype_group) = self.parse_table_datatype_arg(node, named_args);
 
         if let Some(datatype_group) = datatype_group {
             let mut datatype_groups = vec![];
@@ -328,8 +378,7 @@ impl<'a> RsmlParser<'a> {
             };
 
             loop {
-                let (this_node_status, datatype_group) =
-                    self.parse_datatype(node, TOKEN_KIND_INSIDE_PARENS_CONSTRUCT_DELIMITERS);
+                let (this_node_status, datatype_group) = self.parse_table_datatype_arg(node, named_args);
 
                 let this_node = this_node_status.consume_err_or_advance(self);
                 node = if let Some(datatype_group) = datatype_group {
@@ -375,7 +424,7 @@ impl<'a> RsmlParser<'a> {
             }))
         };
 
-        let (node, datatype_groups) = self.parse_table_datatype_args(Some(node));
+        let (node, datatype_groups) = self.parse_table_datatype_args(Some(node), false);
 
         if !node_token_matches!(node, Some(ParensClose)) {
             let construct = Construct::Table {
@@ -456,7 +505,7 @@ impl<'a> RsmlParser<'a> {
             }))
         };
 
-        let (node, datatype_groups) = self.parse_table_datatype_args(Some(node));
+        let (node, datatype_groups) = self.parse_table_datatype_args(Some(node), false);
 
         if !node_token_matches!(node, Some(ParensClose)) {
             let construct = Construct::AnnotatedTable {
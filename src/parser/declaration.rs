@@ -118,6 +118,194 @@ impl<'a> RsmlParser<'a> {
         )
     }
 
+    pub(crate) fn parse_if(&mut self, node: Node<'a>) -> Parsed<'a> {
+        if !node_token_matches!(node, IfDeclaration) {
+            return Parsed(Some(node), None);
+        }
+        let declaration_node = node;
+
+        let node = self.advance_without_flags();
+        self.did_advance = true;
+
+        let (node_status, left) = self.parse_datatype(node, TOKEN_KIND_MACRO_CALL_DELIMITERS);
+        let mut node = match node_status {
+            NodeStatus::Exists => self.advance(),
+            NodeStatus::Err(node) => Some(node),
+            NodeStatus::None => None,
+        };
+
+        // Comparisons are only meaningful as a condition, so they are parsed
+        // here rather than alongside the other operators.
+        let condition = match (left, node) {
+            (Some(left), Some(operator)) if node_token_matches!(operator, OpEq | OpNotEq) => {
+                let right_node = self.advance();
+                let (node_status, right) =
+                    self.parse_datatype(right_node, TOKEN_KIND_MACRO_CALL_DELIMITERS);
+                node = match node_status {
+                    NodeStatus::Exists => self.advance(),
+                    NodeStatus::Err(node) => Some(node),
+                    NodeStatus::None => None,
+                };
+
+                if right.is_none() {
+                    self.ast_errors.push(
+                        ParseError::MissingToken {
+                            msg: Some(ParseErrorMessage::Expected("a datatype")),
+                        },
+                        self.range_from_span(clamp_span_to_end(operator.token.end())),
+                    );
+                }
+
+                Some(Box::new(Construct::MathOperation {
+                    left: Box::new(left),
+                    operators: vec![operator],
+                    right: right.map(Box::new),
+                }))
+            }
+
+            (left, next_node) => {
+                node = next_node;
+                left.map(Box::new)
+            }
+        };
+
+        let condition_end = condition
+            .as_ref()
+            .map(|condition| condition.end())
+            .unwrap_or(declaration_node.token.end());
+
+        let (mut node, body) = self.parse_block_body(node, condition_end);
+
+        let mut else_declaration = None;
+        let mut else_body = None;
+
+        if let Some(else_node) = node.take_if(|node| node_token_matches!(node, ElseDeclaration)) {
+            let else_end = else_node.token.end();
+            else_declaration = Some(else_node);
+
+            let next_node = self.advance();
+            (node, else_body) = self.parse_block_body(next_node, else_end);
+        }
+
+        Parsed(
+            node,
+            Some(Construct::If {
+                declaration: declaration_node,
+                condition,
+                body,
+                else_declaration,
+                else_body,
+            }),
+        )
+    }
+
+    pub(crate) fn parse_each(&mut self, node: Node<'a>) -> Parsed<'a> {
+        if !node_token_matches!(node, EachDeclaration) {
+            return Parsed(Some(node), None);
+        }
+        let declaration_node = node;
+
+        let variable_node = match self.advance_until(
+            token_kind_list!("loop variable", [StaticTokenIdentifier]),
+            &TOKEN_KIND_MACRO_CALL_DELIMITERS,
+        ) {
+            Some(Ok(node)) => node,
+            other => {
+                return Parsed(
+                    other.and_then(Result::err),
+                    Some(Construct::Each {
+                        declaration: declaration_node,
+                        variable: None,
+                        keyword: None,
+                        iterable: None,
+                        body: None,
+                    }),
+                );
+            }
+        };
+
+        let keyword_node = match self.advance_until(
+            token_kind_list!("\"in\"", [Identifier]),
+            &TOKEN_KIND_MACRO_CALL_DELIMITERS,
+        ) {
+            Some(Ok(node)) => node,
+            other => {
+                return Parsed(
+                    other.and_then(Result::err),
+                    Some(Construct::Each {
+                        declaration: declaration_node,
+                        variable: Some(variable_node),
+                        keyword: None,
+                        iterable: None,
+                        body: None,
+                    }),
+                );
+            }
+        };
+
+        if !node_token_matches!(keyword_node, Identifier("in")) {
+            self.ast_errors.push(
+                ParseError::UnexpectedTokens {
+                    msg: Some(ParseErrorMessage::Expected("\"in\"")),
+                },
+                self.range_from_span(keyword_node.token.span()),
+            );
+        }
+
+        let node = self.advance();
+        let (node_status, iterable) = self.parse_datatype(node, TOKEN_KIND_MACRO_CALL_DELIMITERS);
+        let node = match node_status {
+            NodeStatus::Exists => self.advance(),
+            NodeStatus::Err(node) => Some(node),
+            NodeStatus::None => None,
+        };
+
+        let iterable_end = iterable
+            .as_ref()
+            .map(|iterable| iterable.end())
+            .unwrap_or(keyword_node.token.end());
+
+        let (node, body) = self.parse_block_body(node, iterable_end);
+
+        Parsed(
+            node,
+            Some(Construct::Each {
+                declaration: declaration_node,
+                variable: Some(variable_node),
+                keyword: Some(keyword_node),
+                iterable: iterable.map(Box::new),
+                body,
+            }),
+        )
+    }
+
+    /// Parses the `{ ... }` of an `@if`, `@else` or `@each` block, which holds
+    /// the same constructs as a rule body.
+    fn parse_block_body(
+        &mut self,
+        node: Option<Node<'a>>,
+        previous_end: usize,
+    ) -> (Option<Node<'a>>, Option<Delimited<'a>>) {
+        match node {
+            Some(node) if node_token_matches!(node, ScopeOpen) => {
+                match self.parse_rule_scope_body(node, None) {
+                    Parsed(node, Some(Construct::Rule { body, .. })) => (node, body),
+                    Parsed(node, _) => (node, None),
+                }
+            }
+
+            node => {
+                self.ast_errors.push(
+                    ParseError::MissingToken {
+                        msg: Some(ParseErrorMessage::Expected(TokenKind::ScopeOpen.name())),
+                    },
+                    self.range_from_span(clamp_span_to_end(previous_end)),
+                );
+                (node, None)
+            }
+        }
+    }
+
     pub(crate) fn parse_tween(&mut self, node: Node<'a>) -> Parsed<'a> {
         if !node_token_matches!(node, TweenDeclaration) {
             return Parsed(Some(node), None);
@@ -320,7 +508,7 @@ impl<'a> RsmlParser<'a> {
             }
         };
 
-        let (next_node, datatype_groups) = self.parse_table_datatype_args(Some(first_node));
+        let (next_node, datatype_groups) = self.parse_table_datatype_args(Some(first_node), true);
 
         if !node_token_matches!(next_node, Some(ParensClose)) {
             let delimited = Delimited::new(open_node, datatype_groups, None);
@@ -651,7 +839,7 @@ impl<'a> RsmlParser<'a> {
         name_node: Option<Node<'a>>,
     ) -> Parsed<'a> {
         let mut node = match self.advance_until(
-            token_kind_list![MacroArgIdentifier, Comma, ParensClose],
+            token_kind_list![MacroArgIdentifier, MacroRestArgIdentifier, Comma, ParensClose],
             &TOKEN_KIND_INSIDE_PARENS_CONSTRUCT_DELIMITERS,
         ) {
             Some(Ok(node)) => node,
@@ -702,17 +890,17 @@ impl<'a> RsmlParser<'a> {
             );
         }
 
-        let mut args = vec![Construct::Node { node }];
+        let mut args = vec![self.parse_macro_def_arg(node)];
 
         loop {
             let advance_until_result = match last_token_value {
                 Token::Comma => self.advance_until(
-                    token_kind_list![MacroArgIdentifier, ParensClose],
+                    token_kind_list![MacroArgIdentifier, MacroRestArgIdentifier, ParensClose],
                     &TOKEN_KIND_INSIDE_PARENS_CONSTRUCT_DELIMITERS,
                 ),
 
                 _ => self.advance_until(
-                    token_kind_list![MacroArgIdentifier, Comma, ParensClose],
+                    token_kind_list![MacroArgIdentifier, MacroRestArgIdentifier, Comma, ParensClose],
                     &TOKEN_KIND_INSIDE_PARENS_CONSTRUCT_DELIMITERS,
                 ),
             };
@@ -758,11 +946,14 @@ impl<'a> RsmlParser<'a> {
                 );
             };
 
-            args.push(Construct::Node { node });
+            args.push(self.parse_macro_def_arg(node));
 
             if matches!(
                 (&last_token_value, &token_value),
-                (Token::MacroArgIdentifier(_), Token::MacroArgIdentifier(_))
+                (
+                    Token::MacroArgIdentifier(_) | Token::MacroRestArgIdentifier(_),
+                    Token::MacroArgIdentifier(_) | Token::MacroRestArgIdentifier(_)
+                )
             ) {
                 self.ast_errors.push(
                     ParseError::MissingToken {
@@ -777,6 +968,45 @@ impl<'a> RsmlParser<'a> {
         }
     }
 
+    /// Parses the `= default` which may follow an argument in a macro
+    /// definition. Defaulted arguments are kept as assignments.
+    fn parse_macro_def_arg(&mut self, arg_node: Node<'a>) -> Construct<'a> {
+        if !node_token_matches!(arg_node, MacroArgIdentifier(_)) {
+            return Construct::Node { node: arg_node };
+        }
+
+        match self.advance() {
+            Some(equals_node) if node_token_matches!(equals_node, Equals) => {
+                let value_node = self.advance();
+                let (node_status, value) =
+                    self.parse_datatype(value_node, TOKEN_KIND_INSIDE_PARENS_CONSTRUCT_DELIMITERS);
+
+                if value.is_none() {
+                    self.ast_errors.push(
+                        ParseError::MissingToken {
+                            msg: Some(ParseErrorMessage::Expected("a default value")),
+                        },
+                        self.range_from_span(clamp_span_to_end(equals_node.token.end())),
+                    );
+                }
+
+                self.pending_node = node_status.consume_err_or_advance(self);
+
+                Construct::Assignment {
+                    left: arg_node,
+                    middle: Some(equals_node),
+                    right: value.map(Box::new),
+                    terminator: None,
+                }
+            }
+
+            next_node => {
+                self.pending_node = next_node;
+                Construct::Node { node: arg_node }
+            }
+        }
+    }
+
     fn parse_macro_body_open(
         &mut self,
         declaration_node: Node<'a>,
@@ -987,6 +1217,12 @@ impl<'a> RsmlParser<'a> {
             node = parser
                 .parse_tween(node)
                 .handle_construct(&mut body_content)?;
+            node = parser
+                .parse_if(node)
+                .handle_construct(&mut body_content)?;
+            node = parser
+                .parse_each(node)
+                .handle_construct(&mut body_content)?;
 
             node = parser
                 .parse_static_token_assignment(node)
@@ -1246,10 +1482,13 @@ impl<'a> RsmlParser<'a> {
                     TagSelectorOrEnumPart,
                     StateSelectorOrEnumPart,
                     PseudoSelector,
+                    AttributeSelector,
                     QuerySelector,
                     ChildrenSelector,
                     DescendantsSelector,
                     MacroCallIdentifier,
+                    MacroArgIdentifier,
+                    MacroRestArgIdentifier,
                     ScopeClose
                 ]
             ),
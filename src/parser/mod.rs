@@ -82,6 +82,10 @@ impl<'a> RsmlParser<'a> {
 
             node = parser.parse_tween(node).handle_construct(&mut parser.ast)?;
 
+            node = parser.parse_if(node).handle_construct(&mut parser.ast)?;
+
+            node = parser.parse_each(node).handle_construct(&mut parser.ast)?;
+
             node = parser
                 .parse_static_token_assignment(node)
                 .handle_construct(&mut parser.ast)?;
@@ -523,6 +527,7 @@ mod tests {
     parser_test!(builtin_padding_two_args, r#"Frame { Padding!(10px, 20px); }"#);
     parser_test!(builtin_padding_three_args, r#"Frame { Padding!(10px, 20px, 30px); }"#);
     parser_test!(builtin_padding_four_args, r#"Frame { Padding!(10px, 20px, 30px, 40px); }"#);
+    parser_test!(builtin_padding_named_args, r#"Frame { Padding!(top = 4px, left = 2px); }"#);
     parser_test!(builtin_corner_radius, r#"Frame { CornerRadius!(8px); }"#);
     parser_test!(builtin_scale, r#"Frame { Scale!(1.5); }"#);
     parser_test!(macro_call_math_arg, r#"Frame { Padding!(0% + .5); }"#);
@@ -593,8 +598,24 @@ mod tests {
         assert!(parsed.ast_errors.0.iter().any(|d| d.code == "EMPTY_DIRECTIVE"));
     }
 
+    #[test]
+    fn string_escapes_parse_without_errors() {
+        let parsed = RsmlParser::parse_source(r#"Text = "say \"hi\"\n\u{2665}";"#);
+        assert!(parsed.ast_errors.0.is_empty(), "Expected no parse errors, got: {:?}", parsed.ast_errors.0);
+    }
+
+    #[test]
+    fn invalid_string_escape_emits_error() {
+        let parsed = RsmlParser::parse_source(r#"Text = "a\qb";"#);
+        let diagnostic = parsed.ast_errors.0.iter().find(|d| d.code == "INVALID_ESCAPE");
+        assert!(diagnostic.is_some_and(|d| d.range.start.character == 9 && d.range.end.character == 11));
+    }
+
     parser_test!(query_selector, r#"@media { }"#);
     parser_test!(query_selector_unknown, r#"@foobar { }"#);
+    parser_test!(attribute_selector, r#"Frame [Disabled] { }"#);
+    parser_test!(attribute_selector_value, r#".card[Variant = "primary"] { }"#);
+    parser_test!(attribute_selector_quoted_bracket, r#".card[Label = "a]b"] { }"#);
 
     parser_test!(empty_source, r#""#);
     parser_test!(multiple_top_level, "@priority 5;\nFrame { Size = 100; }");
@@ -673,4 +694,103 @@ Frame {
         macro_indirect_recursion_typechecker_error,
         "@macro A() -> Construct { B!(); }\n@macro B() -> Construct { A!(); }\nFrame { A!(); }"
     );
+    parser_test!(
+        macro_named_and_default_args,
+        "@macro Pad(&all, &side = 2px) -> Construct { ::UIPadding { PaddingTop = &all; PaddingLeft = &side; } }\nFrame { Pad!(4px); }\nTextLabel { Pad!(side = 8px, all = 1px); }"
+    );
+    parser_test!(
+        macro_rest_args_splice_into_annotations_and_calls,
+        "@macro Dims(&...dims) { Size = udim2(&...dims); }\n@macro Forward(&first, &...dims) { Dims!(&...dims); }\nFrame { Forward!(1, 0, 10, 0, 20); }"
+    );
+    parser_test!(
+        macro_rest_args_splice_into_selectors_and_loops,
+        "@macro Hide(&...targets) { Frame, &...targets { Visible = false; } }\n@macro Any(&...tags) -> Selector { TextLabel, &...tags }\n@macro Pad(&...sizes) { @each $!size in &...sizes { .p-$!size { } } }\nHide!(\".Primary\", \"#Close\");\nAny!(\".Title\") { }\nPad!(1, 2);"
+    );
+    parser_test!(
+        macro_datatype_call_with_args,
+        "@macro Square(&size) -> Datatype { udim2(&size, &size) }\n@macro Inset(&size) -> Datatype { Square!(&size) }\n@macro Tile(&size) { Size = Inset!(&size); }\nFrame { Size = Square!(40px); }\nTextLabel { Tile!(20px); }"
+    );
+    parser_test!(
+        macro_selector_args_substitute,
+        "@macro Themed(&tag) -> Selector { .&tag }\n@macro Padded(&size) -> Selector { .p-&size }\nThemed!(\"Primary\") { }\nPadded!(4) { }"
+    );
+    parser_test!(
+        macro_args_forwarded_into_selectors,
+        "@macro Named(&name) -> Selector { #&name }\n@macro Within(&class, &name) -> Selector { &class > Named!(&name) }\nWithin!(\"Frame\", \"Title\") { }\n@macro Card(&variant) { .Card-&variant { Visible = false; } }\nFrame { Card!(\"Raised\"); }"
+    );
+
+    parser_test!(
+        if_else_static_tokens,
+        "$!Platform = \"Console\";\n@if $!Platform == \"Console\" { Frame { Size = udim2(0, 10, 0, 10); } }\n@else { Frame { Size = udim2(0, 20, 0, 20); } }"
+    );
+    parser_test!(
+        if_else_in_rule,
+        "TextLabel {\n    $!Compact = false;\n    @if $!Compact { Size = udim2(0, 1, 0, 1); } @else { Size = udim2(0, 2, 0, 2); }\n    @if $!Platform ~= \"Mobile\" { Visible = false; }\n}"
+    );
+    parser_test!(if_missing_condition, r#"@if { Frame { } }"#);
+    parser_test!(
+        each_loop_interpolated_rules,
+        "@each $!size in (1, 2, 4) {\n    .p-$!size { Size = udim2(0, $!size, 0, $!size); }\n}"
+    );
+    parser_test!(
+        each_loop_palette_family,
+        "@each $!color in tw:red {\n    Frame { BackgroundColor3 = $!color; }\n}"
+    );
+    parser_test!(
+        rule_functional_selectors,
+        "TextButton:not(.Ghost, #Close) { }\n:is(TextButton, TextLabel) { }"
+    );
+    parser_test!(
+        rule_parent_reference,
+        ".Button, .Link {\n    $!Inset = 4;\n    &:Hover { Size = udim2(0, $!Inset, 0, $!Inset); }\n    &.Primary, & > #Icon { Visible = false; }\n}"
+    );
+    parser_test!(
+        value_string_backtick,
+        "$!Name = \"World\";\nTextLabel { Text = `Hello {$!Name}!`; }\nTextBox { PlaceholderText = `Hello {$Name}!`; }"
+    );
+    parser_test!(
+        value_string_joined_from_dynamic_tokens,
+        "$IconId = 1234;\n$Icon = \"rbxassetid://\" .. $IconId;\nTextLabel { Text = \"Hi \" .. $Name; }"
+    );
+    parser_test!(
+        value_color_translucent,
+        "$!Overlay = hsla(0, 0%, 0%, 25%);\nFrame { BackgroundColor3 = $!Overlay; }\n::UIStroke { Color = hsva(0, 0%, 100%, 0.5); }"
+    );
+
+    #[cfg(feature = "compiler")]
+    #[test]
+    fn compiler_palette_colors_resolve_against_the_given_palettes() {
+        use crate::datatype::palette::Palettes;
+        use rbx_types::Variant;
+
+        let source = "Frame { BackgroundColor3 = acme:primary:500; }";
+        let palettes = Palettes::from_json(r#"{ "acme:primary:500": [1.0, 0.0, 0.0] }"#).unwrap();
+
+        let compiled = RsmlCompiler::with_palettes(RsmlParser::from_source(source), &[], &palettes);
+        let frame = compiled[0].as_ref().unwrap();
+        assert!(matches!(frame.properties.get("BackgroundColor3"), Some(Variant::Color3(_))));
+
+        let compiled = RsmlCompiler::from_source(source);
+        let frame = compiled[0].as_ref().unwrap();
+        assert_eq!(frame.properties.get("BackgroundColor3"), None);
+    }
+
+    #[cfg(feature = "compiler")]
+    #[test]
+    fn compiler_derived_macros_expand_and_resolve_duplicates_locally() {
+        let base = RsmlParser::from_source(
+            "@macro Width (&w) { Size = udim2(0, &w, 0, 0); }\n\
+             @macro Height (&h) { Size = udim2(0, 0, 0, &h); }",
+        );
+        let compiled = RsmlCompiler::with_derived(
+            RsmlParser::from_source(
+                "@macro Height (&h) { Size = udim2(0, 1, 0, &h); }\n\
+                 Frame { Width!(10); }\n\
+                 TextLabel { Height!(20); }",
+            ),
+            &[&base],
+        );
+
+        insta::assert_debug_snapshot!(compiled);
+    }
 }
@@ -43,6 +43,7 @@ pub enum ParseError<'a> {
     UnknownDirective { name: String },
     EmptyDirective,
     DirectiveNotAtTop { name: String },
+    InvalidEscape { sequence: String },
 }
 
 impl<'a> ParseError<'a> {
@@ -52,7 +53,8 @@ impl<'a> ParseError<'a> {
             Self::MissingToken { .. } |
             Self::UnknownDirective { .. } |
             Self::EmptyDirective |
-            Self::DirectiveNotAtTop { .. } => Severity::Error,
+            Self::DirectiveNotAtTop { .. } |
+            Self::InvalidEscape { .. } => Severity::Error,
         }
     }
 
@@ -73,6 +75,7 @@ impl<'a> ParseError<'a> {
             Self::DirectiveNotAtTop { .. } => String::from(
                 "Directives must appear at the top of the file"
             ),
+            Self::InvalidEscape { sequence } => format!("Invalid escape sequence: \"{sequence}\""),
         }
     }
 
@@ -112,6 +115,7 @@ impl<'a> ToString for ParseError<'a> {
             Self::UnknownDirective { .. } => "UNKNOWN_DIRECTIVE",
             Self::EmptyDirective => "EMPTY_DIRECTIVE",
             Self::DirectiveNotAtTop { .. } => "DIRECTIVE_NOT_AT_TOP",
+            Self::InvalidEscape { .. } => "INVALID_ESCAPE",
         }.into()
     }
 }
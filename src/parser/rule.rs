@@ -14,7 +14,7 @@ impl<'a> RsmlParser<'a> {
                 "property assignment, selector part or rule body", [
                     Equals, ScopeOpen, Identifier, NameSelector,
                     TagSelectorOrEnumPart, StateSelectorOrEnumPart,
-                    PseudoSelector, Comma, ChildrenSelector, DescendantsSelector,
+                    PseudoSelector, AttributeSelector, Comma, ChildrenSelector, DescendantsSelector,
                     MacroCallIdentifier
                 ]
             ),
@@ -142,8 +142,9 @@ impl<'a> RsmlParser<'a> {
         match node.token.value() {
             Token::NameSelector(_) | Token::TagSelectorOrEnumPart(_) |
             Token::StateSelectorOrEnumPart(_) | Token::PseudoSelector(_) |
-            Token::QuerySelector(_) | Token::ChildrenSelector |
-            Token::DescendantsSelector => {
+            Token::AttributeSelector(_) | Token::QuerySelector(_) | Token::ChildrenSelector |
+            Token::DescendantsSelector | Token::MacroArgIdentifier(None) |
+            Token::MacroRestArgIdentifier(_) => {
                 let token = node.token.clone();
                 self.parse_rule_scope_selector(token, vec![SelectorNode::Token(node)], true)
             },
@@ -183,12 +184,14 @@ impl<'a> RsmlParser<'a> {
         let result = if comma_allowed {
             self.advance_until(token_kind_list!("selector part", [
                 Identifier, NameSelector, TagSelectorOrEnumPart, StateSelectorOrEnumPart, PseudoSelector,
-                QuerySelector, ChildrenSelector, DescendantsSelector, MacroCallIdentifier, ScopeOpen, ScopeClose, Comma
+                AttributeSelector, QuerySelector, ChildrenSelector, DescendantsSelector, MacroCallIdentifier, MacroArgIdentifier,
+                MacroRestArgIdentifier, ParensOpen, ScopeOpen, ScopeClose, Comma
             ]), &TOKEN_KIND_CONSTRUCT_DELIMITERS)
         } else {
             self.advance_until(token_kind_list!("selector part", [
                 Identifier, NameSelector, TagSelectorOrEnumPart, StateSelectorOrEnumPart, PseudoSelector,
-                QuerySelector, ChildrenSelector, DescendantsSelector, MacroCallIdentifier, ScopeOpen, ScopeClose
+                AttributeSelector, QuerySelector, ChildrenSelector, DescendantsSelector, MacroCallIdentifier, MacroArgIdentifier,
+                MacroRestArgIdentifier, ParensOpen, ScopeOpen, ScopeClose
             ]), &TOKEN_KIND_CONSTRUCT_DELIMITERS)
         };
 
@@ -200,6 +203,25 @@ impl<'a> RsmlParser<'a> {
 
         self.handle_hierarchy_selector_without_part(&last_token, &node.token);
 
+        if node_token_matches!(node, ParensOpen) {
+            let Some(name) = take_functional_selector_name(&mut selectors) else {
+                self.ast_errors.push(ParseError::UnexpectedTokens { msg: None }, self.range_from_span(node.token.span()));
+                return self.parse_selector_tokens(last_token, selectors, comma_allowed)
+            };
+
+            let (selector_node, next_node) = self.parse_functional_selector(name, node);
+            let close_token = match &selector_node {
+                SelectorNode::Functional { close: Some(close), .. } => Some(close.token.clone()),
+                _ => None
+            };
+            selectors.push(selector_node);
+
+            return match close_token {
+                Some(token) => self.parse_selector_tokens(token, selectors, true),
+                None => (next_node, selectors)
+            }
+        }
+
         if node_token_matches!(node, ScopeOpen) || node_token_matches!(node, ScopeClose) {
             if !comma_allowed && matches!(last_token.value(), Token::Comma) {
                 self.ast_errors.push(
@@ -248,6 +270,59 @@ impl<'a> RsmlParser<'a> {
         }
     }
 
+    /// Parses the selectors of a `:not(...)` or `:is(...)` selector, up to and
+    /// including its `)`. Also returns the node parsing stopped at if the `)`
+    /// is missing.
+    fn parse_functional_selector(
+        &mut self, name: Node<'a>, open: Node<'a>
+    ) -> (SelectorNode<'a>, Option<Node<'a>>) {
+        let mut selectors = vec![];
+        let mut last_end = open.token.end();
+
+        loop {
+            let node = match self.advance_until(token_kind_list!("selector part", [
+                Identifier, NameSelector, TagSelectorOrEnumPart, StateSelectorOrEnumPart, PseudoSelector,
+                AttributeSelector, QuerySelector, ChildrenSelector, DescendantsSelector, Comma, ParensOpen, ParensClose
+            ]), &TOKEN_KIND_CONSTRUCT_DELIMITERS) {
+                Some(Ok(node)) => node,
+
+                result => {
+                    self.ast_errors.push(
+                        ParseError::MissingToken { msg: Some(ParseErrorMessage::Expected(TokenKind::ParensClose.name())) },
+                        self.range_from_span(clamp_span_to_end(last_end))
+                    );
+
+                    let functional = SelectorNode::Functional { name, open, selectors, close: None };
+                    return (functional, result.and_then(Result::err))
+                }
+            };
+
+            if node_token_matches!(node, ParensClose) {
+                return (SelectorNode::Functional { name, open, selectors, close: Some(node) }, None)
+            }
+
+            if node_token_matches!(node, ParensOpen) {
+                let Some(inner_name) = take_functional_selector_name(&mut selectors) else {
+                    self.ast_errors.push(ParseError::UnexpectedTokens { msg: None }, self.range_from_span(node.token.span()));
+                    continue
+                };
+
+                let (inner, next_node) = self.parse_functional_selector(inner_name, node);
+                last_end = inner.end();
+                selectors.push(inner);
+
+                if let Some(next_node) = next_node {
+                    let functional = SelectorNode::Functional { name, open, selectors, close: None };
+                    return (functional, Some(next_node))
+                }
+                continue
+            }
+
+            last_end = node.token.end();
+            selectors.push(SelectorNode::Token(node));
+        }
+    }
+
     fn handle_hierarchy_selector_without_part(&mut self, last_token: &SpannedToken<'a>, token: &SpannedToken<'a>) {
         if !(
             matches!(last_token.value(), Token::DescendantsSelector | Token::ChildrenSelector) &&
@@ -293,6 +368,10 @@ impl<'a> RsmlParser<'a> {
 
                 node = parser.parse_tween(node).handle_construct(&mut body_content)?;
 
+                node = parser.parse_if(node).handle_construct(&mut body_content)?;
+
+                node = parser.parse_each(node).handle_construct(&mut body_content)?;
+
                 node = parser.parse_static_token_assignment(node).handle_construct(&mut body_content)?;
 
                 node = parser.parse_token_assignment(node).handle_construct(&mut body_content)?;
@@ -327,3 +406,17 @@ impl<'a> RsmlParser<'a> {
         }
     }
 }
+
+/// Takes the `:not` or `:is` before a `(` off the end of `selectors`.
+fn take_functional_selector_name<'a>(selectors: &mut Vec<SelectorNode<'a>>) -> Option<Node<'a>> {
+    let is_functional = matches!(
+        selectors.last(),
+        Some(SelectorNode::Token(node)) if node_token_matches!(node, StateSelectorOrEnumPart(Some("not" | "is")))
+    );
+    if !is_functional { return None }
+
+    match selectors.pop() {
+        Some(SelectorNode::Token(node)) => Some(node),
+        _ => None
+    }
+}
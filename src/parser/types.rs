@@ -93,13 +93,20 @@ pub enum SelectorNode<'a> {
         name: Node<'a>,
         body: Option<Delimited<'a>>,
     },
+    /// A `:not(...)` or `:is(...)` selector wrapping a list of selectors.
+    Functional {
+        name: Node<'a>,
+        open: Node<'a>,
+        selectors: Vec<SelectorNode<'a>>,
+        close: Option<Node<'a>>,
+    },
 }
 
 impl<'a> SelectorNode<'a> {
     pub fn start(&self) -> usize {
         match self {
             Self::Token(node) => node.token.start(),
-            Self::MacroCall { name, .. } => name.token.start(),
+            Self::MacroCall { name, .. } | Self::Functional { name, .. } => name.token.start(),
         }
     }
 }
@@ -114,6 +121,12 @@ impl<'a> SpanEnd for SelectorNode<'a> {
                 }
                 name.token.end()
             }
+            Self::Functional { open, selectors, close, .. } => {
+                if let Some(close) = close {
+                    return close.token.end();
+                }
+                selectors.last().map(|selector| selector.end()).unwrap_or(open.token.end())
+            }
         }
     }
 }
@@ -199,6 +212,22 @@ pub enum Construct<'a> {
         body: Option<Delimited<'a>>,
     },
 
+    If {
+        declaration: Node<'a>,
+        condition: Option<Box<Construct<'a>>>,
+        body: Option<Delimited<'a>>,
+        else_declaration: Option<Node<'a>>,
+        else_body: Option<Delimited<'a>>,
+    },
+
+    Each {
+        declaration: Node<'a>,
+        variable: Option<Node<'a>>,
+        keyword: Option<Node<'a>>,
+        iterable: Option<Box<Construct<'a>>>,
+        body: Option<Delimited<'a>>,
+    },
+
     Assignment {
         left: Node<'a>,
         middle: Option<Node<'a>>,
@@ -257,6 +286,8 @@ impl<'a> Construct<'a> {
             Self::Priority { .. } => "Priorities",
             Self::Tween { .. } => "Tweens",
             Self::Rule { .. } => "Rules",
+            Self::If { .. } => "Conditional blocks",
+            Self::Each { .. } => "Loops",
             Self::Assignment { left, .. } => match left.token.value() {
                 Token::Identifier(_) => "Property assignments",
                 Token::StaticTokenIdentifier(_) => "Static token assignments",
@@ -277,7 +308,9 @@ impl<'a> Construct<'a> {
 
             Self::Derive { declaration, .. }
             | Self::Priority { declaration, .. }
-            | Self::Tween { declaration, .. } => declaration.token.start(),
+            | Self::Tween { declaration, .. }
+            | Self::If { declaration, .. }
+            | Self::Each { declaration, .. } => declaration.token.start(),
 
             Self::Rule { selectors, body } => {
                 if let Some(first) = selectors.as_ref().and_then(|s| s.first()) {
@@ -406,6 +439,58 @@ impl<'a> SpanEnd for Construct<'a> {
                 0
             }
 
+            Self::If {
+                declaration,
+                condition,
+                body,
+                else_declaration,
+                else_body,
+            } => {
+                if let Some(else_body) = else_body {
+                    return else_body.end();
+                }
+
+                if let Some(else_declaration) = else_declaration {
+                    return else_declaration.token.end();
+                }
+
+                if let Some(body) = body {
+                    return body.end();
+                }
+
+                if let Some(condition) = condition {
+                    return condition.end();
+                }
+
+                declaration.token.end()
+            }
+
+            Self::Each {
+                declaration,
+                variable,
+                keyword,
+                iterable,
+                body,
+            } => {
+                if let Some(body) = body {
+                    return body.end();
+                }
+
+                if let Some(iterable) = iterable {
+                    return iterable.end();
+                }
+
+                if let Some(keyword) = keyword {
+                    return keyword.token.end();
+                }
+
+                if let Some(variable) = variable {
+                    return variable.token.end();
+                }
+
+                declaration.token.end()
+            }
+
             Self::Assignment {
                 left,
                 middle,
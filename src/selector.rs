@@ -0,0 +1,172 @@
+use crate::datatype::{StaticLookup, interpolate_rest_selectors, interpolate_tag};
+use crate::lexer::{AttributeSelector, Token};
+use crate::parser::types::SelectorNode;
+
+/// A selector node paired with the lookup its static tokens and macro
+/// arguments resolve through. Nodes spliced in from a selector macro resolve
+/// against that macro's arguments rather than the rule's.
+pub type ScopedSelector<'s> = (&'s SelectorNode<'s>, &'s dyn StaticLookup);
+
+/// Static tokens and macro arguments in tags and names, such as `$!size` in
+/// `.p-$!size`, are resolved through each node's lookup.
+pub fn build_selector_string(selectors: &[ScopedSelector]) -> String {
+    let mut result = String::new();
+    let mut last_token_kind: Option<SelectorTokenKind> = None;
+
+    for &(selector_node, lookup) in selectors {
+        let (kind, text) = match selector_node {
+            SelectorNode::Token(node) => classify_and_text(&node.token.1, lookup),
+
+            // `:not(...)` and `:is(...)` are written into the StyleRule's
+            // selector as they are, so they only match where the engine's
+            // selector syntax supports them.
+            SelectorNode::Functional { name, selectors, .. } => {
+                let Token::StateSelectorOrEnumPart(Some(name)) = name.token.value() else {
+                    continue;
+                };
+                let inner: Vec<ScopedSelector> =
+                    selectors.iter().map(|selector| (selector, lookup)).collect();
+                let text = format!(":{}({})", name, build_selector_string(&inner));
+                (SelectorTokenKind::StateOrEnum, text)
+            }
+
+            SelectorNode::MacroCall { .. } => continue,
+        };
+
+        if should_add_space(last_token_kind, kind) {
+            result.push(' ');
+        }
+
+        result.push_str(&text);
+        last_token_kind = Some(kind);
+    }
+
+    result
+}
+
+/// Joins selectors which begin with a parent reference (`&`) onto each of the
+/// parent's alternatives, so `&:Hover` inside `.A, .B` becomes
+/// `.A:Hover, .B:Hover`. Returns `None` unless every alternative begins with
+/// `&`.
+pub fn build_compound_selector_string(
+    selectors: &[ScopedSelector],
+    parent: &str,
+) -> Option<String> {
+    let mut compounds = vec![];
+
+    for alternative in selectors.split(|node| is_token(node, |token| matches!(token, Token::Comma))) {
+        let (first, rest) = alternative.split_first()?;
+        if !is_token(first, |token| matches!(token, Token::MacroArgIdentifier(None))) {
+            return None;
+        }
+
+        let joiner = match rest.first() {
+            Some(node) if is_token(node, |token| {
+                matches!(token, Token::ChildrenSelector | Token::DescendantsSelector)
+            }) => " ",
+            _ => "",
+        };
+        let rest = build_selector_string(rest);
+
+        for parent_alternative in selector_alternatives(parent) {
+            compounds.push(format!("{parent_alternative}{joiner}{rest}"));
+        }
+    }
+
+    Some(compounds.join(", "))
+}
+
+fn is_token((selector_node, _): &ScopedSelector, predicate: impl Fn(&Token) -> bool) -> bool {
+    matches!(selector_node, SelectorNode::Token(node) if predicate(node.token.value()))
+}
+
+/// Splits a selector string into its comma separated alternatives. Commas
+/// within brackets or parentheses don't separate alternatives.
+fn selector_alternatives(selector: &str) -> Vec<&str> {
+    let mut alternatives = vec![];
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (idx, c) in selector.char_indices() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                alternatives.push(selector[start..idx].trim());
+                start = idx + 1;
+            }
+            _ => (),
+        }
+    }
+
+    alternatives.push(selector[start..].trim());
+    alternatives
+}
+
+#[derive(Clone, Copy)]
+enum SelectorTokenKind {
+    Text,
+    ScopeOperator,
+    Comma,
+    StateOrEnum,
+}
+
+fn classify_and_text<'a>(
+    token: &'a Token<'a>,
+    lookup: &dyn StaticLookup,
+) -> (SelectorTokenKind, String) {
+    match token {
+        Token::Identifier(s) => (SelectorTokenKind::Text, s.to_string()),
+        Token::QuerySelector(s) => (SelectorTokenKind::Text, format!("@{}", s)),
+        Token::ChildrenSelector => (SelectorTokenKind::ScopeOperator, ">".to_string()),
+        Token::DescendantsSelector => (SelectorTokenKind::ScopeOperator, ">>".to_string()),
+        Token::Comma => (SelectorTokenKind::Comma, ",".to_string()),
+        Token::NameSelector(s) => {
+            let name = interpolate_tag(s, lookup).unwrap_or_else(|| s.to_string());
+            (SelectorTokenKind::Text, format!("#{}", name))
+        }
+        Token::PseudoSelector(s) => (SelectorTokenKind::Text, format!("::{}", s)),
+        Token::AttributeSelector(AttributeSelector { name, value: Some(value) }) => {
+            (SelectorTokenKind::Text, format!("[{} = {}]", name, value))
+        }
+        Token::AttributeSelector(AttributeSelector { name, value: None }) => {
+            (SelectorTokenKind::Text, format!("[{}]", name))
+        }
+        Token::TagSelectorOrEnumPart(Some(s)) => {
+            let tag = interpolate_tag(s, lookup).unwrap_or_else(|| s.to_string());
+            (SelectorTokenKind::Text, format!(".{}", tag))
+        }
+        Token::TagSelectorOrEnumPart(None) => (SelectorTokenKind::Text, ".".to_string()),
+        Token::StateSelectorOrEnumPart(Some(s)) => {
+            (SelectorTokenKind::StateOrEnum, format!(":{}", s))
+        }
+        Token::StateSelectorOrEnumPart(None) => (SelectorTokenKind::StateOrEnum, ":".to_string()),
+        Token::MacroArgIdentifier(Some(s)) => {
+            let arg = format!("&{}", s);
+            (SelectorTokenKind::Text, interpolate_tag(&arg, lookup).unwrap_or(arg))
+        }
+        Token::MacroRestArgIdentifier(Some(s)) => {
+            let selectors = interpolate_rest_selectors(s, lookup).unwrap_or_default();
+            (SelectorTokenKind::Text, selectors)
+        }
+        _ => (SelectorTokenKind::Text, String::new()),
+    }
+}
+
+fn should_add_space(last: Option<SelectorTokenKind>, current: SelectorTokenKind) -> bool {
+    let Some(last) = last else {
+        return false;
+    };
+
+    if matches!(
+        current,
+        SelectorTokenKind::Comma | SelectorTokenKind::StateOrEnum
+    ) {
+        return false;
+    }
+
+    matches!(
+        last,
+        SelectorTokenKind::ScopeOperator | SelectorTokenKind::Text | SelectorTokenKind::Comma
+    )
+}
@@ -0,0 +1,414 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::iter::Peekable;
+use std::ops::Add;
+use std::str::CharIndices;
+
+use rbx_types::Variant;
+
+/// How strongly a selector binds. Fields are compared in declaration order, so
+/// a single name selector outweighs any number of tags, states or attributes,
+/// which in turn outweigh class and pseudo selectors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Specificity {
+    /// `#Name` selectors.
+    pub names: u32,
+    /// `.Tag`, `:State`, `[Attribute]` and `@Query` selectors.
+    pub modifiers: u32,
+    /// `ClassName` and `::Pseudo` selectors.
+    pub classes: u32,
+}
+
+impl Add for Specificity {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            names: self.names + rhs.names,
+            modifiers: self.modifiers + rhs.modifiers,
+            classes: self.classes + rhs.classes,
+        }
+    }
+}
+
+/// Where a rule sits when two rules set the same property on one instance:
+/// `@priority` decides first, then specificity, then declaration order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Precedence {
+    pub priority: i32,
+    pub specificity: Specificity,
+    pub order: usize,
+}
+
+/// One comma-separated alternative of a selector, with its parents' selectors
+/// folded in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelectorTarget {
+    pub specificity: Specificity,
+    /// The class the alternative matches, or `None` if it can match any class.
+    pub class_name: Option<String>,
+    pub names: Vec<String>,
+    pub states: Vec<String>,
+    has_combinator: bool,
+    /// Whether the alternative ends in a `::Pseudo` selector.
+    pseudo: bool,
+    /// For a pseudo instance nested in a rule, the alternative its parent
+    /// instance matches. Pseudo instances of different parents never overlap.
+    owner: Option<Box<SelectorTarget>>,
+}
+
+impl SelectorTarget {
+    /// Whether a single instance could be matched by both alternatives at once.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        if let (Some(a), Some(b)) = (&self.class_name, &other.class_name)
+            && a != b
+        {
+            return false;
+        }
+
+        if let (Some(a), Some(b)) = (&self.owner, &other.owner)
+            && !a.overlaps(b)
+        {
+            return false;
+        }
+
+        let disjoint = |a: &[String], b: &[String]| {
+            !a.is_empty() && !b.is_empty() && !a.iter().any(|item| b.contains(item))
+        };
+
+        !disjoint(&self.names, &other.names) && !disjoint(&self.states, &other.states)
+    }
+
+    /// The alternative as written inside a rule whose selector matches `parent`.
+    pub fn nested_in(&self, parent: &Self) -> Self {
+        if self.pseudo {
+            return Self {
+                specificity: parent.specificity + self.specificity,
+                owner: Some(Box::new(parent.clone())),
+                ..self.clone()
+            };
+        }
+
+        if self.has_combinator {
+            return Self {
+                specificity: parent.specificity + self.specificity,
+                ..self.clone()
+            };
+        }
+
+        let mut names = parent.names.clone();
+        names.extend(self.names.iter().cloned());
+
+        let mut states = parent.states.clone();
+        states.extend(self.states.iter().cloned());
+
+        Self {
+            specificity: parent.specificity + self.specificity,
+            class_name: self.class_name.clone().or_else(|| parent.class_name.clone()),
+            names,
+            states,
+            has_combinator: parent.has_combinator,
+            pseudo: parent.pseudo,
+            owner: parent.owner.clone(),
+        }
+    }
+}
+
+/// Splits a selector string produced by `build_selector_string` into its
+/// alternatives.
+pub fn parse_selector(selector: &str) -> Vec<SelectorTarget> {
+    let mut targets = vec![SelectorTarget::default()];
+    let mut chars = selector.char_indices().peekable();
+
+    while let Some(&(_, c)) = chars.peek() {
+        let target = targets.last_mut().unwrap();
+
+        match c {
+            ',' => {
+                chars.next();
+                targets.push(SelectorTarget::default());
+            }
+
+            '>' => {
+                chars.next();
+                target.has_combinator = true;
+                target.class_name = None;
+                target.names.clear();
+                target.states.clear();
+                target.pseudo = false;
+            }
+
+            '#' => {
+                chars.next();
+                target.specificity.names += 1;
+                target.names.push(read_ident(&mut chars));
+            }
+
+            '.' | '@' => {
+                chars.next();
+                target.specificity.modifiers += 1;
+                read_ident(&mut chars);
+            }
+
+            ':' => {
+                chars.next();
+                if matches!(chars.peek(), Some((_, ':'))) {
+                    chars.next();
+                    target.specificity.classes += 1;
+                    target.pseudo = true;
+                    target.class_name = Some(read_ident(&mut chars));
+                } else {
+                    let state = read_ident(&mut chars);
+
+                    // `:not(...)` and `:is(...)` are as specific as the most
+                    // specific selector they wrap, and don't narrow the states.
+                    if matches!(chars.peek(), Some((_, '('))) {
+                        chars.next();
+                        let inner = read_until_close_paren(&mut chars);
+                        target.specificity = target.specificity
+                            + parse_selector(&inner)
+                                .into_iter()
+                                .map(|inner| inner.specificity)
+                                .max()
+                                .unwrap_or_default();
+                    } else {
+                        target.specificity.modifiers += 1;
+                        target.states.push(state);
+                    }
+                }
+            }
+
+            '[' => {
+                let mut in_string = None;
+                for (_, c) in chars.by_ref() {
+                    match (in_string, c) {
+                        (None, '"' | '\'') => in_string = Some(c),
+                        (Some(quote), _) if quote == c => in_string = None,
+                        (None, ']') => break,
+                        _ => {}
+                    }
+                }
+                target.specificity.modifiers += 1;
+            }
+
+            c if c.is_alphabetic() || c == '_' => {
+                target.specificity.classes += 1;
+                target.class_name = Some(read_ident(&mut chars));
+            }
+
+            _ => {
+                chars.next();
+            }
+        }
+    }
+
+    targets
+}
+
+fn read_ident(chars: &mut Peekable<CharIndices>) -> String {
+    let mut ident = String::new();
+    while let Some(&(_, c)) = chars.peek() {
+        if !(c.is_alphanumeric() || c == '_') {
+            break;
+        }
+        ident.push(c);
+        chars.next();
+    }
+    ident
+}
+
+/// Reads up to the `)` closing a paren which has already been consumed.
+fn read_until_close_paren(chars: &mut Peekable<CharIndices>) -> String {
+    let mut inner = String::new();
+    let mut depth = 0usize;
+
+    for (_, c) in chars.by_ref() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => break,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        inner.push(c);
+    }
+
+    inner
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// Both rules have the same priority and specificity, so only declaration
+    /// order decides which value is applied.
+    Ambiguous,
+    /// The later rule loses to an earlier one with a higher priority or
+    /// specificity.
+    Overridden,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleConflict {
+    pub property: String,
+    pub kind: ConflictKind,
+    /// The rule whose value is applied.
+    pub winner: usize,
+    /// The rule whose value is discarded.
+    pub loser: usize,
+    pub winner_precedence: Precedence,
+    pub loser_precedence: Precedence,
+}
+
+/// What the conflict analysis needs to know about a rule, whether it comes
+/// from a compiled tree or straight from the AST.
+#[derive(Debug, Clone, Default)]
+pub struct RuleSummary<'a> {
+    /// Identifies the rule in the conflicts reported. Rules declared later
+    /// have larger indices.
+    pub idx: usize,
+    pub priority: i32,
+    /// The selector alternatives the rule matches, including its parents'.
+    pub targets: Vec<SelectorTarget>,
+    pub properties: BTreeMap<&'a str, &'a Variant>,
+    /// The `@if` blocks the rule sits in, as the block and which of its
+    /// branches. Rules in different branches of one block never both apply.
+    pub branches: Vec<(usize, bool)>,
+}
+
+impl RuleSummary<'_> {
+    fn excludes(&self, other: &Self) -> bool {
+        self.branches.iter().any(|(block, branch)| {
+            other.branches.iter().any(|(other_block, other_branch)| {
+                block == other_block && branch != other_branch
+            })
+        })
+    }
+}
+
+/// Reports every pair of rules that can match the same instance and set the
+/// same property to different values, where the winner is decided only by
+/// declaration order or the later rule silently loses. `rules` are expected
+/// in declaration order.
+pub fn analyze_conflicts(rules: &[RuleSummary]) -> Vec<RuleConflict> {
+    let mut conflicts = vec![];
+
+    for (i, earlier) in rules.iter().enumerate() {
+        for later in &rules[i + 1..] {
+            if earlier.excludes(later) {
+                continue;
+            }
+
+            for (property, earlier_value) in &earlier.properties {
+                let Some(later_value) = later.properties.get(property) else {
+                    continue;
+                };
+
+                if earlier_value == later_value {
+                    continue;
+                }
+
+                if let Some(conflict) = classify_conflict(property, earlier, later) {
+                    conflicts.push(conflict);
+                }
+            }
+        }
+    }
+
+    conflicts
+}
+
+fn classify_conflict(
+    property: &str,
+    earlier: &RuleSummary,
+    later: &RuleSummary,
+) -> Option<RuleConflict> {
+    let mut ambiguous = None;
+
+    for earlier_target in &earlier.targets {
+        for later_target in &later.targets {
+            if !earlier_target.overlaps(later_target) {
+                continue;
+            }
+
+            let earlier_precedence = precedence(earlier, earlier_target);
+            let later_precedence = precedence(later, later_target);
+
+            let conflict = |kind, winner: (usize, Precedence), loser: (usize, Precedence)| {
+                RuleConflict {
+                    property: property.to_string(),
+                    kind,
+                    winner: winner.0,
+                    loser: loser.0,
+                    winner_precedence: winner.1,
+                    loser_precedence: loser.1,
+                }
+            };
+
+            match (earlier_precedence.priority, earlier_precedence.specificity)
+                .cmp(&(later_precedence.priority, later_precedence.specificity))
+            {
+                Ordering::Greater => {
+                    return Some(conflict(
+                        ConflictKind::Overridden,
+                        (earlier.idx, earlier_precedence),
+                        (later.idx, later_precedence),
+                    ));
+                }
+
+                Ordering::Equal => {
+                    ambiguous.get_or_insert(conflict(
+                        ConflictKind::Ambiguous,
+                        (later.idx, later_precedence),
+                        (earlier.idx, earlier_precedence),
+                    ));
+                }
+
+                Ordering::Less => {}
+            }
+        }
+    }
+
+    ambiguous
+}
+
+fn precedence(rule: &RuleSummary, target: &SelectorTarget) -> Precedence {
+    Precedence {
+        priority: rule.priority,
+        specificity: target.specificity,
+        order: rule.idx,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_outweighs_modifiers_and_classes() {
+        let name = parse_selector("#Title")[0].specificity;
+        let tagged = parse_selector("TextLabel .big:Hover [Disabled]")[0].specificity;
+        assert!(name > tagged);
+    }
+
+    #[test]
+    fn alternatives_are_split_on_commas() {
+        let targets = parse_selector("Frame, TextLabel .title");
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[1].class_name.as_deref(), Some("TextLabel"));
+        assert_eq!(targets[1].specificity.modifiers, 1);
+    }
+
+    #[test]
+    fn negation_takes_the_specificity_of_its_argument() {
+        let targets = parse_selector("TextButton:not(.Ghost, #Close), Frame");
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].specificity.names, 1);
+        assert_eq!(targets[0].specificity.modifiers, 0);
+        assert!(targets[0].states.is_empty());
+    }
+
+    #[test]
+    fn combinator_resets_target() {
+        let targets = parse_selector("Frame > .title");
+        assert_eq!(targets[0].class_name, None);
+        assert_eq!(targets[0].specificity.classes, 1);
+    }
+}
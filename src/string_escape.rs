@@ -0,0 +1,157 @@
+use std::borrow::Cow;
+
+/// Resolves Luau escape sequences in the content of a quoted string. Invalid
+/// escapes are kept verbatim, and `on_invalid` is called with each one's byte
+/// span within `raw`. Like Luau, byte escapes (`\xFF`, `\255`) cover the full
+/// byte range, so the result isn't necessarily valid UTF-8.
+pub fn unescape_with<'a>(
+    raw: &'a str,
+    mut on_invalid: impl FnMut((usize, usize)),
+) -> Cow<'a, [u8]> {
+    if !raw.contains('\\') {
+        return Cow::Borrowed(raw.as_bytes());
+    }
+
+    let mut result = Vec::with_capacity(raw.len());
+    let mut chars = raw.char_indices().peekable();
+
+    while let Some((start, ch)) = chars.next() {
+        if ch != '\\' {
+            push_char(&mut result, ch);
+            continue;
+        }
+
+        let Some((_, escape)) = chars.next() else {
+            on_invalid((start, raw.len()));
+            result.push(b'\\');
+            break;
+        };
+
+        let resolved = match escape {
+            'a' => Some(Escaped::Byte(0x07)),
+            'b' => Some(Escaped::Byte(0x08)),
+            'f' => Some(Escaped::Byte(0x0C)),
+            'n' | '\n' => Some(Escaped::Byte(b'\n')),
+            'r' => Some(Escaped::Byte(b'\r')),
+            't' => Some(Escaped::Byte(b'\t')),
+            'v' => Some(Escaped::Byte(0x0B)),
+            '\\' | '"' | '\'' => Some(Escaped::Char(escape)),
+
+            'z' => {
+                while chars.next_if(|(_, ch)| ch.is_whitespace()).is_some() {}
+                continue;
+            }
+
+            'x' => {
+                let digits: String = (0..2)
+                    .map_while(|_| chars.next_if(|(_, ch)| ch.is_ascii_hexdigit()))
+                    .map(|(_, ch)| ch)
+                    .collect();
+
+                u8::from_str_radix(&digits, 16)
+                    .ok()
+                    .filter(|_| digits.len() == 2)
+                    .map(Escaped::Byte)
+            }
+
+            '0'..='9' => {
+                let mut digits = String::from(escape);
+                while digits.len() < 3
+                    && let Some((_, digit)) = chars.next_if(|(_, ch)| ch.is_ascii_digit())
+                {
+                    digits.push(digit);
+                }
+
+                digits.parse::<u8>().ok().map(Escaped::Byte)
+            }
+
+            'u' => match chars.next_if(|(_, ch)| *ch == '{') {
+                Some(_) => {
+                    let mut digits = String::new();
+                    while let Some((_, digit)) =
+                        chars.next_if(|(_, ch)| ch.is_ascii_hexdigit())
+                    {
+                        digits.push(digit);
+                    }
+
+                    chars
+                        .next_if(|(_, ch)| *ch == '}')
+                        .and_then(|_| u32::from_str_radix(&digits, 16).ok())
+                        .and_then(char::from_u32)
+                        .map(Escaped::Char)
+                }
+                None => None,
+            },
+
+            _ => None,
+        };
+
+        match resolved {
+            Some(Escaped::Byte(byte)) => result.push(byte),
+            Some(Escaped::Char(ch)) => push_char(&mut result, ch),
+            None => {
+                let end = chars.peek().map(|(idx, _)| *idx).unwrap_or(raw.len());
+                on_invalid((start, end));
+                result.extend_from_slice(&raw.as_bytes()[start..end]);
+            }
+        }
+    }
+
+    Cow::Owned(result)
+}
+
+pub fn unescape(raw: &str) -> Cow<'_, [u8]> {
+    unescape_with(raw, |_| ())
+}
+
+enum Escaped {
+    Byte(u8),
+    Char(char),
+}
+
+fn push_char(bytes: &mut Vec<u8>, ch: char) {
+    bytes.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid_escapes(raw: &str) -> Vec<&str> {
+        let mut spans = vec![];
+        unescape_with(raw, |span| spans.push(span));
+        spans.into_iter().map(|(start, end)| &raw[start..end]).collect()
+    }
+
+    #[test]
+    fn resolves_simple_escapes() {
+        assert_eq!(&*unescape(r#"say \"hi\"\n\tbye\\"#), b"say \"hi\"\n\tbye\\");
+    }
+
+    #[test]
+    fn resolves_numeric_escapes() {
+        assert_eq!(&*unescape(r"\x41\66\u{1F600}"), "AB\u{1F600}".as_bytes());
+    }
+
+    #[test]
+    fn byte_escapes_cover_the_full_byte_range() {
+        assert_eq!(&*unescape(r"\xFF\255\128\xc3\xa9"), b"\xFF\xFF\x80\xC3\xA9");
+    }
+
+    #[test]
+    fn z_skips_following_whitespace() {
+        assert_eq!(&*unescape("a\\z  \n   b"), b"ab");
+    }
+
+    #[test]
+    fn reports_invalid_escapes() {
+        assert_eq!(invalid_escapes(r"\q \x4 \u{110000} \u12 \300"), vec![
+            r"\q",
+            r"\x4",
+            r"\u{110000}",
+            r"\u",
+            r"\300",
+        ]);
+        assert_eq!(&*unescape(r"\q"), br"\q");
+    }
+}
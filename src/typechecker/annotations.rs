@@ -4,10 +4,11 @@ use rbx_types::Variant;
 use crate::{
     datatype::Datatype,
     lexer::{SpannedToken, Token},
+    macro_registry::rest_arg_splice,
     parser::{AstErrors, Construct, Node},
 };
 
-use crate::typechecker::{ReportTypeError, ResolvedTypeKey, Typechecker, TypecheckerLookup, type_error::*};
+use crate::typechecker::{ReportTypeError, ResolvedTypeKey, Typechecker, type_error::*};
 use crate::datatype::StaticLookup;
 
 #[derive(Clone, Copy)]
@@ -181,6 +182,38 @@ static ANNOTATION_SPECS: phf::Map<&'static str, AnnotationSpec> = phf_map! {
             },
         ],
     },
+    "hsl" => AnnotationSpec {
+        signatures: &[
+            AnnotationSignature {
+                head: &[&[Arg::Number], SCALE_OR_NUMBER, SCALE_OR_NUMBER],
+                tail: None,
+            },
+        ],
+    },
+    "hsla" => AnnotationSpec {
+        signatures: &[
+            AnnotationSignature {
+                head: &[&[Arg::Number], SCALE_OR_NUMBER, SCALE_OR_NUMBER, SCALE_OR_NUMBER],
+                tail: None,
+            },
+        ],
+    },
+    "hsv" => AnnotationSpec {
+        signatures: &[
+            AnnotationSignature {
+                head: &[&[Arg::Number], SCALE_OR_NUMBER, SCALE_OR_NUMBER],
+                tail: None,
+            },
+        ],
+    },
+    "hsva" => AnnotationSpec {
+        signatures: &[
+            AnnotationSignature {
+                head: &[&[Arg::Number], SCALE_OR_NUMBER, SCALE_OR_NUMBER, SCALE_OR_NUMBER],
+                tail: None,
+            },
+        ],
+    },
     "brickcolor" => AnnotationSpec {
         signatures: &[
             AnnotationSignature { head: &[&[Arg::String]], tail: None },
@@ -265,6 +298,83 @@ static ANNOTATION_SPECS: phf::Map<&'static str, AnnotationSpec> = phf_map! {
             AnnotationSignature { head: &[&[Arg::Any]], tail: None },
         ],
     },
+    "min" => AnnotationSpec {
+        signatures: &[
+            AnnotationSignature { head: &[&[Arg::Any]], tail: Some(&[Arg::Any]) },
+        ],
+    },
+    "max" => AnnotationSpec {
+        signatures: &[
+            AnnotationSignature { head: &[&[Arg::Any]], tail: Some(&[Arg::Any]) },
+        ],
+    },
+    "clamp" => AnnotationSpec {
+        signatures: &[
+            AnnotationSignature {
+                head: &[&[Arg::Any], &[Arg::Any], &[Arg::Any]],
+                tail: None,
+            },
+        ],
+    },
+    "sqrt" => AnnotationSpec {
+        signatures: &[
+            AnnotationSignature { head: &[&[Arg::Any]], tail: None },
+        ],
+    },
+    "sign" => AnnotationSpec {
+        signatures: &[
+            AnnotationSignature { head: &[&[Arg::Any]], tail: None },
+        ],
+    },
+    "mod" => AnnotationSpec {
+        signatures: &[
+            AnnotationSignature { head: &[&[Arg::Any], &[Arg::Any]], tail: None },
+        ],
+    },
+    "lighten" => AnnotationSpec {
+        signatures: &[
+            AnnotationSignature { head: &[&[Arg::Color], SCALE_OR_NUMBER], tail: None },
+        ],
+    },
+    "darken" => AnnotationSpec {
+        signatures: &[
+            AnnotationSignature { head: &[&[Arg::Color], SCALE_OR_NUMBER], tail: None },
+        ],
+    },
+    "saturate" => AnnotationSpec {
+        signatures: &[
+            AnnotationSignature { head: &[&[Arg::Color], SCALE_OR_NUMBER], tail: None },
+        ],
+    },
+    "desaturate" => AnnotationSpec {
+        signatures: &[
+            AnnotationSignature { head: &[&[Arg::Color], SCALE_OR_NUMBER], tail: None },
+        ],
+    },
+    "hue_rotate" => AnnotationSpec {
+        signatures: &[
+            AnnotationSignature { head: &[&[Arg::Color], &[Arg::Number]], tail: None },
+        ],
+    },
+    "mix" => AnnotationSpec {
+        signatures: &[
+            AnnotationSignature { head: &[&[Arg::Color], &[Arg::Color]], tail: None },
+            AnnotationSignature {
+                head: &[&[Arg::Color], &[Arg::Color], SCALE_OR_NUMBER],
+                tail: None,
+            },
+        ],
+    },
+    "complement" => AnnotationSpec {
+        signatures: &[
+            AnnotationSignature { head: &[&[Arg::Color]], tail: None },
+        ],
+    },
+    "alpha" => AnnotationSpec {
+        signatures: &[
+            AnnotationSignature { head: &[&[Arg::Color], SCALE_OR_NUMBER], tail: None },
+        ],
+    },
 };
 
 fn is_comma(construct: &Construct) -> bool {
@@ -347,7 +457,7 @@ fn datatype_matches_arg_type(dt: &Datatype, arg_type: &AnnotationArgType) -> boo
     match arg_type {
         Arg::Any => !matches!(dt, Datatype::None),
 
-        Arg::Number => matches!(dt, Datatype::Variant(Variant::Float64(_))),
+        Arg::Number => matches!(dt, Datatype::Variant(Variant::Float64(_)) | Datatype::Measure(..)),
 
         Arg::Scale => matches!(
             dt,
@@ -356,7 +466,9 @@ fn datatype_matches_arg_type(dt: &Datatype, arg_type: &AnnotationArgType) -> boo
 
         Arg::Measurement => matches!(
             dt,
-            Datatype::Variant(Variant::Float64(_)) | Datatype::Variant(Variant::UDim(_))
+            Datatype::Variant(Variant::Float64(_))
+                | Datatype::Variant(Variant::UDim(_))
+                | Datatype::Measure(..)
         ),
 
         Arg::String => matches!(dt, Datatype::Variant(Variant::String(_))),
@@ -367,6 +479,7 @@ fn datatype_matches_arg_type(dt: &Datatype, arg_type: &AnnotationArgType) -> boo
                 | Datatype::Variant(Variant::BrickColor(_))
                 | Datatype::Oklab(_)
                 | Datatype::Oklch(_)
+                | Datatype::Translucent(..)
         ),
 
         Arg::Asset => matches!(
@@ -496,26 +609,36 @@ impl<'a> Typechecker<'a> {
         } = construct
         {
             let key = ResolvedTypeKey::Token { name: name.to_string(), is_static: true };
-            let declared = self.declared_tokens.iter().rev().any(|frame| frame.contains(&key));
-            if !declared {
+            if self.token_declaration(&key).is_none() {
                 return true;
             }
-            let lookup = TypecheckerLookup { scopes: &self.static_scopes };
+            let lookup = self.lookup();
             let resolved = lookup.resolve_static(name);
             return datatype_matches_arg_type(&resolved, arg_type);
         }
 
         // Math/unary expressions are accepted for numeric arg types since their
         // result type can't be statically determined without full type inference.
-        let is_arithmetic =
-            matches!(construct, Construct::MathOperation { .. } | Construct::UnaryMinus { .. });
+        let is_concatenation = matches!(
+            construct,
+            Construct::MathOperation { operators, .. }
+                if operators.first().is_some_and(|op| matches!(op.token.value(), Token::OpConcat))
+        );
+        let is_arithmetic = !is_concatenation
+            && matches!(construct, Construct::MathOperation { .. } | Construct::UnaryMinus { .. });
 
         match arg_type {
             Arg::Any => true,
 
             Arg::Number => {
-                token_matches(construct, |token| matches!(token, Token::Number(_)))
-                    || is_arithmetic
+                let matches_token = token_matches(construct, |token| {
+                    matches!(
+                        token,
+                        Token::Number(_) | Token::NumberAngle(_) | Token::NumberDuration(_)
+                    )
+                });
+
+                matches_token || is_arithmetic
             }
 
             Arg::Scale => {
@@ -526,16 +649,26 @@ impl<'a> Typechecker<'a> {
                 let matches_token = token_matches(construct, |token| {
                     matches!(
                         token,
-                        Token::Number(_) | Token::NumberScale(_) | Token::NumberOffset(_)
+                        Token::Number(_)
+                            | Token::NumberScale(_)
+                            | Token::NumberOffset(_)
+                            | Token::NumberRem(_)
                     )
                 });
 
                 matches_token || is_arithmetic
             }
 
-            Arg::String => token_matches(construct, |token| {
-                matches!(token, Token::StringSingle(_) | Token::StringMulti(_))
-            }),
+            Arg::String => {
+                let matches_token = token_matches(construct, |token| {
+                    matches!(
+                        token,
+                        Token::StringSingle(_) | Token::StringMulti(_) | Token::StringInterpolated(_)
+                    )
+                });
+
+                matches_token || is_concatenation
+            }
 
             Arg::Color => {
                 let matches_token = token_matches(construct, |token| {
@@ -546,29 +679,37 @@ impl<'a> Typechecker<'a> {
                             | Token::ColorSkin(_)
                             | Token::ColorBrick(_)
                             | Token::ColorCss(_)
+                            | Token::ColorPalette(_)
                     )
                 });
 
                 let matches_annotation = annotation_name(construct).is_some_and(|name| {
                     matches!(
                         name.to_ascii_lowercase().as_str(),
-                        "color3" | "rgb" | "oklab" | "oklch" | "brickcolor"
+                        "color3" | "rgb" | "oklab" | "oklch" | "hsl" | "hsla" | "hsv" | "hsva"
+                            | "brickcolor" | "lighten" | "darken" | "saturate" | "desaturate" | "hue_rotate"
+                            | "mix" | "complement" | "alpha"
                     )
                 });
 
                 matches_token || matches_annotation
             }
 
-            Arg::Asset => token_matches(construct, |token| {
-                matches!(
-                    token,
-                    Token::RbxAsset(_)
-                        | Token::RbxContent(_)
-                        | Token::Number(_)
-                        | Token::StringSingle(_)
-                        | Token::StringMulti(_)
-                )
-            }),
+            Arg::Asset => {
+                let matches_token = token_matches(construct, |token| {
+                    matches!(
+                        token,
+                        Token::RbxAsset(_)
+                            | Token::RbxContent(_)
+                            | Token::Number(_)
+                            | Token::StringSingle(_)
+                            | Token::StringMulti(_)
+                            | Token::StringInterpolated(_)
+                    )
+                });
+
+                matches_token || is_concatenation
+            }
 
             Arg::Vector2 => annotation_name(construct).is_some_and(|name| {
                 matches!(name.to_ascii_lowercase().as_str(), "vec2" | "vec2i16")
@@ -651,6 +792,13 @@ impl<'a> Typechecker<'a> {
                     return;
                 };
 
+                // How many arguments a `&...name` splice stands for is only
+                // known at each call of the enclosing macro, which checks it
+                // through `validate_spliced_annotation`.
+                if args.iter().any(|arg| rest_arg_splice(arg).is_some()) {
+                    return;
+                }
+
                 self.check_annotation_args(construct, name, spec, &args, ast_errors);
             }
 
@@ -678,6 +826,51 @@ impl<'a> Typechecker<'a> {
         }
     }
 
+    /// Checks the number of arguments an annotation is passed once a call
+    /// has shown how many values the rest argument it splices collects.
+    /// A wrong count is reported at `call_span`, the call which passed them.
+    pub(super) fn validate_spliced_annotation(
+        &self,
+        construct: &Construct<'a>,
+        (rest_name, rest_len): (&str, usize),
+        call_span: (usize, usize),
+        ast_errors: &mut AstErrors,
+    ) {
+        let Construct::AnnotatedTable { annotation, body } = construct else {
+            return;
+        };
+        let Token::Identifier(name) = annotation.token.value() else {
+            return;
+        };
+        let Some(spec) = ANNOTATION_SPECS.get(name.to_ascii_lowercase().as_str()) else {
+            return;
+        };
+
+        let args: Vec<&Construct<'a>> = body
+            .iter()
+            .flat_map(|body| body.content.iter().flatten())
+            .filter(|item| !is_comma(item))
+            .collect();
+        let splices = args.iter().filter(|arg| rest_arg_splice(arg) == Some(rest_name)).count();
+        if splices == 0 {
+            return;
+        }
+
+        let count = args.len() - splices + splices * rest_len;
+        if spec.signatures.iter().any(|signature| signature_accepts_count(signature, count)) {
+            return;
+        }
+
+        ast_errors.report(
+            TypeError::WrongAnnotationArgCount {
+                name,
+                expected: spec.signatures.iter().map(|signature| signature.head.len()).collect(),
+                got: count,
+            },
+            self.parsed.range_from_span(call_span),
+        );
+    }
+
     fn check_annotation_args(
         &self,
         annotation_construct: &Construct<'a>,
@@ -0,0 +1,89 @@
+use crate::{
+    lexer::Token,
+    parser::{AstErrors, Construct, Delimited},
+};
+
+use crate::typechecker::{ReportTypeError, Typechecker, type_error::*};
+
+impl<'a> Typechecker<'a> {
+    /// Checks an `@if` block's condition and what its branches may contain.
+    /// The rest of each branch is checked by the caller as part of the
+    /// enclosing scope, see [`flatten_conditionals`].
+    pub(super) fn typecheck_if(
+        &self,
+        condition: &Option<Box<Construct<'a>>>,
+        branches: [&Option<Delimited<'a>>; 2],
+        ast_errors: &mut AstErrors,
+    ) {
+        if let Some(condition) = condition {
+            self.typecheck_condition(condition, ast_errors);
+        }
+
+        for content in branches.into_iter().flatten().filter_map(|b| b.content.as_ref()) {
+            self.check_duplicate_assignments(content, ast_errors);
+
+            for construct in content {
+                if matches!(construct, Construct::Macro { .. } | Construct::Derive { .. }) {
+                    ast_errors.report(
+                        TypeError::NotAllowedInContext {
+                            name: construct.name_plural(),
+                            context: "conditional blocks",
+                        },
+                        self.parsed.range_from_span(construct.span()),
+                    );
+                }
+            }
+        }
+    }
+
+    fn typecheck_condition(&self, condition: &Construct<'a>, ast_errors: &mut AstErrors) {
+        self.validate_token_refs(condition, ast_errors);
+        self.validate_annotation(condition, ast_errors);
+
+        let operands: Vec<&Construct<'a>> = match condition {
+            Construct::MathOperation { left, right, .. } => {
+                std::iter::once(&**left).chain(right.as_deref()).collect()
+            }
+            _ => vec![condition],
+        };
+
+        // Branches are picked when the stylesheet is compiled, long before a
+        // dynamic token has a value.
+        for operand in operands {
+            let Construct::Node { node } = operand else { continue };
+            if matches!(node.token.value(), Token::TokenIdentifier(_)) {
+                ast_errors.report(
+                    TypeError::NotAllowedInContext {
+                        name: "Tokens",
+                        context: "conditions",
+                    },
+                    self.parsed.range_from_span(node.token.span()),
+                );
+            }
+        }
+    }
+}
+
+/// `content` with the constructs of every `@if` and `@else` branch spliced in
+/// after the block itself, so that both branches are checked as part of the
+/// enclosing scope. Macros and derives are left out, as they aren't allowed
+/// in a branch.
+pub(super) fn flatten_conditionals<'c, 'a>(content: &'c [Construct<'a>]) -> Vec<&'c Construct<'a>> {
+    let mut flattened = vec![];
+
+    for construct in content {
+        flattened.push(construct);
+
+        let Construct::If { body, else_body, .. } = construct else { continue };
+
+        for branch in [body, else_body].into_iter().flatten() {
+            let Some(branch_content) = &branch.content else { continue };
+
+            flattened.extend(flatten_conditionals(branch_content).into_iter().filter(|construct| {
+                !matches!(construct, Construct::Macro { .. } | Construct::Derive { .. })
+            }));
+        }
+    }
+
+    flattened
+}
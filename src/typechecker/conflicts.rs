@@ -0,0 +1,256 @@
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+use rbx_types::Variant;
+
+use crate::{
+    datatype::{
+        Datatype, StaticLookup, evaluate_construct, paired_transparency_property,
+        palette::Palettes,
+    },
+    lexer::Token,
+    parser::{AstErrors, Construct, Delimited, Node, SelectorNode},
+    selector::{ScopedSelector, build_compound_selector_string, build_selector_string},
+    specificity::{ConflictKind, RuleSummary, SelectorTarget, analyze_conflicts, parse_selector},
+};
+
+use crate::typechecker::{ReportTypeError, Typechecker, TypecheckerLookup, type_error::*};
+
+/// A rule as it was checked, kept so that [`Typechecker::check_conflicts`] can
+/// compare it against the others once the whole file has been walked. Rules in
+/// a loop are noted once for every value.
+pub(super) struct CheckedRule {
+    /// The selector as the compiler would build it, or `None` when it calls a
+    /// selector macro, which is only expanded once compiled.
+    selector: Option<String>,
+    /// The rule whose selector this one is nested in, which for rules joined
+    /// on with `&` is their parent's parent, as in the compiler.
+    parent: Option<usize>,
+    body_start: usize,
+    priority: i32,
+    /// The static value each property is set to, and where.
+    properties: BTreeMap<String, (Variant, (usize, usize))>,
+}
+
+/// Resolves dynamic tokens to their names, the same as the compiler, so that
+/// two rules setting a property to one token agree.
+struct ConflictLookup<'l>(TypecheckerLookup<'l>);
+
+impl StaticLookup for ConflictLookup<'_> {
+    fn resolve_static(&self, name: &str) -> Datatype {
+        self.0.resolve_static(name)
+    }
+
+    fn resolve_dynamic(&self, name: &str) -> Datatype {
+        Datatype::Variant(Variant::String(format!("${}", name)))
+    }
+
+    fn palettes(&self) -> &Palettes {
+        self.0.palettes()
+    }
+}
+
+impl<'a> Typechecker<'a> {
+    /// Notes a rule whose body is about to be checked, and makes it the one
+    /// properties are recorded against. Returns the rule it's nested in, which
+    /// becomes current again once the body has been checked.
+    pub(super) fn enter_checked_rule(
+        &mut self,
+        selectors: &Option<Vec<SelectorNode<'a>>>,
+        body_start: usize,
+    ) -> Option<usize> {
+        let enclosing = self.current_rule;
+        let mut parent = enclosing;
+
+        let selector = match selectors {
+            Some(selectors) if calls_selector_macro(selectors) => None,
+
+            Some(selectors) => {
+                let lookup = self.lookup();
+                let scoped: Vec<ScopedSelector> = selectors
+                    .iter()
+                    .map(|selector| (selector, &lookup as &dyn StaticLookup))
+                    .collect();
+
+                let compound = enclosing.and_then(|idx| {
+                    let rule = &self.checked_rules[idx];
+                    let selector = rule.selector.as_ref()?;
+                    build_compound_selector_string(&scoped, selector)
+                        .map(|compound| (rule.parent, compound))
+                });
+
+                Some(match compound {
+                    Some((grandparent, compound)) => {
+                        parent = grandparent;
+                        compound
+                    }
+                    None => build_selector_string(&scoped),
+                })
+            }
+
+            None => Some(String::new()),
+        };
+
+        self.current_rule = Some(self.checked_rules.len());
+        self.checked_rules.push(CheckedRule {
+            selector,
+            parent,
+            body_start,
+            priority: 0,
+            properties: BTreeMap::new(),
+        });
+
+        enclosing
+    }
+
+    /// Records the `@priority` of the current rule.
+    pub(super) fn record_checked_priority(&mut self, body: &Construct<'a>) {
+        let Some(idx) = self.current_rule else { return };
+
+        if let Some(Datatype::Variant(Variant::Float64(value))) =
+            evaluate_construct(body, None, &self.lookup())
+        {
+            self.checked_rules[idx].priority = value as i32;
+        }
+    }
+
+    /// Records the value the current rule sets a property to, if it's known
+    /// before the stylesheet runs.
+    pub(super) fn record_checked_property(&mut self, left: &Node<'a>, right: &Construct<'a>) {
+        let Some(idx) = self.current_rule else { return };
+        let Token::Identifier(name) = left.token.value() else { return };
+
+        let datatype = evaluate_construct(right, Some(name), &ConflictLookup(self.lookup()));
+        let span = left.token.span();
+        let properties = &mut self.checked_rules[idx].properties;
+
+        if let Some(Datatype::Translucent(_, transparency)) = &datatype
+            && let Some(transparency_name) = paired_transparency_property(name)
+        {
+            properties.insert(transparency_name, (Variant::Float64(*transparency), span));
+        }
+
+        match datatype {
+            Some(Datatype::StringTemplate(_)) => {
+                properties.remove(*name);
+            }
+
+            datatype => {
+                if let Some(variant) = datatype.and_then(|d| d.coerce_to_variant(Some(name))) {
+                    properties.insert(name.to_string(), (variant, span));
+                }
+            }
+        }
+    }
+
+    /// Warns wherever a property is set by two rules which can match the same
+    /// instance, and the value that applies is decided only by declaration
+    /// order or comes from the earlier rule. Rules a macro expands into aren't
+    /// covered, as they only exist once compiled.
+    pub(super) fn check_conflicts(&self, ast_errors: &mut AstErrors) {
+        let mut blocks = vec![];
+        conditional_blocks(&self.parsed.ast, &mut blocks);
+
+        let rules: Vec<RuleSummary> = (0..self.checked_rules.len())
+            .filter_map(|idx| {
+                let rule = &self.checked_rules[idx];
+                let branches = blocks
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(block, branches)| {
+                        branches.iter().enumerate().filter_map(move |(branch, range)| {
+                            range
+                                .as_ref()
+                                .filter(|range| range.contains(&rule.body_start))
+                                .map(|_| (block, branch == 1))
+                        })
+                    })
+                    .collect();
+
+                Some(RuleSummary {
+                    idx,
+                    priority: rule.priority,
+                    targets: self.checked_rule_targets(idx)?,
+                    properties: rule
+                        .properties
+                        .iter()
+                        .map(|(name, (value, _))| (name.as_str(), value))
+                        .collect(),
+                    branches,
+                })
+            })
+            .collect();
+
+        for conflict in analyze_conflicts(&rules) {
+            let span = |idx: usize| {
+                self.checked_rules[idx].properties.get(&conflict.property).map(|(_, span)| *span)
+            };
+            let (Some(winner), Some(loser)) = (span(conflict.winner), span(conflict.loser)) else {
+                continue;
+            };
+
+            ast_errors.report(
+                TypeError::PropertyConflict {
+                    name: conflict.property.clone(),
+                    ambiguous: conflict.kind == ConflictKind::Ambiguous,
+                    winner: self.parsed.range_from_span(winner),
+                },
+                self.parsed.range_from_span(loser),
+            );
+        }
+    }
+
+    /// The selector alternatives a checked rule matches, including its
+    /// parents', or `None` if any of them calls a selector macro.
+    fn checked_rule_targets(&self, idx: usize) -> Option<Vec<SelectorTarget>> {
+        let rule = &self.checked_rules[idx];
+        let own = parse_selector(rule.selector.as_deref()?);
+
+        let Some(parent) = rule.parent else {
+            return Some(own);
+        };
+
+        let parents = self.checked_rule_targets(parent)?;
+        Some(
+            parents
+                .iter()
+                .flat_map(|parent| own.iter().map(move |target| target.nested_in(parent)))
+                .collect(),
+        )
+    }
+}
+
+fn calls_selector_macro(selectors: &[SelectorNode]) -> bool {
+    selectors.iter().any(|selector| match selector {
+        SelectorNode::MacroCall { .. } => true,
+        SelectorNode::Functional { selectors, .. } => calls_selector_macro(selectors),
+        SelectorNode::Token(_) => false,
+    })
+}
+
+/// The source ranges of the two branches of every `@if` block in `content`.
+fn conditional_blocks(
+    content: &[Construct],
+    blocks: &mut Vec<[Option<RangeInclusive<usize>>; 2]>,
+) {
+    let visit = |body: &Option<Delimited>, blocks: &mut Vec<_>| {
+        if let Some(content) = body.as_ref().and_then(|body| body.content.as_ref()) {
+            conditional_blocks(content, blocks);
+        }
+    };
+
+    for construct in content {
+        match construct {
+            Construct::Rule { body, .. } | Construct::Each { body, .. } => visit(body, blocks),
+
+            Construct::If { body, else_body, .. } => {
+                let range = |body: &Option<Delimited>| body.as_ref().map(|body| body.start()..=body.end());
+                blocks.push([range(body), range(else_body)]);
+                visit(body, blocks);
+                visit(else_body, blocks);
+            }
+
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use rbx_types::{Color3, Variant};
+
+use crate::{
+    datatype::{Datatype, paired_transparency_property},
+    lexer::Token,
+    parser::{AstErrors, Construct, Delimited},
+    range_from_span::RangeFromSpan,
+    types::Range,
+};
+
+use crate::typechecker::{ReportTypeError, ResolvedTypeKey, ResolvedTypes, Typechecker, type_error::*};
+
+/// WCAG 2 AA minimum for body text, used when a luaurc sets
+/// `contrastThreshold` to `true`.
+pub(crate) const DEFAULT_CONTRAST_THRESHOLD: f64 = 4.5;
+
+/// A colour property assigned in a rule, along with where it was assigned.
+#[derive(Clone, Copy)]
+struct ColorAssignment {
+    color: Color3,
+    /// How see-through the colour is, from its paired transparency property.
+    /// `None` when that property is set to something that isn't known until
+    /// runtime.
+    transparency: Option<f64>,
+    span: (usize, usize),
+}
+
+impl ColorAssignment {
+    /// The colour that actually shows once this one is blended over the one
+    /// behind it. `None` when that can't be known, so the check is skipped
+    /// rather than run against the wrong colour.
+    fn over(self, behind: Option<ColorAssignment>) -> Option<ColorAssignment> {
+        match self.transparency? {
+            transparency if transparency <= 0.0 => Some(self),
+            transparency if transparency >= 1.0 => behind,
+            transparency => {
+                let behind = behind?;
+                let blend = |front: f32, back: f32| {
+                    front + (back - front) * transparency as f32
+                };
+
+                Some(ColorAssignment {
+                    color: Color3::new(
+                        blend(self.color.r, behind.color.r),
+                        blend(self.color.g, behind.color.g),
+                        blend(self.color.b, behind.color.b),
+                    ),
+                    transparency: Some(0.0),
+                    span: self.span,
+                })
+            }
+        }
+    }
+}
+
+fn relative_luminance(color: Color3) -> f64 {
+    let linearize = |channel: f32| {
+        let channel = channel.clamp(0.0, 1.0) as f64;
+        if channel <= 0.04045 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    };
+
+    0.2126 * linearize(color.r) + 0.7152 * linearize(color.g) + 0.0722 * linearize(color.b)
+}
+
+fn contrast_ratio(a: Color3, b: Color3) -> f64 {
+    let (a, b) = (relative_luminance(a), relative_luminance(b));
+    let (lighter, darker) = if a > b { (a, b) } else { (b, a) };
+
+    (lighter + 0.05) / (darker + 0.05)
+}
+
+impl<'a> Typechecker<'a> {
+    /// Warns for every rule whose `TextColor3` doesn't stand out enough from
+    /// the `BackgroundColor3` set by it or by the closest enclosing rule. Runs
+    /// once the whole file has been typechecked so that every colour is
+    /// already resolved, and only when a luaurc opts in with a threshold.
+    pub(super) fn check_contrast(
+        &self,
+        content: &[Construct<'a>],
+        resolved_types: &ResolvedTypes,
+        ast_errors: &mut AstErrors,
+    ) {
+        if let Some(threshold) = self.contrast_threshold {
+            self.check_contrast_in(content, None, threshold, resolved_types, ast_errors);
+        }
+    }
+
+    fn check_contrast_in(
+        &self,
+        content: &[Construct<'a>],
+        background: Option<ColorAssignment>,
+        threshold: f64,
+        resolved_types: &ResolvedTypes,
+        ast_errors: &mut AstErrors,
+    ) {
+        for construct in content {
+            if let Construct::If { body, else_body, .. } = construct {
+                for branch in [body, else_body] {
+                    if let Some(content) = branch.as_ref().and_then(|b| b.content.as_ref()) {
+                        self.check_contrast_in(content, background, threshold, resolved_types, ast_errors);
+                    }
+                }
+                continue;
+            }
+
+            if let Construct::Each { body: Some(Delimited { content: Some(content), .. }), .. } =
+                construct
+            {
+                self.check_contrast_in(content, background, threshold, resolved_types, ast_errors);
+                continue;
+            }
+
+            let Construct::Rule { body: Some(body), .. } = construct else { continue };
+            let Some(content) = &body.content else { continue };
+
+            let background = match assigned_color(content, "BackgroundColor3", resolved_types) {
+                Some(own) => own.over(background),
+                None => background,
+            };
+
+            if let Some(text) = assigned_color(content, "TextColor3", resolved_types)
+                && let Some(background) = background
+                && let Some(text) = text.over(Some(background))
+            {
+                let ratio = contrast_ratio(text.color, background.color);
+
+                if ratio < threshold {
+                    ast_errors.report(
+                        TypeError::LowContrast {
+                            ratio,
+                            threshold,
+                            background: Range::from_span(&self.parsed.rope, background.span),
+                        },
+                        Range::from_span(&self.parsed.rope, text.span),
+                    );
+                }
+            }
+
+            self.check_contrast_in(content, background, threshold, resolved_types, ast_errors);
+        }
+    }
+}
+
+/// The last assignment to `property` directly in a rule body, along with its
+/// resolved value if it has one.
+fn last_assignment<'b>(
+    content: &[Construct],
+    property: &str,
+    resolved_types: &'b ResolvedTypes,
+) -> Option<(Option<&'b Datatype>, (usize, usize))> {
+    content.iter().rev().find_map(|construct| {
+        let Construct::Assignment { left, right: Some(right), .. } = construct else {
+            return None;
+        };
+
+        if !matches!(left.token.value(), Token::Identifier(name) if *name == property) {
+            return None;
+        }
+
+        let key = ResolvedTypeKey::Property { start: left.token.span().0 };
+        Some((resolved_types.get(&key), right.span()))
+    })
+}
+
+/// The last colour `property` is set to directly in a rule body, with the
+/// transparency it's paired with.
+fn assigned_color(
+    content: &[Construct],
+    property: &str,
+    resolved_types: &ResolvedTypes,
+) -> Option<ColorAssignment> {
+    let (datatype, span) = last_assignment(content, property, resolved_types)?;
+
+    let (datatype, transparency) = match datatype? {
+        Datatype::Translucent(color, transparency) => (&**color, *transparency),
+        datatype => (datatype, 0.0),
+    };
+
+    let color = match datatype {
+        Datatype::Variant(Variant::Color3(color)) => *color,
+        Datatype::Variant(Variant::Color3uint8(color)) => (*color).into(),
+        _ => return None,
+    };
+
+    let transparency = match paired_transparency_property(property)
+        .and_then(|paired| last_assignment(content, &paired, resolved_types))
+    {
+        Some((Some(Datatype::Variant(Variant::Float32(value))), _)) => Some(*value as f64),
+        Some((Some(Datatype::Variant(Variant::Float64(value))), _)) => Some(*value),
+        Some(_) => None,
+        None => Some(transparency),
+    };
+
+    Some(ColorAssignment { color, transparency, span })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn black_on_white_is_maximum_contrast() {
+        let ratio = contrast_ratio(Color3::new(0.0, 0.0, 0.0), Color3::new(1.0, 1.0, 1.0));
+        assert!((ratio - 21.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn contrast_is_symmetric() {
+        let a = Color3::new(0.2, 0.4, 0.6);
+        let b = Color3::new(0.9, 0.8, 0.1);
+        assert_eq!(contrast_ratio(a, b), contrast_ratio(b, a));
+    }
+}
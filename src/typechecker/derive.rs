@@ -6,16 +6,109 @@ use std::{
 };
 
 use crate::{
+    datatype::{Datatype, evaluate_construct},
     lexer::{MultilineString, SpannedToken, Token},
-    parser::{AstErrors, Construct, Delimited, Node},
+    macro_registry::collect_macros,
+    parser::{AstErrors, Construct, Delimited, Node, ParsedRsml},
 };
 
 use crate::typechecker::luaurc::Luaurc;
 use crate::typechecker::normalize_path::NormalizePath;
 
-use crate::typechecker::{ReportTypeError, Typechecker, type_error::*};
+use crate::typechecker::{
+    ReportTypeError, ResolvedTypeKey, TokenDeclaration, Typechecker, TypecheckerLookup,
+    type_error::*,
+};
 
 impl<'a> Typechecker<'a> {
+    /// Brings the macros of each newly resolved derive into scope. A macro
+    /// whose name and arity is already visible is reported at the derive, and
+    /// the definition already in scope is kept.
+    pub(super) fn register_derived_macros(
+        &mut self,
+        derived: &HashMap<PathBuf, &'a ParsedRsml<'a>>,
+        derives: &HashMap<PathBuf, RangeInclusive<usize>>,
+        registered: &mut HashSet<PathBuf>,
+        ast_errors: &mut AstErrors,
+    ) {
+        for (path, span) in derives {
+            let Some(sheet) = derived.get(path) else { continue };
+            if !registered.insert(path.clone()) {
+                continue;
+            }
+
+            for (key, definition) in collect_macros(&sheet.ast) {
+                let builtin_collision = !self.parsed.directives.nobuiltins
+                    && crate::builtins::BUILTINS.registry.contains_key(&key);
+
+                if builtin_collision
+                    || self.macro_registry.contains_key(&key)
+                    || self.derived_macros.contains_key(&key)
+                {
+                    ast_errors.report(
+                        TypeError::DuplicateMacro {
+                            name: key.name,
+                            arg_count: key.arity,
+                        },
+                        self.parsed.range_from_span((*span.start(), *span.end())),
+                    );
+                } else {
+                    self.derived_macros.insert(key, definition);
+                }
+            }
+
+            self.register_derived_tokens(path, sheet);
+        }
+    }
+
+    /// Declares the global tokens of a derived stylesheet, so they can be
+    /// referenced and are checked against the properties they flow into. A
+    /// token this file has already declared keeps its own declaration.
+    fn register_derived_tokens(&mut self, path: &Path, sheet: &ParsedRsml<'a>) {
+        let mut static_scope = HashMap::new();
+
+        for construct in &sheet.ast {
+            let Construct::Assignment { left, right: Some(right), .. } = construct else {
+                continue;
+            };
+            let (name, is_static) = match left.token.value() {
+                Token::TokenIdentifier(name) => (*name, false),
+                Token::StaticTokenIdentifier(name) => (*name, true),
+                _ => continue,
+            };
+
+            let lookup = TypecheckerLookup {
+                scopes: std::slice::from_ref(&static_scope),
+                palettes: &self.palettes,
+            };
+            let evaluated = evaluate_construct(right, Some(name), &lookup);
+
+            let datatype = match evaluated {
+                Some(datatype) if is_static => datatype,
+                Some(datatype) => datatype
+                    .coerce_to_variant_in(Some(name), self.language_mode)
+                    .map(Datatype::Variant)
+                    .unwrap_or(Datatype::None),
+                None => Datatype::None,
+            };
+
+            if is_static {
+                static_scope.insert(name.to_string(), datatype.clone());
+                self.static_scopes[0]
+                    .entry(name.to_string())
+                    .or_insert_with(|| datatype.clone());
+            }
+
+            self.declared_tokens[0]
+                .entry(ResolvedTypeKey::Token { name: name.to_string(), is_static })
+                .or_insert_with(|| TokenDeclaration {
+                    definition: sheet.range_from_span(left.token.span()),
+                    path: Some(path.to_path_buf()),
+                    datatype,
+                });
+        }
+    }
+
     pub(super) fn typecheck_derive<'b>(
         &'b self,
         body: &'b Construct<'a>,
@@ -144,7 +237,7 @@ impl<'a> Typechecker<'a> {
             }
         };
 
-        current_path.join("../").join(path)
+        current_path.parent().unwrap_or(current_path).join(path)
     }
 
     async fn resolve_derive(
@@ -0,0 +1,134 @@
+use std::collections::HashSet;
+
+use crate::{
+    lexer::Token,
+    macro_registry::{MacroKey, MacroReturnContext, collect_macro_call_args},
+    parser::{AstErrors, Construct, MacroBodyContent, SelectorNode},
+    range_from_span::RangeFromSpan,
+    types::Range,
+};
+
+use crate::typechecker::{ReportTypeError, Typechecker, type_error::*};
+
+/// A property or token assigned somewhere in a rule body. Assignments emitted
+/// by construct macros are attributed to the macro call, and any rules the
+/// macro nests them in are recorded in `scope` so that two `Padding(...)` calls
+/// collide on their `::UIPadding` children.
+struct AssignmentSite<'a> {
+    scope: Vec<Token<'a>>,
+    name: String,
+    span: (usize, usize),
+}
+
+impl<'a> Typechecker<'a> {
+    pub(super) fn check_duplicate_assignments(
+        &self,
+        content: &[Construct<'a>],
+        ast_errors: &mut AstErrors,
+    ) {
+        let mut sites = vec![];
+        self.collect_assignment_sites(content, &[], None, &mut HashSet::new(), &mut sites);
+
+        for (idx, site) in sites.iter().enumerate() {
+            let Some(previous) = sites[..idx]
+                .iter()
+                .rev()
+                .find(|previous| previous.name == site.name && previous.scope == site.scope)
+            else {
+                continue;
+            };
+
+            // A macro which assigns the same property twice is a problem with
+            // its definition, not with the call site.
+            if previous.span == site.span {
+                continue;
+            }
+
+            ast_errors.report(
+                TypeError::DuplicateAssignment {
+                    name: site.name.clone(),
+                    previous: Range::from_span(&self.parsed.rope, previous.span),
+                },
+                Range::from_span(&self.parsed.rope, site.span),
+            );
+        }
+    }
+
+    fn collect_assignment_sites(
+        &self,
+        content: &[Construct<'a>],
+        scope: &[Token<'a>],
+        call_site: Option<(usize, usize)>,
+        active: &mut HashSet<MacroKey<'a>>,
+        sites: &mut Vec<AssignmentSite<'a>>,
+    ) {
+        for construct in content {
+            match construct {
+                Construct::Assignment { left, .. } => {
+                    let name = match left.token.value() {
+                        Token::Identifier(name) => name.to_string(),
+                        Token::TokenIdentifier(name) => format!("${}", name),
+                        Token::StaticTokenIdentifier(name) => format!("$!{}", name),
+                        _ => continue,
+                    };
+
+                    sites.push(AssignmentSite {
+                        scope: scope.to_vec(),
+                        name,
+                        span: call_site.unwrap_or(left.token.span()),
+                    });
+                }
+
+                Construct::MacroCall { name, body, .. } => {
+                    let Token::MacroCallIdentifier(Some(macro_name)) = name.token.value() else {
+                        continue;
+                    };
+
+                    let call_args = collect_macro_call_args(body);
+                    let definition = self.find_macro_definition(macro_name, &call_args);
+
+                    let Some((key, definition)) = definition else { continue };
+                    if definition.return_context != MacroReturnContext::Construct {
+                        continue;
+                    }
+
+                    let Some(MacroBodyContent::Construct(Some(body))) = definition.body else {
+                        continue;
+                    };
+
+                    if !active.insert(key) {
+                        continue;
+                    }
+
+                    self.collect_assignment_sites(
+                        body,
+                        scope,
+                        call_site.or(Some(construct.span())),
+                        active,
+                        sites,
+                    );
+
+                    active.remove(&key);
+                }
+
+                // Rules written directly in the body are checked on their own;
+                // only rules produced by a macro are folded into this one.
+                Construct::Rule { selectors, body: Some(body) } if call_site.is_some() => {
+                    let Some(body) = &body.content else { continue };
+
+                    let mut nested_scope = scope.to_vec();
+                    nested_scope.extend(selectors.iter().flatten().filter_map(|selector| {
+                        match selector {
+                            SelectorNode::Token(node) => Some(node.token.value().clone()),
+                            SelectorNode::MacroCall { .. } | SelectorNode::Functional { .. } => None,
+                        }
+                    }));
+
+                    self.collect_assignment_sites(body, &nested_scope, call_site, active, sites);
+                }
+
+                _ => (),
+            }
+        }
+    }
+}
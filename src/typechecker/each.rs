@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use crate::{
+    datatype::{Datatype, evaluate_each_values},
+    lexer::Token,
+    parser::{AstErrors, Construct, Delimited, Node},
+};
+
+use crate::typechecker::{
+    DefinitionKind, Definitions, ReportTypeError, ResolvedTypeKey, ResolvedTypes, TokenDeclaration,
+    Typechecker, type_error::*,
+};
+
+impl<'a> Typechecker<'a> {
+    /// Checks an `@each` loop. Its body is checked once for every value the
+    /// loop iterates over, so selectors and properties built from the loop
+    /// variable are checked the way each unrolled copy will be compiled.
+    pub(super) fn typecheck_each(
+        &mut self,
+        (variable, iterable, body): (
+            &Option<Node<'a>>,
+            &Option<Box<Construct<'a>>>,
+            &Option<Delimited<'a>>,
+        ),
+        current_classes: &Vec<String>,
+        ast_errors: &mut AstErrors,
+        definitions: &mut Definitions,
+        resolved_types: &mut ResolvedTypes,
+    ) {
+        if let Some(iterable) = iterable {
+            self.typecheck_iterable(iterable, ast_errors);
+        }
+
+        let content = body.as_ref().and_then(|body| body.content.as_ref());
+        if let Some(content) = content {
+            self.check_duplicate_assignments(content, ast_errors);
+        }
+
+        let mut values = match iterable.as_deref() {
+            Some(iterable) => evaluate_each_values(iterable, &self.lookup()),
+            None => vec![],
+        };
+        if values.is_empty() {
+            values.push(Datatype::None);
+        }
+
+        for datatype in values {
+            self.static_scopes.push(HashMap::new());
+            self.declared_tokens.push(HashMap::new());
+
+            if let Some(variable) = variable
+                && let Token::StaticTokenIdentifier(name) = variable.token.value()
+            {
+                let (start, end) = variable.token.span();
+                let key = ResolvedTypeKey::Token { name: name.to_string(), is_static: true };
+
+                if let Some(frame) = self.static_scopes.last_mut() {
+                    frame.insert(name.to_string(), datatype.clone());
+                }
+                if let Some(frame) = self.declared_tokens.last_mut() {
+                    frame.insert(
+                        key.clone(),
+                        TokenDeclaration {
+                            definition: self.parsed.range_from_span((start, end)),
+                            path: None,
+                            datatype: datatype.clone(),
+                        },
+                    );
+                }
+                resolved_types.insert(key, datatype);
+                definitions.insert(
+                    start..=end,
+                    DefinitionKind::Token { name: name.to_string(), is_static: true },
+                );
+            }
+
+            // Every copy shares the body's source ranges, so a problem found
+            // in more than one of them is only reported once.
+            if let Some(content) = content {
+                let mut iteration_errors = AstErrors::new();
+                self.typecheck_rule_content(
+                    content,
+                    current_classes,
+                    &mut iteration_errors,
+                    definitions,
+                    resolved_types,
+                );
+
+                for error in iteration_errors.0 {
+                    let reported = ast_errors.0.iter().any(|existing| {
+                        existing.range == error.range && existing.message == error.message
+                    });
+                    if !reported {
+                        ast_errors.0.push(error);
+                    }
+                }
+            }
+
+            self.static_scopes.pop();
+            self.declared_tokens.pop();
+        }
+    }
+
+    fn typecheck_iterable(&self, iterable: &Construct<'a>, ast_errors: &mut AstErrors) {
+        self.validate_token_refs(iterable, ast_errors);
+        self.validate_annotation(iterable, ast_errors);
+
+        let items: Vec<&Construct<'a>> = match iterable {
+            Construct::Table { body } => body.content.iter().flatten().collect(),
+            _ => vec![iterable],
+        };
+
+        // Loops are unrolled when the stylesheet is compiled, long before a
+        // dynamic token has a value.
+        for item in items {
+            let Construct::Node { node } = item else { continue };
+            if matches!(node.token.value(), Token::TokenIdentifier(_)) {
+                ast_errors.report(
+                    TypeError::NotAllowedInContext {
+                        name: "Tokens",
+                        context: "loops",
+                    },
+                    self.parsed.range_from_span(node.token.span()),
+                );
+            }
+        }
+    }
+}
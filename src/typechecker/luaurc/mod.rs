@@ -1,9 +1,13 @@
+use ropey::Rope;
 use serde::de::Deserialize;
 use tokio::fs;
 use std::{collections::BTreeMap, ops::{Deref, DerefMut}, path::{Path, PathBuf}};
 
 use crate::typechecker::multibimap::MultiBiMap;
-use crate::types::LanguageMode;
+use crate::types::{Diagnostic, LanguageMode, Range, Severity};
+use crate::typechecker::contrast::DEFAULT_CONTRAST_THRESHOLD;
+use crate::datatype::palette::{PaletteError, Palettes};
+use crate::range_from_span::RangeFromSpan;
 
 #[derive(Debug, Default)]
 pub struct Aliases(pub BTreeMap<String, PathBuf>);
@@ -120,6 +124,36 @@ pub struct Luaurc {
     pub aliases: Aliases,
     pub dependants: Dependants,
     pub language_mode: LanguageMode,
+    /// Minimum WCAG contrast ratio between text and background colours. The
+    /// check only runs when this is set; `true` asks for the WCAG AA ratio.
+    pub contrast_threshold: Option<f64>,
+    /// Colour palettes in the same shape as `colors/tailwind.json`, written
+    /// inline or as paths relative to the luaurc. A palette with any invalid
+    /// entry is left out and reported in `diagnostics`.
+    pub palettes: Palettes,
+    /// Problems with the luaurc's own contents.
+    pub diagnostics: Vec<Diagnostic>,
+    palette_sources: Vec<PaletteSource>,
+}
+
+/// One palette under the `"palettes"` key, which holds either a palette or a
+/// list of them.
+#[derive(Debug)]
+enum PaletteSource {
+    Inline(serde_json::Value),
+    File(String),
+}
+
+impl PaletteSource {
+    fn from_value(value: serde_json::Value) -> Vec<Self> {
+        match value {
+            serde_json::Value::Array(values) => {
+                values.into_iter().flat_map(Self::from_value).collect()
+            }
+            serde_json::Value::String(path) => vec![Self::File(path)],
+            value => vec![Self::Inline(value)],
+        }
+    }
 }
 
 impl<'de> Deserialize<'de> for Luaurc {
@@ -145,6 +179,8 @@ impl<'de> Deserialize<'de> for Luaurc {
             {
                 let mut aliases = Aliases::default();
                 let mut language_mode = LanguageMode::default();
+                let mut contrast_threshold = None;
+                let mut palette_sources = vec![];
 
                 while let Some(key) = access.next_key::<String>()? {
                     match key.as_str() {
@@ -159,6 +195,17 @@ impl<'de> Deserialize<'de> for Luaurc {
                                 _ => LanguageMode::Nonstrict,
                             };
                         }
+                        "contrastThreshold" => {
+                            let value: serde_json::Value = access.next_value()?;
+                            contrast_threshold = match value {
+                                serde_json::Value::Bool(true) => Some(DEFAULT_CONTRAST_THRESHOLD),
+                                value => value.as_f64().filter(|threshold| *threshold > 0.0),
+                            };
+                        }
+                        "palettes" => {
+                            let value: serde_json::Value = access.next_value()?;
+                            palette_sources = PaletteSource::from_value(value);
+                        }
                         _ => {
                             let _: serde::de::IgnoredAny = access.next_value()?;
                         }
@@ -169,6 +216,10 @@ impl<'de> Deserialize<'de> for Luaurc {
                     aliases,
                     dependants: Dependants::new(),
                     language_mode,
+                    contrast_threshold,
+                    palettes: Palettes::new(),
+                    diagnostics: vec![],
+                    palette_sources,
                 })
             }
         }
@@ -178,16 +229,60 @@ impl<'de> Deserialize<'de> for Luaurc {
 }
 
 impl Luaurc {
+    /// Parses a luaurc whose palette paths are relative to the current
+    /// directory.
     pub fn new<S: AsRef<str>>(contents: S) -> Self {
-        serde_json::from_str::<Luaurc>(contents.as_ref())
-            .unwrap_or_else(|_| Luaurc::default())
+        Self::with_dir(contents, Path::new(""))
+    }
+
+    /// Parses a luaurc whose palette paths are relative to `dir`.
+    pub fn with_dir<S: AsRef<str>>(contents: S, dir: &Path) -> Self {
+        let contents = contents.as_ref();
+        let mut luaurc = serde_json::from_str::<Luaurc>(contents)
+            .unwrap_or_else(|_| Luaurc::default());
+
+        for source in std::mem::take(&mut luaurc.palette_sources) {
+            let result = match &source {
+                PaletteSource::Inline(value) => serde_json::from_value(value.clone())
+                    .map_err(PaletteError::Json)
+                    .and_then(|entries| luaurc.palettes.add_entries(entries)),
+                PaletteSource::File(path) => luaurc.palettes.add_file(&dir.join(path)),
+            };
+
+            if let Err(err) = result {
+                luaurc.report_palette_error(contents, &source, err);
+            }
+        }
+
+        luaurc
     }
 
     pub async fn from_path(path: &Path) -> Self {
         if let Ok(contents) = fs::read_to_string(path).await {
-            Luaurc::new(&contents)
+            Luaurc::with_dir(&contents, path.parent().unwrap_or(Path::new("")))
         } else {
             Luaurc::default()
         }
     }
+
+    /// Reports a palette which couldn't be loaded at its path, or at the
+    /// `"palettes"` key for one written inline.
+    fn report_palette_error(&mut self, contents: &str, source: &PaletteSource, err: PaletteError) {
+        let needle = match source {
+            PaletteSource::Inline(_) => "\"palettes\"".to_string(),
+            PaletteSource::File(path) => serde_json::to_string(path).unwrap_or_default(),
+        };
+        let span = contents
+            .find(&needle)
+            .map(|start| (start, start + needle.len()))
+            .unwrap_or((0, 0));
+
+        self.diagnostics.push(Diagnostic {
+            range: Range::from_span(&Rope::from_str(contents), span),
+            severity: Severity::Error,
+            code: "LUAURC_ERROR(INVALID_PALETTE)".to_string(),
+            message: format!("Luaurc Error (Invalid Palette): {}.", err),
+            data: None,
+        });
+    }
 }
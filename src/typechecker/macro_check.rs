@@ -1,8 +1,15 @@
 use std::collections::{HashMap, HashSet};
 
 use crate::{
+    builtins::BUILTINS,
+    datatype::{Reference, tag_references},
     lexer::Token,
-    macro_registry::{MacroKey, MacroReturnContext, count_macro_call_args},
+    macro_registry::{
+        MacroCallArgs, MacroDefinition, MacroKey, MacroRegistry, MacroReturnContext,
+        accepted_arg_counts, collect_macro_call_args, collect_macro_def_arg_defaults,
+        collect_macro_def_arg_names, collect_macro_def_rest_arg, find_macro, find_macro_in,
+        rest_arg_splice,
+    },
     parser::{AstErrors, Construct, Delimited, MacroBody, MacroBodyContent, Node, SelectorNode},
     range_from_span::RangeFromSpan,
 };
@@ -17,6 +24,14 @@ impl<'a> Typechecker<'a> {
         ast_errors: &mut AstErrors,
     ) {
         let macro_args = collect_macro_arg_names(args);
+        self.validate_rest_arg_position(args, ast_errors);
+
+        // Defaults are evaluated where the macro is called, so they can't see
+        // the macro's other arguments.
+        for default in collect_macro_def_arg_defaults(args).into_iter().flatten() {
+            self.validate_macro_arg_refs(default, None, ast_errors);
+        }
+
         let Some(body) = body else { return };
 
         match &body.content {
@@ -31,6 +46,8 @@ impl<'a> Typechecker<'a> {
                 }
             }
             MacroBodyContent::Selector(Some(selectors)) => {
+                self.validate_selector_macro_arg_refs(selectors, &macro_args, ast_errors);
+
                 for selector in selectors {
                     if let SelectorNode::MacroCall { name, body } = selector {
                         self.validate_macro_call(
@@ -49,7 +66,7 @@ impl<'a> Typechecker<'a> {
     fn typecheck_macro_body_content(
         &self,
         content: &Vec<Construct<'a>>,
-        macro_args: &HashSet<&str>,
+        macro_args: &MacroArgNames,
         ast_errors: &mut AstErrors,
     ) {
         for construct in content {
@@ -69,7 +86,11 @@ impl<'a> Typechecker<'a> {
                     }
                 }
 
-                Construct::Rule { body, .. } => {
+                Construct::Rule { selectors, body } => {
+                    if let Some(selectors) = selectors {
+                        self.validate_selector_macro_arg_refs(selectors, macro_args, ast_errors);
+                    }
+
                     if let Some(body) = body {
                         if let Some(content) = &body.content {
                             self.typecheck_macro_body_content(content, macro_args, ast_errors);
@@ -77,6 +98,33 @@ impl<'a> Typechecker<'a> {
                     }
                 }
 
+                Construct::If {
+                    condition,
+                    body,
+                    else_body,
+                    ..
+                } => {
+                    if let Some(condition) = condition {
+                        self.validate_macro_arg_refs(condition, Some(macro_args), ast_errors);
+                    }
+
+                    for branch in [body, else_body] {
+                        if let Some(content) = branch.as_ref().and_then(|b| b.content.as_ref()) {
+                            self.typecheck_macro_body_content(content, macro_args, ast_errors);
+                        }
+                    }
+                }
+
+                Construct::Each { iterable, body, .. } => {
+                    if let Some(iterable) = iterable {
+                        self.validate_macro_arg_list_item(iterable, Some(macro_args), ast_errors);
+                    }
+
+                    if let Some(content) = body.as_ref().and_then(|b| b.content.as_ref()) {
+                        self.typecheck_macro_body_content(content, macro_args, ast_errors);
+                    }
+                }
+
                 Construct::Tween { body, .. } => {
                     if let Some(body) = body {
                         self.validate_macro_arg_refs(body, Some(macro_args), ast_errors);
@@ -84,6 +132,7 @@ impl<'a> Typechecker<'a> {
                 }
 
                 Construct::MacroCall { name, body, .. } => {
+                    self.validate_macro_arg_refs(construct, Some(macro_args), ast_errors);
                     self.validate_macro_call(name, body, MacroReturnContext::Construct, ast_errors);
                 }
 
@@ -112,6 +161,110 @@ impl<'a> Typechecker<'a> {
         }
     }
 
+    /// Checks the macro arguments a selector refers to, whether standing in
+    /// for a class (`&class`), spliced into a tag or name (`.p-&size`), or
+    /// splicing a rest argument in as a list of selectors (`&...tags`).
+    fn validate_selector_macro_arg_refs(
+        &self,
+        selectors: &[SelectorNode<'a>],
+        macro_args: &MacroArgNames,
+        ast_errors: &mut AstErrors,
+    ) {
+        for selector in selectors {
+            match selector {
+                SelectorNode::Token(node) => match node.token.value() {
+                    Token::MacroArgIdentifier(Some(name)) => {
+                        self.validate_selector_macro_arg_ref(
+                            name,
+                            node.token.span(),
+                            macro_args,
+                            ast_errors,
+                        );
+                    }
+
+                    Token::TagSelectorOrEnumPart(Some(tag)) | Token::NameSelector(tag) => {
+                        // Skips the `.` or `#` before the tag.
+                        let tag_start = node.token.start() + 1;
+
+                        for (reference, (start, end)) in tag_references(tag) {
+                            if let Reference::MacroArg(name) = reference {
+                                self.validate_selector_macro_arg_ref(
+                                    name,
+                                    (tag_start + start, tag_start + end),
+                                    macro_args,
+                                    ast_errors,
+                                );
+                            }
+                        }
+                    }
+
+                    Token::MacroRestArgIdentifier(name) => {
+                        self.validate_rest_arg_ref(
+                            *name,
+                            node.token.span(),
+                            Some(macro_args),
+                            ast_errors,
+                        );
+                    }
+
+                    _ => (),
+                },
+
+                SelectorNode::Functional { selectors, .. } => {
+                    self.validate_selector_macro_arg_refs(selectors, macro_args, ast_errors);
+                }
+
+                SelectorNode::MacroCall { body, .. } => {
+                    for arg in body.iter().flat_map(|body| body.content.iter().flatten()) {
+                        self.validate_macro_arg_list_item(arg, Some(macro_args), ast_errors);
+                    }
+                }
+            }
+        }
+    }
+
+    fn validate_selector_macro_arg_ref(
+        &self,
+        name: &str,
+        span: (usize, usize),
+        macro_args: &MacroArgNames,
+        ast_errors: &mut AstErrors,
+    ) {
+        if !macro_args.args.contains(name) {
+            ast_errors.report(
+                TypeError::InvalidMacroArg {
+                    msg: &format!("No macro argument named \"{}\" exists.", name),
+                },
+                self.range_from_span(span),
+            );
+        }
+    }
+
+    fn validate_rest_arg_position(&self, args: &Option<Delimited<'a>>, ast_errors: &mut AstErrors) {
+        let mut args = args
+            .iter()
+            .flat_map(|args| args.content.iter().flatten())
+            .filter(|construct| {
+                !matches!(
+                    construct,
+                    Construct::Node { node } if matches!(node.token.value(), Token::Comma)
+                )
+            });
+
+        let Some(rest) = args.by_ref().find(|construct| rest_arg_splice(construct).is_some()) else {
+            return;
+        };
+
+        if args.next().is_some() {
+            ast_errors.report(
+                TypeError::InvalidMacroArg {
+                    msg: "The rest argument must be the macro's last argument.",
+                },
+                self.range_from_span(rest.span()),
+            );
+        }
+    }
+
     pub(super) fn validate_macro_call(
         &self,
         name: &Node<'a>,
@@ -123,18 +276,7 @@ impl<'a> Typechecker<'a> {
             return;
         };
 
-        let local_arities = self
-            .macro_registry
-            .keys()
-            .filter(|k| k.name == *macro_name)
-            .map(|k| k.arity);
-        let builtin_arities = crate::builtins::BUILTINS
-            .registry
-            .keys()
-            .filter(|k| k.name == *macro_name)
-            .map(|k| k.arity);
-
-        let mut expected_counts: Vec<usize> = local_arities.chain(builtin_arities).collect();
+        let expected_counts = accepted_arg_counts(&self.macro_registries(), macro_name);
 
         if expected_counts.is_empty() {
             ast_errors.report(
@@ -144,37 +286,31 @@ impl<'a> Typechecker<'a> {
             return;
         }
 
-        let call_arg_count = count_macro_call_args(body);
-        let key = MacroKey {
-            name: *macro_name,
-            arity: call_arg_count,
-        };
+        if !self.validate_named_macro_args(macro_name, body, ast_errors) {
+            return;
+        }
 
-        let matching_context = self
-            .macro_registry
-            .get(&key)
-            .map(|def| def.return_context)
-            .or_else(|| {
-                crate::builtins::BUILTINS
-                    .registry
-                    .get(&key)
-                    .map(|def| def.return_context)
-            });
+        let call_args = collect_macro_call_args(body);
 
-        let Some(matching_context) = matching_context else {
-            expected_counts.sort();
-            expected_counts.dedup();
+        // How many arguments a `&...name` splice stands for is only known at
+        // each call of the enclosing macro, which checks it through
+        // `validate_rest_splices`.
+        if call_args.positional.iter().any(|arg| rest_arg_splice(arg).is_some()) {
+            return;
+        }
 
+        let Some((key, definition)) = self.find_macro_definition(macro_name, &call_args) else {
             ast_errors.report(
                 TypeError::WrongMacroArgCount {
                     name: macro_name,
                     expected: expected_counts,
-                    got: call_arg_count,
+                    got: call_args.len(),
                 },
                 self.range_from_span(name.token.span()),
             );
             return;
         };
+        let matching_context = definition.return_context;
 
         if matching_context != expected_context {
             ast_errors.report(
@@ -186,19 +322,265 @@ impl<'a> Typechecker<'a> {
                 self.range_from_span(name.token.span()),
             );
         }
+
+        if let Some((rest_name, rest_values)) =
+            definition.bind_args(&call_args).and_then(|bound_args| bound_args.rest)
+            && let Some(body) = definition.body
+        {
+            self.validate_rest_splices(
+                body,
+                (rest_name, rest_values.len()),
+                name.token.span(),
+                &mut HashSet::from([key]),
+                ast_errors,
+            );
+        }
+    }
+
+    /// Checks the arity of the macro calls and annotations in a macro's body
+    /// which splice its rest argument, now that a call has shown how many
+    /// values the rest argument collects. Errors are reported at that call.
+    fn validate_rest_splices(
+        &self,
+        body: &'a MacroBodyContent<'a>,
+        rest: (&str, usize),
+        call_span: (usize, usize),
+        expanding: &mut HashSet<MacroKey<'a>>,
+        ast_errors: &mut AstErrors,
+    ) {
+        match body {
+            MacroBodyContent::Construct(Some(content)) => {
+                for construct in content {
+                    self.validate_rest_splices_in(construct, rest, call_span, expanding, ast_errors);
+                }
+            }
+            MacroBodyContent::Datatype(Some(content)) => {
+                self.validate_rest_splices_in(content, rest, call_span, expanding, ast_errors);
+            }
+            MacroBodyContent::Selector(Some(selectors)) => {
+                self.validate_rest_splices_in_selectors(
+                    selectors, rest, call_span, expanding, ast_errors,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn validate_rest_splices_in(
+        &self,
+        construct: &'a Construct<'a>,
+        rest: (&str, usize),
+        call_span: (usize, usize),
+        expanding: &mut HashSet<MacroKey<'a>>,
+        ast_errors: &mut AstErrors,
+    ) {
+        let inner: Vec<&'a Construct<'a>> = match construct {
+            Construct::MacroCall { name, body, .. } => {
+                self.validate_spliced_macro_call(
+                    name, body, rest, call_span, expanding, ast_errors,
+                );
+                delimited_items(body).collect()
+            }
+
+            Construct::AnnotatedTable { body, .. } => {
+                self.validate_spliced_annotation(construct, rest, call_span, ast_errors);
+                delimited_items(body).collect()
+            }
+
+            Construct::Rule { selectors, body } => {
+                if let Some(selectors) = selectors {
+                    self.validate_rest_splices_in_selectors(
+                        selectors, rest, call_span, expanding, ast_errors,
+                    );
+                }
+                delimited_items(body).collect()
+            }
+
+            Construct::If { body, else_body, .. } => {
+                delimited_items(body).chain(delimited_items(else_body)).collect()
+            }
+
+            Construct::Each { body, .. } => delimited_items(body).collect(),
+
+            Construct::Table { body } => body.content.iter().flatten().collect(),
+
+            Construct::Assignment { right: Some(inner), .. }
+            | Construct::Tween { body: Some(inner), .. }
+            | Construct::UnaryMinus { operand: inner, .. } => vec![inner],
+
+            Construct::MathOperation { left, right, .. } => {
+                std::iter::once(&**left).chain(right.as_deref()).collect()
+            }
+
+            _ => vec![],
+        };
+
+        for inner in inner {
+            self.validate_rest_splices_in(inner, rest, call_span, expanding, ast_errors);
+        }
+    }
+
+    fn validate_rest_splices_in_selectors(
+        &self,
+        selectors: &'a [SelectorNode<'a>],
+        rest: (&str, usize),
+        call_span: (usize, usize),
+        expanding: &mut HashSet<MacroKey<'a>>,
+        ast_errors: &mut AstErrors,
+    ) {
+        for selector in selectors {
+            if let SelectorNode::MacroCall { name, body } = selector {
+                self.validate_spliced_macro_call(
+                    name, body, rest, call_span, expanding, ast_errors,
+                );
+            }
+        }
+    }
+
+    /// Checks a call which splices a rest argument collecting `rest.1`
+    /// values, then the splices of its own rest argument in turn.
+    fn validate_spliced_macro_call(
+        &self,
+        name: &Node<'a>,
+        body: &'a Option<Delimited<'a>>,
+        rest: (&str, usize),
+        call_span: (usize, usize),
+        expanding: &mut HashSet<MacroKey<'a>>,
+        ast_errors: &mut AstErrors,
+    ) {
+        let Token::MacroCallIdentifier(Some(macro_name)) = name.token.value() else {
+            return;
+        };
+        let Some(call_args) = splice_rest_arity(collect_macro_call_args(body), rest) else {
+            return;
+        };
+
+        let Some((key, definition)) = self.find_macro_definition(macro_name, &call_args) else {
+            let expected_counts = accepted_arg_counts(&self.macro_registries(), macro_name);
+            if !expected_counts.is_empty() {
+                ast_errors.report(
+                    TypeError::WrongMacroArgCount {
+                        name: macro_name,
+                        expected: expected_counts,
+                        got: call_args.len(),
+                    },
+                    self.range_from_span(call_span),
+                );
+            }
+            return;
+        };
+
+        if let Some((rest_name, rest_values)) =
+            definition.bind_args(&call_args).and_then(|bound_args| bound_args.rest)
+            && let Some(body) = definition.body
+            && expanding.insert(key)
+        {
+            self.validate_rest_splices(
+                body,
+                (rest_name, rest_values.len()),
+                call_span,
+                expanding,
+                ast_errors,
+            );
+            expanding.remove(&key);
+        }
+    }
+
+    /// Every registry a macro call can resolve against, in lookup order: the
+    /// file's own macros, then those of derived stylesheets, then the builtins.
+    pub(super) fn macro_registries(&self) -> [&MacroRegistry<'a>; 3] {
+        [&self.macro_registry, &self.derived_macros, &BUILTINS.registry]
+    }
+
+    /// Resolves a macro call against the file's own macros, then derived
+    /// ones, then the builtins.
+    pub(super) fn find_macro_definition<'c>(
+        &self,
+        name: &str,
+        call_args: &MacroCallArgs<'c>,
+    ) -> Option<(MacroKey<'a>, &MacroDefinition<'a>)>
+    where
+        'a: 'c,
+    {
+        find_macro_in(&self.macro_registries(), name, call_args)
+    }
+
+    /// Reports named arguments which no definition of `macro_name` has, and
+    /// positional arguments passed after a named one. Returns whether the
+    /// call's arguments are well formed.
+    fn validate_named_macro_args(
+        &self,
+        macro_name: &str,
+        body: &Option<Delimited<'a>>,
+        ast_errors: &mut AstErrors,
+    ) -> bool {
+        let mut valid = true;
+        let mut seen_named = false;
+
+        for construct in body.iter().flat_map(|body| body.content.iter().flatten()) {
+            let Construct::Assignment { left, .. } = construct else {
+                let is_comma = matches!(
+                    construct,
+                    Construct::Node { node } if matches!(node.token.value(), Token::Comma)
+                );
+                if seen_named && !is_comma {
+                    valid = false;
+                    ast_errors.report(
+                        TypeError::InvalidMacroArg {
+                            msg: "Positional arguments must come before named arguments.",
+                        },
+                        self.range_from_span(construct.span()),
+                    );
+                }
+                continue;
+            };
+            seen_named = true;
+
+            let Token::Identifier(arg_name) = left.token.value() else { continue };
+
+            let exists = self
+                .macro_registries()
+                .into_iter()
+                .flat_map(|registry| registry.iter())
+                .filter(|(key, _)| key.name == macro_name)
+                .any(|(_, definition)| definition.arg_names.contains(arg_name));
+
+            if !exists {
+                valid = false;
+                ast_errors.report(
+                    TypeError::InvalidMacroArg {
+                        msg: &format!("No macro argument named \"{}\" exists.", arg_name),
+                    },
+                    self.range_from_span(left.token.span()),
+                );
+            }
+        }
+
+        valid
     }
 
     pub(super) fn validate_macro_arg_refs(
         &self,
         construct: &Construct<'a>,
-        macro_args: Option<&HashSet<&str>>,
+        macro_args: Option<&MacroArgNames>,
         ast_errors: &mut AstErrors,
     ) {
         match construct {
             Construct::Node { node } => {
+                if let Token::MacroRestArgIdentifier(_) = node.token.value() {
+                    ast_errors.report(
+                        TypeError::InvalidMacroArg {
+                            msg: "Rest arguments can only be spliced into tables, annotations, macro calls, selectors and loops.",
+                        },
+                        self.range_from_span(node.token.span()),
+                    );
+                }
+
                 if let Token::MacroArgIdentifier(name) = node.token.value() {
                     let is_valid = match macro_args {
-                        Some(args) => name.is_some_and(|arg_name| args.contains(arg_name)),
+                        Some(macro_args) => {
+                            name.is_some_and(|arg_name| macro_args.args.contains(arg_name))
+                        }
                         None => false,
                     };
 
@@ -236,49 +618,110 @@ impl<'a> Typechecker<'a> {
                 self.validate_macro_arg_refs(operand, macro_args, ast_errors);
             }
 
-            Construct::Table { body } => {
+            Construct::Table { body }
+            | Construct::AnnotatedTable { body: Some(body), .. }
+            | Construct::MacroCall { body: Some(body), .. } => {
                 let Some(content) = &body.content else { return };
                 for item in content {
-                    self.validate_macro_arg_refs(item, macro_args, ast_errors);
+                    self.validate_macro_arg_list_item(item, macro_args, ast_errors);
                 }
             }
 
-            Construct::AnnotatedTable { body, .. } => {
-                let Some(body) = body else { return };
-                let Some(content) = &body.content else { return };
-                for item in content {
-                    self.validate_macro_arg_refs(item, macro_args, ast_errors);
-                }
+            // A named argument passed to a macro call.
+            Construct::Assignment { right: Some(right), .. } => {
+                self.validate_macro_arg_refs(right, macro_args, ast_errors);
             }
 
             _ => (),
         }
     }
 
+    /// Validates an item of a table, annotation or macro call, or the
+    /// iterable of a loop, which unlike anywhere else may also be a
+    /// `&...name` splice.
+    fn validate_macro_arg_list_item(
+        &self,
+        item: &Construct<'a>,
+        macro_args: Option<&MacroArgNames>,
+        ast_errors: &mut AstErrors,
+    ) {
+        let Construct::Node { node } = item else {
+            return self.validate_macro_arg_refs(item, macro_args, ast_errors);
+        };
+        let Token::MacroRestArgIdentifier(name) = node.token.value() else {
+            return self.validate_macro_arg_refs(item, macro_args, ast_errors);
+        };
+
+        self.validate_rest_arg_ref(*name, node.token.span(), macro_args, ast_errors);
+    }
+
+    fn validate_rest_arg_ref(
+        &self,
+        name: Option<&str>,
+        span: (usize, usize),
+        macro_args: Option<&MacroArgNames>,
+        ast_errors: &mut AstErrors,
+    ) {
+        let rest = macro_args.and_then(|macro_args| macro_args.rest);
+        if name.is_some() && name == rest {
+            return;
+        }
+
+        let msg = match name {
+            Some(name) => format!("No rest argument named \"{}\" exists.", name),
+            None => String::from("Missing macro argument name."),
+        };
+        ast_errors.report(TypeError::InvalidMacroArg { msg: &msg }, self.range_from_span(span));
+    }
+
     fn range_from_span(&self, span: (usize, usize)) -> crate::types::Range {
         crate::types::Range::from_span(&self.parsed.rope, span)
     }
 }
 
-fn collect_macro_arg_names<'a>(args: &Option<Delimited<'a>>) -> HashSet<&'a str> {
-    let mut names = HashSet::new();
-    if let Some(args) = args {
-        if let Some(content) = &args.content {
-            for construct in content {
-                if let Construct::Node { node } = construct {
-                    if let Token::MacroArgIdentifier(Some(name)) = node.token.value() {
-                        names.insert(*name);
-                    }
-                }
-            }
-        }
+/// Stands each `&...name` splice of the rest argument `name` in for the `len`
+/// arguments it collected, so the call can be matched to a definition.
+/// Returns `None` if the call doesn't splice it.
+fn splice_rest_arity<'c>(
+    call_args: MacroCallArgs<'c>,
+    (name, len): (&str, usize),
+) -> Option<MacroCallArgs<'c>> {
+    let is_splice = |arg: &&Construct| rest_arg_splice(arg) == Some(name);
+    if !call_args.positional.iter().any(is_splice) {
+        return None;
+    }
+
+    let positional = call_args
+        .positional
+        .into_iter()
+        .flat_map(|arg| std::iter::repeat_n(arg, if is_splice(&arg) { len } else { 1 }))
+        .collect();
+
+    Some(MacroCallArgs { positional, ..call_args })
+}
+
+fn delimited_items<'a>(
+    delimited: &'a Option<Delimited<'a>>,
+) -> impl Iterator<Item = &'a Construct<'a>> {
+    delimited.iter().flat_map(|delimited| delimited.content.iter().flatten())
+}
+
+/// The arguments a macro's body can refer to.
+pub(super) struct MacroArgNames<'a> {
+    args: HashSet<&'a str>,
+    rest: Option<&'a str>,
+}
+
+fn collect_macro_arg_names<'a>(args: &Option<Delimited<'a>>) -> MacroArgNames<'a> {
+    MacroArgNames {
+        args: collect_macro_def_arg_names(args).into_iter().collect(),
+        rest: collect_macro_def_rest_arg(args),
     }
-    names
 }
 
 fn for_each_macro_call_in_body<'a, F>(body: &MacroBodyContent<'a>, cb: &mut F)
 where
-    F: FnMut(&'a str, usize, (usize, usize)),
+    F: FnMut(&'a str, &MacroCallArgs<'_>, (usize, usize)),
 {
     match body {
         MacroBodyContent::Construct(Some(content)) => {
@@ -301,12 +744,12 @@ where
 
 fn visit_construct_for_calls<'a, F>(construct: &Construct<'a>, cb: &mut F)
 where
-    F: FnMut(&'a str, usize, (usize, usize)),
+    F: FnMut(&'a str, &MacroCallArgs<'_>, (usize, usize)),
 {
     match construct {
         Construct::MacroCall { name, body, .. } => {
             if let Token::MacroCallIdentifier(Some(n)) = name.token.value() {
-                cb(*n, count_macro_call_args(body), name.token.span());
+                cb(n, &collect_macro_call_args(body), name.token.span());
             }
         }
 
@@ -330,18 +773,45 @@ where
             }
         }
 
+        Construct::If {
+            condition,
+            body,
+            else_body,
+            ..
+        } => {
+            if let Some(condition) = condition {
+                visit_construct_for_calls(condition, cb);
+            }
+
+            for branch in [body, else_body] {
+                for inner in branch.iter().flat_map(|branch| branch.content.iter().flatten()) {
+                    visit_construct_for_calls(inner, cb);
+                }
+            }
+        }
+
+        Construct::Each { iterable, body, .. } => {
+            if let Some(iterable) = iterable {
+                visit_construct_for_calls(iterable, cb);
+            }
+
+            for inner in body.iter().flat_map(|body| body.content.iter().flatten()) {
+                visit_construct_for_calls(inner, cb);
+            }
+        }
+
         _ => {}
     }
 }
 
 fn visit_selectors_for_calls<'a, F>(selectors: &[SelectorNode<'a>], cb: &mut F)
 where
-    F: FnMut(&'a str, usize, (usize, usize)),
+    F: FnMut(&'a str, &MacroCallArgs<'_>, (usize, usize)),
 {
     for selector in selectors {
         if let SelectorNode::MacroCall { name, body } = selector {
             if let Token::MacroCallIdentifier(Some(n)) = name.token.value() {
-                cb(*n, count_macro_call_args(body), name.token.span());
+                cb(n, &collect_macro_call_args(body), name.token.span());
             }
         }
     }
@@ -383,18 +853,14 @@ impl<'a> Typechecker<'a> {
             return;
         };
 
-        let mut calls: Vec<(&'a str, usize, (usize, usize))> = Vec::new();
-        for_each_macro_call_in_body(body, &mut |name, arity, span| {
-            calls.push((name, arity, span));
-        });
-
-        for (name, arity, span) in calls {
-            let callee = MacroKey { name, arity };
-
-            if !self.macro_registry.contains_key(&callee) {
-                continue;
+        let mut calls: Vec<(MacroKey<'a>, (usize, usize))> = Vec::new();
+        for_each_macro_call_in_body(body, &mut |name, call_args, span| {
+            if let Some((callee, _)) = find_macro(&self.macro_registry, name, call_args) {
+                calls.push((callee, span));
             }
+        });
 
+        for (callee, span) in calls {
             match color.get(&callee) {
                 Some(DfsColor::Gray) => {
                     ast_errors.report(TypeError::RecursiveMacroCall, self.range_from_span(span))
@@ -5,24 +5,35 @@ use std::{
 };
 
 use crate::{
-    datatype::{Datatype, StaticLookup, evaluate_construct, shorthand_rebind},
+    datatype::{
+        Datatype, Reference, StaticLookup, StringPart, Unit, evaluate_construct, in_srgb_gamut,
+        interpolation_parts, paired_transparency_property, palette::Palettes, shorthand_rebind,
+    },
     lexer::Token,
     parser::{AstErrors, Construct, Delimited, Node, ParsedRsml},
     range_from_span::RangeFromSpan,
     types::{Diagnostic, Range},
 };
 
+use self::conditional::flatten_conditionals;
+use self::conflicts::CheckedRule;
 use self::luaurc::Luaurc;
 use crate::types::LanguageMode;
 pub use crate::macro_registry::{
-    MacroDefinition, MacroKey, MacroRegistry, MacroReturnContext, collect_macro_def_arg_names,
-    macro_return_context,
+    MacroDefinition, MacroKey, MacroRegistry, MacroReturnContext, collect_macro_def_arg_defaults,
+    collect_macro_def_arg_names, collect_macro_def_rest_arg, macro_return_context,
 };
 
+use palette::{IntoColor, Oklch};
 use rangemap::RangeInclusiveMap;
 
 mod annotations;
+mod conditional;
+mod conflicts;
+mod contrast;
 mod derive;
+mod duplicates;
+mod each;
 pub mod luaurc;
 mod macro_check;
 pub(crate) mod multibimap;
@@ -110,6 +121,46 @@ pub enum ResolvedTypeKey {
 
 pub type ResolvedTypes = HashMap<ResolvedTypeKey, Datatype>;
 
+/// A token declared somewhere in the current scope chain, along with the
+/// value it resolved to at that point.
+#[derive(Clone)]
+pub(crate) struct TokenDeclaration {
+    pub definition: Range,
+    /// The derived stylesheet the token was declared in, or `None` if it was
+    /// declared in this one.
+    pub path: Option<PathBuf>,
+    pub datatype: Datatype,
+}
+
+/// Resolves dynamic tokens to the value they were declared with, so
+/// expressions built from them can be checked against the properties they
+/// flow into.
+struct DeclaredTokenLookup<'b, 'a> {
+    typechecker: &'b Typechecker<'a>,
+}
+
+impl<'b, 'a> StaticLookup for DeclaredTokenLookup<'b, 'a> {
+    fn resolve_static(&self, name: &str) -> Datatype {
+        self.typechecker.lookup().resolve_static(name)
+    }
+
+    fn resolve_dynamic(&self, name: &str) -> Datatype {
+        let key = ResolvedTypeKey::Token { name: name.to_string(), is_static: false };
+        self.typechecker
+            .token_declaration(&key)
+            .map(|declaration| declaration.datatype.clone())
+            .unwrap_or(Datatype::None)
+    }
+
+    fn resolves_dynamic_values(&self) -> bool {
+        true
+    }
+
+    fn palettes(&self) -> &Palettes {
+        &self.typechecker.palettes
+    }
+}
+
 #[derive(Clone, Copy)]
 enum LhsKind<'a> {
     Token { name: &'a str, is_static: bool },
@@ -157,13 +208,23 @@ pub struct TypecheckedRsml {
 pub struct Typechecker<'a> {
     pub parsed: &'a ParsedRsml<'a>,
     macro_registry: MacroRegistry<'a>,
+    /// Macros defined by the stylesheets this one derives from.
+    derived_macros: MacroRegistry<'a>,
     pub(crate) static_scopes: Vec<HashMap<String, Datatype>>,
-    pub(crate) declared_tokens: Vec<HashSet<ResolvedTypeKey>>,
+    pub(crate) declared_tokens: Vec<HashMap<ResolvedTypeKey, TokenDeclaration>>,
     pub(crate) language_mode: LanguageMode,
+    contrast_threshold: Option<f64>,
+    /// The user palettes from the luaurc.
+    pub(crate) palettes: Palettes,
+    /// Every rule checked so far, for [`Self::check_conflicts`].
+    checked_rules: Vec<CheckedRule>,
+    /// The rule whose body is being checked, as an index into `checked_rules`.
+    current_rule: Option<usize>,
 }
 
 pub(crate) struct TypecheckerLookup<'a> {
     pub scopes: &'a [HashMap<String, Datatype>],
+    pub palettes: &'a Palettes,
 }
 
 impl<'a> StaticLookup for TypecheckerLookup<'a> {
@@ -179,13 +240,33 @@ impl<'a> StaticLookup for TypecheckerLookup<'a> {
     fn resolve_dynamic(&self, _name: &str) -> Datatype {
         Datatype::None
     }
+
+    fn palettes(&self) -> &Palettes {
+        self.palettes
+    }
 }
 
 impl<'a> Typechecker<'a> {
+    pub(crate) fn lookup(&self) -> TypecheckerLookup<'_> {
+        TypecheckerLookup { scopes: &self.static_scopes, palettes: &self.palettes }
+    }
+
     pub async fn new(
+        parsed: &'a ParsedRsml<'a>,
+        current_path: &Path,
+        luaurc: Option<&mut Luaurc>,
+    ) -> TypecheckedRsml {
+        Self::with_derived(parsed, current_path, luaurc, &HashMap::new()).await
+    }
+
+    /// Typechecks `parsed` with the macros of the stylesheets it derives from
+    /// in scope. `derived` is keyed by canonical path, the same as
+    /// [`TypecheckedRsml::derives`]; derives missing from it are skipped.
+    pub async fn with_derived(
         parsed: &'a ParsedRsml<'a>,
         current_path: &Path,
         mut luaurc: Option<&mut Luaurc>,
+        derived: &HashMap<PathBuf, &'a ParsedRsml<'a>>,
     ) -> TypecheckedRsml {
         let language_mode = parsed.directives.language_mode.unwrap_or_else(|| {
             luaurc
@@ -194,12 +275,26 @@ impl<'a> Typechecker<'a> {
                 .unwrap_or_default()
         });
 
+        let contrast_threshold = luaurc
+            .as_deref()
+            .and_then(|luaurc_ref| luaurc_ref.contrast_threshold);
+
+        let palettes = luaurc
+            .as_deref()
+            .map(|luaurc_ref| luaurc_ref.palettes.clone())
+            .unwrap_or_default();
+
         let mut typechecker: Typechecker<'a> = Self {
             parsed,
             macro_registry: MacroRegistry::new(),
+            derived_macros: MacroRegistry::new(),
             static_scopes: vec![HashMap::new()],
-            declared_tokens: vec![HashSet::new()],
+            declared_tokens: vec![HashMap::new()],
             language_mode,
+            contrast_threshold,
+            palettes,
+            checked_rules: vec![],
+            current_rule: None,
         };
 
         // A separate `AstErrors` is needed because the shared one would conflict
@@ -210,8 +305,9 @@ impl<'a> Typechecker<'a> {
         let mut definitions = Definitions::new();
         let mut resolved_types: ResolvedTypes = HashMap::new();
         let mut dependencies = HashSet::new();
+        let mut registered_derives = HashSet::new();
 
-        for construct in &typechecker.parsed.ast {
+        for construct in flatten_conditionals(&typechecker.parsed.ast) {
             match construct {
                 Construct::Derive {
                     body: Some(derive_body),
@@ -227,6 +323,13 @@ impl<'a> Typechecker<'a> {
                             &mut derives,
                         )
                         .await;
+
+                    typechecker.register_derived_macros(
+                        derived,
+                        &derives,
+                        &mut registered_derives,
+                        &mut ast_errors,
+                    );
                 }
 
                 Construct::Tween {
@@ -277,8 +380,11 @@ impl<'a> Typechecker<'a> {
                             && crate::builtins::BUILTINS.registry.contains_key(&key);
 
                         let local_collision = typechecker.macro_registry.contains_key(&key);
+                        // A derived macro is still reported, but the file's own
+                        // definition is the one calls resolve to, as in the compiler.
+                        let derived_collision = typechecker.derived_macros.contains_key(&key);
 
-                        if builtin_collision || local_collision {
+                        if builtin_collision || local_collision || derived_collision {
                             ast_errors.report(
                                 TypeError::DuplicateMacro {
                                     name: name_str,
@@ -286,11 +392,15 @@ impl<'a> Typechecker<'a> {
                                 },
                                 Range::from_span(&typechecker.parsed.rope, construct.span()),
                             );
-                        } else {
+                        }
+
+                        if !(builtin_collision || local_collision) {
                             typechecker.macro_registry.insert(
                                 key,
                                 MacroDefinition {
                                     arg_names,
+                                    arg_defaults: collect_macro_def_arg_defaults(args),
+                                    rest_arg: collect_macro_def_rest_arg(args),
                                     body: body.as_ref().map(|b| &b.content),
                                     return_context: context,
                                 },
@@ -302,6 +412,9 @@ impl<'a> Typechecker<'a> {
                 }
 
                 Construct::MacroCall { name, body, .. } => {
+                    if typechecker.language_mode == LanguageMode::Strict {
+                        typechecker.validate_token_refs(construct, &mut ast_errors);
+                    }
                     typechecker.validate_macro_call(
                         name,
                         body,
@@ -348,11 +461,37 @@ impl<'a> Typechecker<'a> {
                     );
                 }
 
+                Construct::If {
+                    condition,
+                    body,
+                    else_body,
+                    ..
+                } => {
+                    typechecker.typecheck_if(condition, [body, else_body], &mut ast_errors);
+                }
+
+                Construct::Each {
+                    variable,
+                    iterable,
+                    body,
+                    ..
+                } => {
+                    typechecker.typecheck_each(
+                        (variable, iterable, body),
+                        &vec![],
+                        &mut ast_errors,
+                        &mut definitions,
+                        &mut resolved_types,
+                    );
+                }
+
                 _ => (),
             }
         }
 
         typechecker.detect_recursive_macro_calls(&mut ast_errors);
+        typechecker.check_contrast(&typechecker.parsed.ast, &resolved_types, &mut ast_errors);
+        typechecker.check_conflicts(&mut ast_errors);
 
         TypecheckedRsml {
             errors: ast_errors,
@@ -385,10 +524,14 @@ impl<'a> Typechecker<'a> {
         // collapses to `unknown`.
         let enum_valid = self.validate_enum_refs(left, right, ast_errors);
 
+        // Set once an error explaining why the LHS is `unknown` has been
+        // reported, so strict mode doesn't pile an unresolved-type error on top.
+        let mut already_reported = !enum_valid;
+
         let resolved_type = if !enum_valid {
             Datatype::None
         } else {
-            let lookup = TypecheckerLookup { scopes: &self.static_scopes };
+            let lookup = self.lookup();
 
             let evaluated = match lhs_kind {
                 LhsKind::Token { .. } => {
@@ -405,6 +548,41 @@ impl<'a> Typechecker<'a> {
                 LhsKind::Property { .. } => evaluate_construct(right, Some(name), &lookup),
             };
 
+            if let Some(color) = evaluated.as_ref().and_then(authored_color)
+                && matches!(right, Construct::AnnotatedTable { .. })
+                && !in_srgb_gamut(color)
+            {
+                ast_errors.report(
+                    TypeError::OutOfGamutColor,
+                    Range::from_span(&self.parsed.rope, right.span()),
+                );
+            }
+
+            if let (LhsKind::Property { name }, Some(Datatype::Translucent(..))) =
+                (lhs_kind, &evaluated)
+            {
+                self.check_paired_transparency(name, current_classes, right, ast_errors);
+            }
+
+            if let (LhsKind::Property { name }, Some(Datatype::Measure(_, got))) =
+                (lhs_kind, &evaluated)
+            {
+                let expected = Unit::of_property(name);
+                if expected != Some(*got) {
+                    ast_errors.report(
+                        TypeError::UnitMismatch { name: name.to_string(), expected, got: *got },
+                        Range::from_span(&self.parsed.rope, right.span()),
+                    );
+                    already_reported = true;
+                }
+            }
+
+            let evaluated = evaluated.and_then(|datatype| {
+                let checked = self.enforce_strict_datatype(lhs_kind, datatype, right, ast_errors);
+                already_reported |= checked.is_none();
+                checked
+            });
+
             match lhs_kind {
                 LhsKind::Token { is_static, .. } => match evaluated {
                     Some(Datatype::IncompleteEnumShorthand(variant)) => {
@@ -412,14 +590,14 @@ impl<'a> Typechecker<'a> {
                     }
                     Some(d) if is_static => d,
                     Some(d) => d
-                        .coerce_to_variant(Some(name))
+                        .coerce_to_variant_in(Some(name), self.language_mode)
                         .map(Datatype::Variant)
                         .unwrap_or(Datatype::None),
                     None => Datatype::None,
                 },
                 LhsKind::Property { .. } => match evaluated {
                     Some(d) => d
-                        .coerce_to_variant(Some(name))
+                        .coerce_to_variant_in(Some(name), self.language_mode)
                         .map(Datatype::Variant)
                         .unwrap_or(Datatype::None),
                     None => Datatype::None,
@@ -438,10 +616,17 @@ impl<'a> Typechecker<'a> {
                 }
 
                 let key = ResolvedTypeKey::Token { name: name.to_string(), is_static };
-                resolved_types.insert(key.clone(), resolved_type);
+                resolved_types.insert(key.clone(), resolved_type.clone());
 
                 if let Some(frame) = self.declared_tokens.last_mut() {
-                    frame.insert(key);
+                    frame.insert(
+                        key,
+                        TokenDeclaration {
+                            definition: self.parsed.range_from_span((start, end)),
+                            path: None,
+                            datatype: resolved_type,
+                        },
+                    );
                 }
 
                 definitions.insert(
@@ -450,6 +635,18 @@ impl<'a> Typechecker<'a> {
                 );
             }
             LhsKind::Property { .. } => {
+                if self.language_mode == LanguageMode::Strict
+                    && !already_reported
+                    && !current_classes.is_empty()
+                    && resolved_type == Datatype::None
+                    && !resolves_at_runtime(right)
+                {
+                    ast_errors.report(
+                        TypeError::UnresolvedPropertyType { name: name.to_string() },
+                        Range::from_span(&self.parsed.rope, right.span()),
+                    );
+                }
+
                 self.check_property_against_reflection(
                     name,
                     &resolved_type,
@@ -475,6 +672,94 @@ impl<'a> Typechecker<'a> {
         }
     }
 
+    /// Strict mode refuses the implicit conversions that nonstrict mode
+    /// performs quietly: collapsing a tuple down to its first element, and
+    /// enum shorthands with no key to pin down their enum. Returns `None` once
+    /// an error has been reported so the LHS resolves to `unknown`.
+    fn enforce_strict_datatype(
+        &self,
+        lhs_kind: LhsKind<'a>,
+        datatype: Datatype,
+        right: &Construct<'a>,
+        ast_errors: &mut AstErrors,
+    ) -> Option<Datatype> {
+        if self.language_mode != LanguageMode::Strict {
+            return Some(datatype);
+        }
+
+        let range = Range::from_span(&self.parsed.rope, right.span());
+
+        // Static tokens keep tuples intact, so only the LHS kinds which
+        // coerce down to a single variant are affected.
+        if let (
+            Datatype::TupleData(_),
+            LhsKind::Property { name } | LhsKind::Token { name, is_static: false },
+        ) = (&datatype, lhs_kind)
+        {
+            ast_errors.report(TypeError::ImplicitTupleCoercion { name }, range);
+            return None;
+        }
+
+        if let Some(variant) = datatype.nested_enum_shorthand() {
+            ast_errors.report(
+                TypeError::UnpinnedEnumShorthand { variant: variant.to_string() },
+                range,
+            );
+            return None;
+        }
+
+        if let (Datatype::IncompleteEnumShorthand(variant), LhsKind::Token { name, .. }) =
+            (&datatype, lhs_kind)
+            && !annotations::enum_exists(shorthand_rebind(name))
+        {
+            ast_errors.report(
+                TypeError::UnpinnedEnumShorthand { variant: variant.clone() },
+                range,
+            );
+            return None;
+        }
+
+        Some(datatype)
+    }
+
+    /// A translucent colour also sets the transparency property paired with the
+    /// colour property it's assigned to, so one of the selector classes has to
+    /// have it.
+    fn check_paired_transparency(
+        &self,
+        property_name: &str,
+        current_classes: &[String],
+        right: &Construct<'a>,
+        ast_errors: &mut AstErrors,
+    ) {
+        let paired = match paired_transparency_property(property_name) {
+            Some(_) if current_classes.is_empty() => return,
+            Some(paired) => paired,
+            None => {
+                ast_errors.report(
+                    TypeError::UnpairedTransparency { name: property_name.to_string() },
+                    Range::from_span(&self.parsed.rope, right.span()),
+                );
+                return;
+            }
+        };
+
+        let Ok(db) = rbx_reflection_database::get() else {
+            return;
+        };
+
+        let has_paired = current_classes
+            .iter()
+            .any(|class_name| properties::lookup_property(db, class_name, &paired).is_some());
+
+        if !has_paired {
+            ast_errors.report(
+                TypeError::UnpairedTransparency { name: property_name.to_string() },
+                Range::from_span(&self.parsed.rope, right.span()),
+            );
+        }
+    }
+
     /// Cross-checks a property assignment against the reflection database.
     /// Emits `UnknownProperty` when the property doesn't appear on the selector
     /// classes, and `PropertyTypeMismatch` when the RHS's runtime type doesn't
@@ -533,8 +818,31 @@ impl<'a> Typechecker<'a> {
             return;
         }
 
-        let Datatype::Variant(value) = resolved_type else {
-            return;
+        // Dynamic tokens never resolve to anything on their own, so an
+        // expression built from them is checked against the values they were
+        // declared with. A mismatch is blamed on the token when only one is
+        // involved.
+        let mut referenced = vec![];
+        token_references(right, &mut referenced);
+
+        let token_declaration = match referenced.as_slice() {
+            [key] => self.token_declaration(key).map(|declaration| (key.clone(), declaration)),
+            _ => None,
+        };
+
+        let value = match resolved_type {
+            Datatype::Variant(value) => value.clone(),
+
+            _ if !referenced.is_empty() => {
+                let lookup = DeclaredTokenLookup { typechecker: self };
+                let evaluated = evaluate_construct(right, Some(property_name), &lookup);
+                let coerced = evaluated
+                    .and_then(|datatype| datatype.coerce_to_variant(Some(property_name)));
+                let Some(value) = coerced else { return };
+                value
+            }
+
+            _ => return,
         };
 
         // Multi-class selectors with differing declared types are essentially
@@ -548,18 +856,39 @@ impl<'a> Typechecker<'a> {
             return;
         };
 
-        if properties::variant_matches(descriptor, value) {
+        if properties::variant_matches(descriptor, &value) {
             return;
         }
 
-        ast_errors.report(
-            TypeError::PropertyTypeMismatch {
+        let expected = properties::expected_type_label(descriptor);
+        let got = crate::datatype::variant_type_name(value.ty()).to_string();
+
+        let error = match token_declaration {
+            Some((ResolvedTypeKey::Token { name, is_static }, declaration)) => {
+                TypeError::TokenTypeMismatch {
+                    name,
+                    is_static,
+                    property: property_name.to_string(),
+                    expected,
+                    got,
+                    definition: declaration.definition,
+                    path: declaration.path.clone(),
+                }
+            }
+
+            _ => TypeError::PropertyTypeMismatch {
                 name: property_name.to_string(),
-                expected: properties::expected_type_label(descriptor),
-                got: crate::datatype::variant_type_name(value.ty()).to_string(),
+                expected,
+                got,
             },
-            Range::from_span(&self.parsed.rope, right.span()),
-        );
+        };
+
+        ast_errors.report(error, Range::from_span(&self.parsed.rope, right.span()));
+    }
+
+    /// Finds the innermost declaration of a token visible from the current scope.
+    pub(crate) fn token_declaration(&self, key: &ResolvedTypeKey) -> Option<&TokenDeclaration> {
+        self.declared_tokens.iter().rev().find_map(|frame| frame.get(key))
     }
 
     /// Validates every enum reference on the RHS of an assignment against the
@@ -713,23 +1042,26 @@ impl<'a> Typechecker<'a> {
                 let (name, is_static) = match node.token.value() {
                     Token::TokenIdentifier(n) => (*n, false),
                     Token::StaticTokenIdentifier(n) => (*n, true),
+                    Token::ColorPalette(color) => {
+                        if self.palettes.color(color).is_none() {
+                            ast_errors.report(
+                                TypeError::UnknownPaletteColor { name: color },
+                                self.parsed.range_from_span(node.token.span()),
+                            );
+                        }
+                        return;
+                    }
+                    Token::StringInterpolated(content) => {
+                        self.validate_interpolated_token_refs(
+                            content,
+                            node.token.start() + 1,
+                            ast_errors,
+                        );
+                        return;
+                    }
                     _ => return,
                 };
-                let key = ResolvedTypeKey::Token {
-                    name: name.to_string(),
-                    is_static,
-                };
-                let in_scope = self
-                    .declared_tokens
-                    .iter()
-                    .rev()
-                    .any(|frame| frame.contains(&key));
-                if !in_scope {
-                    ast_errors.report(
-                        TypeError::UndefinedToken { name, is_static },
-                        self.parsed.range_from_span(node.token.span()),
-                    );
-                }
+                self.validate_token_ref(name, is_static, node.token.span(), ast_errors);
             }
             Construct::MathOperation { left, right, .. } => {
                 self.validate_token_refs(left, ast_errors);
@@ -753,10 +1085,57 @@ impl<'a> Typechecker<'a> {
             } => {
                 self.validate_token_refs_delimited(body, ast_errors);
             }
+            // A named macro argument.
+            Construct::Assignment {
+                right: Some(right), ..
+            } => {
+                self.validate_token_refs(right, ast_errors);
+            }
             _ => {}
         }
     }
 
+    fn validate_token_ref(
+        &self,
+        name: &'a str,
+        is_static: bool,
+        span: (usize, usize),
+        ast_errors: &mut AstErrors,
+    ) {
+        let key = ResolvedTypeKey::Token {
+            name: name.to_string(),
+            is_static,
+        };
+        if self.token_declaration(&key).is_none() {
+            ast_errors.report(
+                TypeError::UndefinedToken { name, is_static },
+                self.parsed.range_from_span(span),
+            );
+        }
+    }
+
+    /// Checks the `{$Name}` and `{$!Name}` placeholders in an interpolated
+    /// string, whose content begins at `offset`.
+    fn validate_interpolated_token_refs(
+        &self,
+        content: &'a str,
+        offset: usize,
+        ast_errors: &mut AstErrors,
+    ) {
+        for part in interpolation_parts(content) {
+            let StringPart::Placeholder { reference, span } = part else { continue };
+            let span = (offset + span.0, offset + span.1);
+
+            match reference {
+                Reference::StaticToken(name) => {
+                    self.validate_token_ref(name, true, span, ast_errors)
+                }
+                Reference::Token(name) => self.validate_token_ref(name, false, span, ast_errors),
+                Reference::MacroArg(_) => (),
+            }
+        }
+    }
+
     fn validate_token_refs_delimited(
         &self,
         delim: &Delimited<'a>,
@@ -771,6 +1150,104 @@ impl<'a> Typechecker<'a> {
     }
 }
 
+/// Collects the keys of the tokens `construct` refers to, including those in
+/// the operands of math operations and annotations.
+fn token_references(construct: &Construct, keys: &mut Vec<ResolvedTypeKey>) {
+    match construct {
+        Construct::Node { node } => {
+            let references = match node.token.value() {
+                Token::TokenIdentifier(name) => vec![(*name, false)],
+                Token::StaticTokenIdentifier(name) => vec![(*name, true)],
+                Token::StringInterpolated(content) => interpolation_parts(content)
+                    .into_iter()
+                    .filter_map(|part| match part {
+                        StringPart::Placeholder { reference: Reference::Token(name), .. } => {
+                            Some((name, false))
+                        }
+                        StringPart::Placeholder {
+                            reference: Reference::StaticToken(name), ..
+                        } => Some((name, true)),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => return,
+            };
+
+            for (name, is_static) in references {
+                let key = ResolvedTypeKey::Token { name: name.to_string(), is_static };
+                if !keys.contains(&key) {
+                    keys.push(key);
+                }
+            }
+        }
+
+        Construct::MathOperation { left, right, .. } => {
+            token_references(left, keys);
+            if let Some(right) = right {
+                token_references(right, keys);
+            }
+        }
+
+        Construct::UnaryMinus { operand, .. } => token_references(operand, keys),
+
+        Construct::Table { body }
+        | Construct::AnnotatedTable {
+            body: Some(body), ..
+        } => {
+            for item in body.content.iter().flatten() {
+                token_references(item, keys);
+            }
+        }
+
+        _ => (),
+    }
+}
+
+/// Dynamic tokens, macro args and macro calls only take on a value at runtime
+/// or expansion time, so the typechecker can't be expected to resolve them.
+fn resolves_at_runtime(construct: &Construct) -> bool {
+    match construct {
+        Construct::Node { node } => match node.token.value() {
+            Token::StringInterpolated(content) => {
+                interpolation_parts(content).iter().any(|part| {
+                    matches!(part, StringPart::Placeholder { reference: Reference::Token(_), .. })
+                })
+            }
+            token => matches!(
+                token,
+                Token::TokenIdentifier(_)
+                    | Token::MacroArgIdentifier(_)
+                    | Token::MacroRestArgIdentifier(_)
+                    | Token::Nil
+            ),
+        },
+
+        Construct::MacroCall { .. } => true,
+
+        Construct::MathOperation { left, right, .. } => {
+            resolves_at_runtime(left) || right.as_deref().is_some_and(resolves_at_runtime)
+        }
+
+        Construct::UnaryMinus { operand, .. } => resolves_at_runtime(operand),
+
+        Construct::Table { body } | Construct::AnnotatedTable { body: Some(body), .. } => {
+            body.content.iter().flatten().any(resolves_at_runtime)
+        }
+
+        _ => false,
+    }
+}
+
+/// The OKLCH form of a colour written with `oklab(...)`, `oklch(...)` or one of
+/// the colour adjustment annotations, which can all leave the sRGB gamut.
+fn authored_color(datatype: &Datatype) -> Option<Oklch> {
+    match datatype {
+        Datatype::Oklch(color) => Some(*color),
+        Datatype::Oklab(color) => Some((*color).into_color()),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::typechecker::*;
@@ -927,6 +1404,40 @@ mod tests {
         assert!(result.errors.is_empty());
     }
 
+    #[tokio::test]
+    async fn negation_keeps_the_selected_class() {
+        let result = typecheck("TextButton:not(.Ghost) {}").await;
+        assert_eq!(result.selectors.len(), 1);
+        assert_eq!(result.selectors[0].2, vec!["TextButton"]);
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn grouping_narrows_to_the_grouped_classes() {
+        let result = typecheck(":is(TextButton, TextLabel) {}").await;
+        assert_eq!(result.selectors.len(), 1);
+        assert_eq!(result.selectors[0].2, vec!["TextButton", "TextLabel"]);
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn states_inside_negation_and_grouping_are_checked() {
+        let result = typecheck(
+            "TextButton:not(:Bogus) {}\n\
+             :is(TextButton:hover, TextLabel:Nope) {}\n\
+             TextButton:not(:is(:Missing)) {}",
+        )
+        .await;
+        assert_eq!(
+            result.errors,
+            vec![
+                "Type Error (Invalid Selector): No state named \"Bogus\" exists.",
+                "Type Error (Invalid Selector): No state named \"Nope\" exists.",
+                "Type Error (Invalid Selector): No state named \"Missing\" exists.",
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn invalid_class_name() {
         let result = typecheck("NotARealClass {}").await;
@@ -1186,54 +1697,143 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn scope_inserted_for_rule_body() {
-        let result = typecheck("Frame {}").await;
-        assert_eq!(result.scopes.len(), 1);
-        assert_eq!(result.scopes[0].2, vec!["Frame"]);
+    async fn attribute_selector_after_class() {
+        let result = typecheck("Frame [Disabled] {}").await;
+        assert_eq!(result.selectors.len(), 1);
+        assert_eq!(result.selectors[0].2, vec!["Frame"]);
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
     }
 
     #[tokio::test]
-    async fn scope_has_union_types() {
-        let result = typecheck("Frame, TextButton {}").await;
-        assert_eq!(result.scopes.len(), 1);
-        assert_eq!(result.scopes[0].2, vec!["Frame", "TextButton"]);
+    async fn top_level_attribute_selector_resolves_to_instance() {
+        let result = typecheck("[Variant = \"primary\"] {}").await;
+        assert_eq!(result.selectors.len(), 1);
+        assert_eq!(result.selectors[0].2, vec!["Instance"]);
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
     }
 
     #[tokio::test]
-    async fn nested_scopes_have_correct_types() {
-        let result = typecheck("Frame { > TextButton {} }").await;
-        // Outer scope gets split by inner scope insertion, so 3 entries:
-        // two halves of the outer Frame scope + the inner TextButton scope
-        assert!(result.scopes.len() >= 2);
-        let scope_types: Vec<&Vec<String>> = result.scopes.iter().map(|s| &s.2).collect();
-        assert!(scope_types.contains(&&vec!["Frame".to_string()]));
-        assert!(scope_types.contains(&&vec!["TextButton".to_string()]));
+    async fn attribute_selector_value_must_be_literal() {
+        let result = typecheck("Frame [Variant = foo bar] {}").await;
+        assert!(
+            result.errors.iter().any(|err| err.contains("The value of attribute \"Variant\"")),
+            "expected Invalid Selector error, got: {:?}",
+            result.errors
+        );
     }
 
     #[tokio::test]
-    async fn scope_with_combinator() {
-        let result = typecheck("Frame > TextButton {}").await;
-        assert_eq!(result.scopes.len(), 1);
-        assert_eq!(result.scopes[0].2, vec!["TextButton"]);
+    async fn attribute_selector_value_matches_token_type() {
+        let result = typecheck("$Variant = \"primary\"; Frame [Variant = \"secondary\"] {}").await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
     }
 
     #[tokio::test]
-    async fn scope_with_pseudo_selector() {
-        let result = typecheck("Frame ::UIPadding {}").await;
-        assert_eq!(result.scopes.len(), 1);
-        assert_eq!(result.scopes[0].2, vec!["UIPadding"]);
+    async fn attribute_selector_value_mismatches_token_type() {
+        let result = typecheck("$Variant = \"primary\"; Frame [Variant = -3] {}").await;
+        assert!(
+            result.errors.iter().any(|err| err.contains("Attribute Type Mismatch")),
+            "expected Attribute Type Mismatch error, got: {:?}",
+            result.errors
+        );
+    }
+
+    fn duplicate_assignment_warnings(result: &TypecheckResult) -> Vec<&String> {
+        result
+            .errors
+            .iter()
+            .filter(|err| err.contains("Duplicate Assignment"))
+            .collect()
     }
 
     #[tokio::test]
-    async fn top_level_state_selector_resolves_to_instance() {
-        let result = typecheck(":hover {}").await;
-        assert_eq!(result.selectors.len(), 1);
-        assert_eq!(result.selectors[0].2, vec!["Instance"]);
-        assert!(result.errors.is_empty());
+    async fn duplicate_property_in_rule_warns() {
+        let result = typecheck("Frame {\n    Visible = true;\n    Visible = false;\n}").await;
+        let warnings = duplicate_assignment_warnings(&result);
+        assert_eq!(warnings.len(), 1, "got: {:?}", result.errors);
+        assert!(warnings[0].contains("`Visible`"));
+        assert!(warnings[0].contains("line 2"));
     }
 
     #[tokio::test]
-    async fn top_level_state_selector_invalid_state() {
+    async fn duplicate_token_in_rule_warns() {
+        let result = typecheck("Frame { $Gap = 4; $Gap = 8; }").await;
+        let warnings = duplicate_assignment_warnings(&result);
+        assert_eq!(warnings.len(), 1, "got: {:?}", result.errors);
+        assert!(warnings[0].contains("`$Gap`"));
+    }
+
+    #[tokio::test]
+    async fn same_property_in_different_rules_does_not_warn() {
+        let result = typecheck("Frame { Visible = true; }\nFrame { Visible = false; > Frame { Visible = true; } }").await;
+        assert!(duplicate_assignment_warnings(&result).is_empty(), "got: {:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn duplicate_through_construct_macro_warns() {
+        let result = typecheck(
+            "@macro Hide () { Visible = false; }\nFrame { Visible = true; Hide!(); }",
+        )
+        .await;
+        assert_eq!(duplicate_assignment_warnings(&result).len(), 1, "got: {:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn repeated_builtin_padding_warns() {
+        let result = typecheck("Frame { Padding!(4px); Padding!(8px, 4px); }").await;
+        // One warning per side of the second `::UIPadding`.
+        assert_eq!(duplicate_assignment_warnings(&result).len(), 4, "got: {:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn scope_inserted_for_rule_body() {
+        let result = typecheck("Frame {}").await;
+        assert_eq!(result.scopes.len(), 1);
+        assert_eq!(result.scopes[0].2, vec!["Frame"]);
+    }
+
+    #[tokio::test]
+    async fn scope_has_union_types() {
+        let result = typecheck("Frame, TextButton {}").await;
+        assert_eq!(result.scopes.len(), 1);
+        assert_eq!(result.scopes[0].2, vec!["Frame", "TextButton"]);
+    }
+
+    #[tokio::test]
+    async fn nested_scopes_have_correct_types() {
+        let result = typecheck("Frame { > TextButton {} }").await;
+        // Outer scope gets split by inner scope insertion, so 3 entries:
+        // two halves of the outer Frame scope + the inner TextButton scope
+        assert!(result.scopes.len() >= 2);
+        let scope_types: Vec<&Vec<String>> = result.scopes.iter().map(|s| &s.2).collect();
+        assert!(scope_types.contains(&&vec!["Frame".to_string()]));
+        assert!(scope_types.contains(&&vec!["TextButton".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn scope_with_combinator() {
+        let result = typecheck("Frame > TextButton {}").await;
+        assert_eq!(result.scopes.len(), 1);
+        assert_eq!(result.scopes[0].2, vec!["TextButton"]);
+    }
+
+    #[tokio::test]
+    async fn scope_with_pseudo_selector() {
+        let result = typecheck("Frame ::UIPadding {}").await;
+        assert_eq!(result.scopes.len(), 1);
+        assert_eq!(result.scopes[0].2, vec!["UIPadding"]);
+    }
+
+    #[tokio::test]
+    async fn top_level_state_selector_resolves_to_instance() {
+        let result = typecheck(":hover {}").await;
+        assert_eq!(result.selectors.len(), 1);
+        assert_eq!(result.selectors[0].2, vec!["Instance"]);
+        assert!(result.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn top_level_state_selector_invalid_state() {
         let result = typecheck(":notastate {}").await;
         assert_eq!(result.selectors.len(), 1);
         assert_eq!(result.selectors[0].2, vec!["Instance"]);
@@ -1816,6 +2416,12 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn builtin_padding_named_args_no_error() {
+        let result = typecheck("Frame { Padding!(top = 4px, left = 2px); }").await;
+        assert!(result.errors.is_empty(), "unexpected errors: {:?}", result.errors);
+    }
+
     #[tokio::test]
     async fn builtin_corner_radius_no_error() {
         let result = typecheck("Frame { CornerRadius!(8); }").await;
@@ -2592,217 +3198,1086 @@ mod tests {
         assert!(matches!(dt, Datatype::Oklch(_)), "got {:?}", dt);
     }
 
-    fn has_undefined_token_error(result: &TypecheckResult) -> bool {
-        result.errors.iter().any(|err| err.contains("Undefined Token"))
+    #[tokio::test]
+    async fn static_token_lighten_raises_lightness() {
+        let result = typecheck("$!Base = oklch(0.5, 0.1, 180); $!Hover = lighten($!Base, 10%);").await;
+        let Datatype::Oklch(color) = find_token(&result, "Hover", true) else {
+            panic!("expected Oklch, got {:?}", find_token(&result, "Hover", true));
+        };
+        assert!((color.l - 0.6).abs() < 1e-4, "got {:?}", color);
     }
 
     #[tokio::test]
-    async fn undefined_dynamic_token_direct() {
-        let result = typecheck("$A = $nope;").await;
-        assert!(
-            has_undefined_token_error(&result),
-            "expected Undefined Token error, got: {:?}",
-            result.errors
-        );
+    async fn static_token_complement_rotates_hue() {
+        let result = typecheck("$!X = complement(oklch(0.5, 0.1, 90));").await;
+        let Datatype::Oklch(color) = find_token(&result, "X", true) else {
+            panic!("expected Oklch, got {:?}", find_token(&result, "X", true));
+        };
+        assert!((color.hue.into_positive_degrees() - 270.0).abs() < 1e-3, "got {:?}", color);
     }
 
     #[tokio::test]
-    async fn undefined_static_token_direct() {
-        let result = typecheck("$!A = $!nope;").await;
-        assert!(
-            has_undefined_token_error(&result),
-            "expected Undefined Token error, got: {:?}",
-            result.errors
-        );
+    async fn static_token_alpha_makes_color_translucent() {
+        let result = typecheck("$!Overlay = alpha(#000000, 40%); $!Hover = lighten($!Overlay, 10%);")
+            .await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+
+        let Datatype::Translucent(_, transparency) = find_token(&result, "Overlay", true) else {
+            panic!("expected translucent colour, got {:?}", find_token(&result, "Overlay", true));
+        };
+        assert!((transparency - 0.6).abs() < 1e-6);
+
+        let Datatype::Translucent(color, transparency) = find_token(&result, "Hover", true) else {
+            panic!("expected translucent colour, got {:?}", find_token(&result, "Hover", true));
+        };
+        assert!(matches!(**color, Datatype::Oklch(_)));
+        assert!((transparency - 0.6).abs() < 1e-6);
     }
 
     #[tokio::test]
-    async fn undefined_token_in_property_assignment() {
-        let result = typecheck("Frame { Size = $nope; }").await;
-        assert!(
-            has_undefined_token_error(&result),
-            "expected Undefined Token error, got: {:?}",
-            result.errors
-        );
+    async fn static_token_math_annotations() {
+        let result = typecheck(
+            "$!Clamped = clamp(150, 0, 100); $!Mod = mod(-1, 3); $!Sign = sign(-4); $!Sqrt = sqrt(16);",
+        )
+        .await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+
+        for (name, expected) in [("Clamped", 100.0), ("Mod", 2.0), ("Sign", -1.0), ("Sqrt", 4.0)] {
+            assert_eq!(
+                find_token(&result, name, true),
+                &Datatype::Variant(rbx_types::Variant::Float64(expected)),
+                "{}",
+                name
+            );
+        }
     }
 
     #[tokio::test]
-    async fn undefined_token_in_annotated_tuple() {
-        let result = typecheck("Frame { Size = udim2(0%, $!Hello, 0%, 0%); }").await;
-        assert!(
-            has_undefined_token_error(&result),
-            "expected Undefined Token error, got: {:?}",
-            result.errors
+    async fn static_token_min_is_component_wise() {
+        let result =
+            typecheck("$!Size = min(udim2(0.5, 20, 0.5, 20), udim2(0.25, 40, 1, 0), 0.4);").await;
+        let expected = rbx_types::UDim2::new(
+            rbx_types::UDim::new(0.25, 0),
+            rbx_types::UDim::new(0.4, 0),
+        );
+        assert_eq!(
+            find_token(&result, "Size", true),
+            &Datatype::Variant(rbx_types::Variant::UDim2(expected))
         );
     }
 
     #[tokio::test]
-    async fn undefined_token_in_math() {
-        let result = typecheck("$!A = 10; $!B = $!A + $!nope;").await;
+    async fn clamp_requires_three_args() {
+        let result = typecheck("Frame { Size = clamp(udim2(1, 0, 1, 0), 0); }").await;
         assert!(
-            has_undefined_token_error(&result),
-            "expected Undefined Token error, got: {:?}",
+            result.errors.iter().any(|err| err.contains("Wrong Annotation Argument Count")),
+            "expected Wrong Annotation Argument Count error, got: {:?}",
             result.errors
         );
     }
 
     #[tokio::test]
-    async fn undefined_token_in_table() {
-        let result = typecheck("$A = { $nope };").await;
-        assert!(
-            has_undefined_token_error(&result),
-            "expected Undefined Token error, got: {:?}",
-            result.errors
-        );
+    async fn static_token_hsl_converts_to_color3() {
+        let result = typecheck("$!X = hsl(120, 100%, 50%);").await;
+        let Datatype::Variant(rbx_types::Variant::Color3(color)) = find_token(&result, "X", true) else {
+            panic!("expected Color3, got {:?}", find_token(&result, "X", true));
+        };
+        assert!(color.r.abs() < 1e-4 && (color.g - 1.0).abs() < 1e-4 && color.b.abs() < 1e-4);
     }
 
     #[tokio::test]
-    async fn same_statement_self_ref_errors() {
-        let result = typecheck("$A = $A;").await;
-        assert!(
-            has_undefined_token_error(&result),
-            "expected Undefined Token error, got: {:?}",
-            result.errors
-        );
+    async fn static_token_hsva_pairs_color_with_transparency() {
+        let result = typecheck("$!X = hsva(0, 1, 1, 0.25);").await;
+        let Datatype::Translucent(color, transparency) = find_token(&result, "X", true) else {
+            panic!("expected translucent colour, got {:?}", find_token(&result, "X", true));
+        };
+        assert!(matches!(**color, Datatype::Variant(rbx_types::Variant::Color3(_))));
+        assert!((transparency - 0.75).abs() < 1e-6);
     }
 
     #[tokio::test]
-    async fn dynamic_and_static_distinct_keys() {
-        let result = typecheck("$A = 10; $B = $!A;").await;
-        assert!(
-            has_undefined_token_error(&result),
-            "expected Undefined Token error, got: {:?}",
-            result.errors
+    async fn translucent_colors_need_a_transparency_property() {
+        let result = typecheck(
+            "Frame { BackgroundColor3 = hsla(0, 1, 0.5, 0.5); }\n\
+             TextLabel { TextColor3 = hsla(0, 1, 0.5, 0.5); }\n\
+             Frame { BorderColor3 = hsla(0, 1, 0.5, 0.5); }",
+        )
+        .await;
+        assert_eq!(
+            result.errors,
+            vec![
+                "Type Error (Unpaired Transparency): `BorderColor3` has no transparency property to hold this colour's alpha."
+            ],
         );
     }
 
     #[tokio::test]
-    async fn defined_static_token_no_error() {
-        let result = typecheck("$!A = 10; $!B = $!A;").await;
-        assert!(
-            !has_undefined_token_error(&result),
-            "unexpected Undefined Token error, got: {:?}",
-            result.errors
-        );
+    async fn hsl_annotation_accepts_percentages() {
+        let result = typecheck("Frame { BackgroundColor3 = hsl(210, 40%, 0.5); }").await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
     }
 
     #[tokio::test]
-    async fn defined_dynamic_token_no_error() {
-        let result = typecheck("$A = 10; Frame { Size = $A; }").await;
-        assert!(
-            !has_undefined_token_error(&result),
-            "unexpected Undefined Token error, got: {:?}",
-            result.errors
-        );
+    async fn color_adjustment_annotations_accept_colors() {
+        let result = typecheck(
+            "Frame {\n    BackgroundColor3 = darken(#3366ff, 0.1);\n    BorderColor3 = mix(#ff0000, saturate(#0000ff, 20%), 25%);\n}",
+        )
+        .await;
+        let annotation_errors: Vec<_> = result
+            .errors
+            .iter()
+            .filter(|err| err.contains("Annotation"))
+            .collect();
+        assert!(annotation_errors.is_empty(), "unexpected errors: {:?}", annotation_errors);
     }
 
     #[tokio::test]
-    async fn nested_rule_inherits_outer_token() {
-        let result = typecheck("$!A = 10; Frame { $!B = $!A; }").await;
+    async fn color_adjustment_annotation_rejects_number_color() {
+        let result = typecheck("Frame { BackgroundColor3 = hue_rotate(5, 30); }").await;
         assert!(
-            !has_undefined_token_error(&result),
-            "unexpected Undefined Token error, got: {:?}",
+            result.errors.iter().any(|err| err.contains("Wrong Annotation Argument Type")),
+            "expected Wrong Annotation Argument Type error, got: {:?}",
             result.errors
         );
     }
 
+    const OUT_OF_GAMUT_WARNING: &str = "Type Warning (Out Of Gamut Color): This color can't be displayed in sRGB, so its chroma will be reduced until it fits.";
+
     #[tokio::test]
-    async fn inner_shadow_resolves_to_outer_in_same_rhs() {
-        let result = typecheck("$!A = 10; Frame { $!A = $!A; }").await;
-        assert!(
-            !has_undefined_token_error(&result),
-            "unexpected Undefined Token error, got: {:?}",
-            result.errors
-        );
+    async fn out_of_gamut_oklch_warns() {
+        let result = typecheck("Frame { BackgroundColor3 = oklch(0.7, 0.4, 150); }").await;
+        assert_eq!(result.errors, vec![OUT_OF_GAMUT_WARNING]);
     }
 
     #[tokio::test]
-    async fn declared_unknown_static_token_errors_in_annotation_arg() {
-        let result =
-            typecheck("$!Hello = Enum.Hello.world; Frame { Size = udim2(50%, $!Hello); }").await;
-        let errs = annotation_arg_type_errors(&result);
-        assert!(
-            !errs.is_empty(),
-            "expected a Wrong Annotation Argument Type error, got: {:?}",
-            result.errors
-        );
+    async fn in_gamut_oklch_does_not_warn() {
+        let result = typecheck("Frame { BackgroundColor3 = oklch(0.5, 0.05, 180); }").await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
     }
 
     #[tokio::test]
-    async fn same_scope_redeclaration_still_declared() {
-        let result = typecheck("$A = 10; $A = 20; Frame { Size = $A; }").await;
-        assert!(
-            !has_undefined_token_error(&result),
-            "unexpected Undefined Token error, got: {:?}",
-            result.errors
-        );
+    async fn out_of_gamut_warns_where_authored_only() {
+        let result = typecheck("$!X = oklch(0.7, 0.4, 150); Frame { BackgroundColor3 = $!X; }").await;
+        assert_eq!(result.errors, vec![OUT_OF_GAMUT_WARNING]);
     }
 
-    fn has_unknown_enum_error(result: &TypecheckResult) -> bool {
-        result.errors.iter().any(|err| err.contains("Unknown Enum"))
+    fn low_contrast_warnings(result: &TypecheckResult) -> Vec<&String> {
+        result
+            .errors
+            .iter()
+            .filter(|err| err.contains("Low Contrast"))
+            .collect()
     }
 
+    const CONTRAST_LUAURC: Option<&str> = Some(r#"{ "contrastThreshold": true }"#);
+
     #[tokio::test]
-    async fn property_shorthand_unknown_enum_name() {
-        let result = typecheck("Frame { Hello = :World; }").await;
-        assert!(
-            has_unknown_enum_error(&result),
-            "expected Unknown Enum error, got: {:?}",
-            result.errors
-        );
-        let dt = find_property(&result, "Hello");
-        assert!(matches!(dt, Datatype::None), "got {:?}", dt);
+    async fn low_contrast_text_warns() {
+        let result = typecheck_with_luaurc(
+            "TextLabel { BackgroundColor3 = #ffffff; TextColor3 = #cccccc; }",
+            CONTRAST_LUAURC,
+        )
+        .await;
+        let warnings = low_contrast_warnings(&result);
+        assert_eq!(warnings.len(), 1, "got: {:?}", result.errors);
+        assert!(warnings[0].contains("1.61:1"), "got: {:?}", warnings);
     }
 
     #[tokio::test]
-    async fn property_full_enum_unknown_name() {
-        let result = typecheck("Frame { Foo = Enum.Hello.World; }").await;
-        assert!(
-            has_unknown_enum_error(&result),
-            "expected Unknown Enum error, got: {:?}",
-            result.errors
-        );
-        let dt = find_property(&result, "Foo");
-        assert!(matches!(dt, Datatype::None), "got {:?}", dt);
+    async fn contrast_is_not_checked_by_default() {
+        let result =
+            typecheck("TextLabel { BackgroundColor3 = #ffffff; TextColor3 = #cccccc; }").await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
     }
 
     #[tokio::test]
-    async fn token_full_enum_unknown_variant() {
-        let result = typecheck("$X = Enum.Material.NotAVariant;").await;
-        let dt = find_token(&result, "X", false);
-        assert!(matches!(dt, Datatype::None), "got {:?}", dt);
-        assert!(
-            result
-                .errors
-                .iter()
-                .any(|err| err.contains("Unknown Enum Variant")),
-            "expected Unknown Enum Variant error, got: {:?}",
-            result.errors
-        );
+    async fn sufficient_contrast_does_not_warn() {
+        let result = typecheck_with_luaurc(
+            "TextLabel { BackgroundColor3 = #ffffff; TextColor3 = #222222; }",
+            CONTRAST_LUAURC,
+        )
+        .await;
+        assert!(low_contrast_warnings(&result).is_empty(), "got: {:?}", result.errors);
     }
 
     #[tokio::test]
-    async fn property_full_enum_valid() {
-        let result = typecheck("Frame { Material = Enum.Material.Plastic; }").await;
-        assert!(
-            !has_unknown_enum_error(&result),
-            "unexpected Unknown Enum error, got: {:?}",
-            result.errors
-        );
-        let dt = find_property(&result, "Material");
-        assert!(
-            matches!(
-                dt,
-                Datatype::Variant(rbx_types::Variant::EnumItem(item)) if item.ty == "Material"
-            ),
-            "got {:?}",
-            dt
-        );
+    async fn low_contrast_against_ancestor_background_warns() {
+        let result = typecheck_with_luaurc(
+            "Frame {\n    BackgroundColor3 = #000000;\n    > TextLabel { TextColor3 = #333333; }\n}",
+            CONTRAST_LUAURC,
+        )
+        .await;
+        let warnings = low_contrast_warnings(&result);
+        assert_eq!(warnings.len(), 1, "got: {:?}", result.errors);
+        assert!(warnings[0].contains("line 2"), "got: {:?}", warnings);
     }
 
-    fn has_unknown_property_error(result: &TypecheckResult) -> bool {
-        result
-            .errors
+    #[tokio::test]
+    async fn translucent_background_is_blended_over_its_ancestor() {
+        let result = typecheck_with_luaurc(
+            "Frame {\n    BackgroundColor3 = #000000;\n    > TextLabel {\n        BackgroundColor3 = #ffffff;\n        BackgroundTransparency = 0.9;\n        TextColor3 = #333333;\n    }\n}",
+            CONTRAST_LUAURC,
+        )
+        .await;
+        let warnings = low_contrast_warnings(&result);
+        assert_eq!(warnings.len(), 1, "got: {:?}", result.errors);
+        assert!(warnings[0].contains("line 4"), "got: {:?}", warnings);
+    }
+
+    #[tokio::test]
+    async fn fully_transparent_background_shows_its_ancestor() {
+        let result = typecheck_with_luaurc(
+            "Frame {\n    BackgroundColor3 = #000000;\n    > TextLabel {\n        BackgroundColor3 = #ffffff;\n        BackgroundTransparency = 1;\n        TextColor3 = #333333;\n    }\n}",
+            CONTRAST_LUAURC,
+        )
+        .await;
+        let warnings = low_contrast_warnings(&result);
+        assert_eq!(warnings.len(), 1, "got: {:?}", result.errors);
+        assert!(warnings[0].contains("line 2"), "got: {:?}", warnings);
+    }
+
+    #[tokio::test]
+    async fn translucent_background_over_unknown_colour_is_skipped() {
+        let result = typecheck_with_luaurc(
+            "TextLabel { BackgroundColor3 = #ffffff; BackgroundTransparency = 0.5; TextColor3 = #cccccc; }",
+            CONTRAST_LUAURC,
+        )
+        .await;
+        assert!(low_contrast_warnings(&result).is_empty(), "got: {:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn luaurc_contrast_threshold_is_respected() {
+        let source = "TextLabel { BackgroundColor3 = #ffffff; TextColor3 = #767676; }";
+
+        let result = typecheck_with_luaurc(source, CONTRAST_LUAURC).await;
+        assert!(low_contrast_warnings(&result).is_empty(), "got: {:?}", result.errors);
+
+        let result = typecheck_with_luaurc(source, Some(r#"{ "contrastThreshold": 7 }"#)).await;
+        assert_eq!(low_contrast_warnings(&result).len(), 1, "got: {:?}", result.errors);
+
+        let result = typecheck_with_luaurc(source, Some(r#"{ "contrastThreshold": 0 }"#)).await;
+        assert!(low_contrast_warnings(&result).is_empty(), "got: {:?}", result.errors);
+    }
+
+    fn property_conflict_warnings(result: &TypecheckResult) -> Vec<&String> {
+        result
+            .errors
+            .iter()
+            .filter(|err| err.contains("Property Conflict"))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn equally_specific_rules_setting_a_property_warn() {
+        let result =
+            typecheck("TextLabel { TextColor3 = #ffffff; }\nTextLabel { TextColor3 = #000000; }")
+                .await;
+        let warnings = property_conflict_warnings(&result);
+        assert_eq!(warnings.len(), 1, "got: {:?}", result.errors);
+        assert!(warnings[0].contains("comes later"), "got: {:?}", warnings);
+        assert_eq!(result.errors.len(), 1, "got: {:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn more_specific_earlier_rule_warns_at_the_overridden_property() {
+        let result = typecheck(
+            "#Title { TextColor3 = #ffffff; }\nTextLabel { TextColor3 = #000000; }",
+        )
+        .await;
+        let warnings = property_conflict_warnings(&result);
+        assert_eq!(warnings.len(), 1, "got: {:?}", result.errors);
+        assert!(warnings[0].contains("earlier rule on line 1"), "got: {:?}", warnings);
+    }
+
+    #[tokio::test]
+    async fn rules_in_different_conditional_branches_do_not_conflict() {
+        let result = typecheck(
+            "$!Theme = \"Dark\";\n\
+             @if $!Theme == \"Dark\" { TextLabel { TextColor3 = #ffffff; } }\n\
+             @else { TextLabel { TextColor3 = #000000; } }",
+        )
+        .await;
+        assert!(property_conflict_warnings(&result).is_empty(), "got: {:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn parent_references_conflict_as_the_joined_selector() {
+        let result = typecheck(
+            "TextButton { TextColor3 = #ffffff; &:Hover { TextColor3 = #cccccc; } }\n\
+             TextButton:Hover { TextColor3 = #000000; }",
+        )
+        .await;
+        let warnings = property_conflict_warnings(&result);
+        assert_eq!(warnings.len(), 1, "got: {:?}", result.errors);
+        assert!(warnings[0].contains("line 2"), "got: {:?}", warnings);
+    }
+
+    #[tokio::test]
+    async fn luaurc_palette_color_resolves() {
+        let result = typecheck_with_luaurc(
+            "$!Primary = brandtest:primary:500; Frame { BackgroundColor3 = lighten(brandtest:primary:500, 10%); }",
+            Some(r#"{ "palettes": { "brandtest:primary:500": [0.6, 0.03, -0.03] } }"#),
+        )
+        .await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+        assert!(matches!(find_token(&result, "Primary", true), Datatype::Oklab(_)));
+    }
+
+    #[tokio::test]
+    async fn palettes_are_scoped_to_their_luaurc() {
+        let source = "Frame { BackgroundColor3 = scopedtest:primary:500; }";
+        let luaurc = r#"{ "palettes": { "scopedtest:primary:500": [0.6, 0.03, -0.03] } }"#;
+
+        let result = typecheck_with_luaurc(source, Some(luaurc)).await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+
+        let result = typecheck(source).await;
+        assert_eq!(
+            result.errors,
+            vec!["Type Error (Unknown Palette Color): `scopedtest:primary:500` is not in any palette loaded from the luaurc."]
+        );
+    }
+
+    #[tokio::test]
+    async fn luaurc_palette_files_load_relative_to_the_luaurc() {
+        let dir = std::env::temp_dir().join(format!("rsml-palette-file-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("colors")).unwrap();
+        std::fs::write(dir.join("colors/brand.json"), r#"{ "filetest:primary:500": [0.6, 0.03, -0.03] }"#)
+            .unwrap();
+        std::fs::write(dir.join(".luaurc"), r#"{ "palettes": ["colors/brand.json"] }"#).unwrap();
+
+        let mut luaurc = Luaurc::from_path(&dir.join(".luaurc")).await;
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(luaurc.diagnostics.is_empty(), "got: {:?}", luaurc.diagnostics);
+
+        let parsed = RsmlParser::new(RsmlLexer::new("Frame { BackgroundColor3 = filetest:primary:500; }"));
+        let result = Typechecker::new(&parsed, &PathBuf::from("/test.rsml"), Some(&mut luaurc)).await;
+        assert!(result.errors.0.is_empty(), "got: {:?}", result.errors.0);
+    }
+
+    #[tokio::test]
+    async fn rejected_luaurc_palettes_are_reported() {
+        let luaurc = Luaurc::new(
+            "{\n  \"palettes\": [\n    \"missing-palette.json\",\n    { \"tw:red:500\": [0.6, 0.2, 0.1] },\n    { \"kept:primary:500\": [0.6, 0.03, -0.03] }\n  ]\n}",
+        );
+
+        let diagnostics: Vec<(&str, u32, &str)> = luaurc
+            .diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.code.as_str(), diagnostic.range.start.line, diagnostic.message.as_str()))
+            .collect();
+        assert_eq!(diagnostics.len(), 2, "got: {:?}", diagnostics);
+        assert_eq!(diagnostics[0].0, "LUAURC_ERROR(INVALID_PALETTE)");
+        assert_eq!(diagnostics[0].1, 2);
+        assert!(diagnostics[0].2.contains("Failed to read palette"), "got: {:?}", diagnostics);
+        assert_eq!(
+            diagnostics[1],
+            ("LUAURC_ERROR(INVALID_PALETTE)", 1, "Luaurc Error (Invalid Palette): The \"tw\" palette namespace is built in.")
+        );
+        assert!(luaurc.palettes.color("kept:primary:500").is_some());
+    }
+
+    #[tokio::test]
+    async fn unregistered_palette_color_errors() {
+        let result = typecheck("Frame { BackgroundColor3 = nopalette:primary:500; }").await;
+        assert!(
+            result.errors.iter().any(|err| err.contains("Unknown Palette Color")),
+            "expected Unknown Palette Color error, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn state_selectors_are_not_palette_colors() {
+        let result = typecheck("TextButton:hover { BackgroundColor3 = tw:red:500; }").await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn static_token_string_concatenation() {
+        let result = typecheck(
+            "$!Id = 123; $!Icon = \"rbxassetid://\" .. $!Id; $!Label = \"x\" .. 1 + 2;",
+        )
+        .await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+        assert_eq!(
+            find_token(&result, "Icon", true),
+            &Datatype::Variant(rbx_types::Variant::String("rbxassetid://123".into()))
+        );
+        assert_eq!(
+            find_token(&result, "Label", true),
+            &Datatype::Variant(rbx_types::Variant::String("x3".into()))
+        );
+    }
+
+    #[tokio::test]
+    async fn static_token_string_interpolation() {
+        let result = typecheck("$!Name = \"World\"; $!Greeting = `Hello {$!Name}!`;").await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+        assert_eq!(
+            find_token(&result, "Greeting", true),
+            &Datatype::Variant(rbx_types::Variant::String("Hello World!".into()))
+        );
+    }
+
+    #[tokio::test]
+    async fn dynamic_tokens_concatenate_into_strings() {
+        let result = typecheck(
+            "$IconId = 1234;\n$Icon = \"rbxassetid://\" .. $IconId;\nTextLabel { Text = \"Icon \" .. $IconId; }",
+        )
+        .await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn dynamic_tokens_interpolate_into_strings() {
+        let result = typecheck("$Name = \"Ada\";\nTextLabel { Text = `Hello {$Name}`; }").await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn strings_from_dynamic_tokens_are_type_checked() {
+        let result = typecheck("$Name = \"Ada\";\nFrame { Size = `Hello {$Name}`; }").await;
+        assert_eq!(
+            result.errors,
+            vec!["Type Error (Token Type Mismatch): Property `Size` expects type `UDim2`, but token `$Name` (defined on line 1) has type `string`."]
+        );
+    }
+
+    #[tokio::test]
+    async fn undefined_token_in_interpolated_dynamic_placeholder() {
+        let result = typecheck("TextLabel { Text = `Hello {$Nope}`; }").await;
+        assert!(has_undefined_token_error(&result), "got: {:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn undefined_token_in_interpolated_string() {
+        let result = typecheck("$!A = `x{$!nope}`;").await;
+        assert!(
+            has_undefined_token_error(&result),
+            "expected Undefined Token error, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn static_token_string_escapes() {
+        let result = typecheck(r#"$!Text = "\"quoted\"\tand\z
+            joined";"#).await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+        assert_eq!(
+            find_token(&result, "Text", true),
+            &Datatype::Variant(rbx_types::Variant::String("\"quoted\"\tandjoined".into()))
+        );
+    }
+
+    #[tokio::test]
+    async fn static_token_numeric_literals() {
+        let result = typecheck(
+            "$!Exp = 1e-3; $!Hex = 0x1F; $!Bin = 0b1010; $!Grouped = 1_000.5E2; $!Scale = 5e1%;",
+        )
+        .await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+
+        for (name, expected) in
+            [("Exp", 0.001), ("Hex", 31.0), ("Bin", 10.0), ("Grouped", 100_050.0)]
+        {
+            assert_eq!(
+                find_token(&result, name, true),
+                &Datatype::Variant(rbx_types::Variant::Float64(expected)),
+                "{}",
+                name
+            );
+        }
+        assert_eq!(
+            find_token(&result, "Scale", true),
+            &Datatype::Variant(rbx_types::Variant::UDim(rbx_types::UDim::new(0.5, 0)))
+        );
+    }
+
+    #[tokio::test]
+    async fn static_token_unit_suffixes() {
+        let result = typecheck(
+            "$!Turn = 0.5rad; $!Tilt = 45deg; $!Fast = 150ms; $!Slow = 2s; $!Text = 1.5rem;",
+        )
+        .await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+
+        let expected = [
+            ("Turn", Datatype::Measure(0.5f64.to_degrees(), Unit::Angle)),
+            ("Tilt", Datatype::Measure(45.0, Unit::Angle)),
+            ("Fast", Datatype::Measure(0.15, Unit::Duration)),
+            ("Slow", Datatype::Measure(2.0, Unit::Duration)),
+            ("Text", Datatype::Variant(rbx_types::Variant::UDim(rbx_types::UDim::new(0.0, 24)))),
+        ];
+        for (name, expected) in expected {
+            assert_eq!(find_token(&result, name, true), &expected, "{}", name);
+        }
+    }
+
+    #[tokio::test]
+    async fn rem_is_relative_to_root_size_token() {
+        let gap = |offset| Datatype::Variant(rbx_types::Variant::UDim(rbx_types::UDim::new(0.0, offset)));
+
+        let result = typecheck("$!RootSize = 10px; $!Gap = 2rem;").await;
+        assert_eq!(find_token(&result, "Gap", true), &gap(20));
+
+        let result = typecheck("$!RootSize = 10; $!Gap = 2rem;").await;
+        assert_eq!(find_token(&result, "Gap", true), &gap(20));
+
+        let result = typecheck("$!Gap = 2rem;").await;
+        assert_eq!(find_token(&result, "Gap", true), &gap(32));
+    }
+
+    #[tokio::test]
+    async fn units_match_the_property_they_are_assigned_to() {
+        let result = typecheck(
+            "$!Quarter = 0.25rad;\nFrame { Rotation = $!Quarter + 45deg; }\nUIGradient { Rotation = -90deg; }",
+        )
+        .await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn mismatched_units_are_reported() {
+        let result = typecheck(
+            "$!Fast = 150ms;\nFrame {\n    Rotation = $!Fast * 2;\n    BackgroundTransparency = 90deg;\n}",
+        )
+        .await;
+        assert_eq!(
+            result.errors,
+            vec![
+                "Type Error (Unit Mismatch): Property `Rotation` expects an angle, got a duration.",
+                "Type Error (Unit Mismatch): Property `BackgroundTransparency` doesn't take a unit, got an angle.",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn tween_accepts_duration() {
+        let result = typecheck("@tween Fade 150ms;").await;
+        assert!(
+            !result.errors.iter().any(|err| err.contains("Invalid Tween Argument")),
+            "got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn macro_args_can_have_defaults_and_names() {
+        let result = typecheck(
+            "@macro Pad (&top, &side = 8px) { ::UIPadding { PaddingTop = &top; PaddingLeft = &side; } }\n\
+             Frame { Pad!(4px); }\n\
+             TextLabel { Pad!(side = 2px, top = 6px); }",
+        )
+        .await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn unknown_named_macro_arg_is_reported() {
+        let result = typecheck(
+            "@macro Pad (&all = 8px) { ::UIPadding { PaddingTop = &all; } }\n\
+             Frame { Pad!(every = 4px); }",
+        )
+        .await;
+        assert!(
+            result.errors.iter().any(|err| err.contains("No macro argument named \"every\"")),
+            "got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_required_macro_arg_is_reported() {
+        let result = typecheck(
+            "@macro Pad (&top, &side = 8px) { ::UIPadding { PaddingTop = &top; } }\n\
+             Frame { Pad!(side = 4px); }",
+        )
+        .await;
+        assert!(
+            result.errors.iter().any(|err| err.contains("Wrong Macro Argument Count")),
+            "got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn macro_arg_defaults_cannot_use_other_args() {
+        let result =
+            typecheck("@macro Pad (&top, &side = &top) { ::UIPadding { PaddingTop = &side; } }")
+                .await;
+        assert!(
+            result.errors.iter().any(|err| err.contains("No macro argument named \"top\"")),
+            "got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn rest_macro_args_accept_any_count() {
+        let result = typecheck(
+            "@macro Gradient (&...stops) { ::UIGradient { Color = colorseq(&...stops); } }\n\
+             Frame { Gradient!(#fff, #000); }\n\
+             TextLabel { Gradient!(#fff, #888, #000); }",
+        )
+        .await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn rest_macro_arg_must_be_last() {
+        let result = typecheck("@macro Bad (&...items, &last) { }").await;
+        assert!(
+            result.errors.iter().any(|err| err.contains("must be the macro's last argument")),
+            "got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn rest_macro_args_splice_into_selectors_and_loops() {
+        let result = typecheck(
+            "@macro Hide (&...targets) { Frame, &...targets { Visible = false; } }\n\
+             @macro Any (&...tags) -> Selector { TextLabel, &...tags }\n\
+             @macro Pad (&...sizes) { @each $!size in &...sizes { .p-$!size { } } }\n\
+             Frame { Hide!(\".Primary\", \"#Close\"); Pad!(1, 2); }\n\
+             Any!(\".Title\") { }",
+        )
+        .await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+
+        let result = typecheck("@macro Hide (&...targets) { &...others { } }").await;
+        assert_eq!(
+            result.errors,
+            vec!["Type Error (Invalid Macro Argument): No rest argument named \"others\" exists."]
+        );
+    }
+
+    #[tokio::test]
+    async fn rest_splices_are_checked_against_each_call() {
+        let result = typecheck(
+            "@macro Dims (&...dims) { Size = udim2(&...dims); }\n\
+             @macro Forward (&first, &...dims) { Dims!(&...dims); }\n\
+             @macro Pair (&a, &b) { }\n\
+             @macro Spread (&...items) { Pair!(&...items); }\n\
+             Frame { Dims!(1, 0, 10, 0); Spread!(1, 2); }\n\
+             TextLabel { Forward!(0, 1, 0, 10, 0); }",
+        )
+        .await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+
+        let result = typecheck(
+            "@macro Dims (&...dims) { Size = udim2(&...dims); }\n\
+             @macro Forward (&first, &...dims) { Dims!(&...dims); }\n\
+             @macro Pair (&a, &b) { }\n\
+             @macro Spread (&...items) { Pair!(&...items); }\n\
+             Frame { Forward!(0, 1, 0, 10); Spread!(1); }",
+        )
+        .await;
+        assert_eq!(
+            result.errors,
+            vec![
+                "Type Error (Wrong Annotation Argument Count): Annotation `udim2` expects 1 or 2 or 4 arguments, but 3 were provided.",
+                "Type Error (Wrong Macro Argument Count): Macro `Pair` expects 2 arguments, but 1 was provided.",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn rest_macro_arg_only_splices_into_lists() {
+        let result = typecheck("@macro Bad (&...items) { Size = &...items; }").await;
+        assert!(
+            result.errors.iter().any(|err| err.contains("Rest arguments can only be spliced")),
+            "got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn derived_macros_are_callable_and_report_collisions() {
+        let dir = std::env::temp_dir().join(format!("rsml-derived-macros-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let base_source = "@macro Pad (&size) { ::UIPadding { PaddingTop = &size; } }\n\
+                           @macro Round () { ::UICorner { CornerRadius = udim(0, 4); } }";
+        let main_source = "@derive \"base\";\n\
+                           @macro Round () { ::UICorner { CornerRadius = udim(0, 8); } }\n\
+                           Frame { Pad!(udim(0, 4)); Round!(); }";
+        std::fs::write(dir.join("base.rsml"), base_source).unwrap();
+        std::fs::write(dir.join("main.rsml"), main_source).unwrap();
+
+        let main_path = dir.join("main.rsml").canonicalize().unwrap();
+        let base = RsmlParser::new(RsmlLexer::new(base_source));
+        let main = RsmlParser::new(RsmlLexer::new(main_source));
+
+        let derived = HashMap::from([(dir.join("base.rsml").canonicalize().unwrap(), &base)]);
+        let result = Typechecker::with_derived(&main, &main_path, None, &derived).await;
+        std::fs::remove_dir_all(&dir).ok();
+
+        let codes: Vec<&str> = result.errors.0.iter().map(|err| err.code.as_str()).collect();
+        assert_eq!(codes, vec!["TYPE_ERROR(DUPLICATE_MACRO)"], "got: {:?}", codes);
+    }
+
+    #[tokio::test]
+    async fn conditional_branches_are_both_checked() {
+        let result = typecheck(
+            "$!Platform = \"Console\";\n\
+             @if $!Platform == \"Console\" { Frame { Size = udim2(0, 10, 0, 10); } }\n\
+             @else { Frame { BackgroundTransparency = $!nope; } }",
+        )
+        .await;
+        assert!(has_undefined_token_error(&result), "got: {:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn conditions_reject_dynamic_tokens() {
+        let result = typecheck("$Theme = \"Dark\";\n@if $Theme == \"Dark\" { }").await;
+        assert!(
+            result.errors.iter().any(|err| err.contains("are not allowed in conditions")),
+            "got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn macros_are_not_allowed_in_conditional_blocks() {
+        let result = typecheck("@if true { @macro Foo () { } }").await;
+        assert!(
+            result.errors.iter().any(|err| err.contains("are not allowed in conditional blocks")),
+            "got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn each_loop_variables_are_scoped_to_the_loop() {
+        let result = typecheck(
+            "@each $!size in (1, 2) { .p-$!size { Size = udim2(0, $!size, 0, $!size); } }",
+        )
+        .await;
+        assert!(!has_undefined_token_error(&result), "got: {:?}", result.errors);
+
+        let result = typecheck(
+            "@each $!size in (1, 2) { }\n\
+             .p-$!size { }",
+        )
+        .await;
+        assert!(has_undefined_token_error(&result), "got: {:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn each_loop_bodies_are_checked_against_every_value() {
+        let result = typecheck(
+            "@each $!size in (udim2(1, 0, 1, 0), vec3(1, 2, 3), vec3(4, 5, 6)) {\n\
+                 Frame { Size = $!size; }\n\
+             }",
+        )
+        .await;
+        let mismatches: Vec<&String> =
+            result.errors.iter().filter(|err| err.contains("Vector3")).collect();
+        assert_eq!(mismatches.len(), 1, "got: {:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn each_loops_iterate_palette_families() {
+        let result = typecheck(
+            "@each $!color in tw:red { Frame { BackgroundColor3 = $!color; } }\n\
+             @each $!color in skin:rose { Frame { BackgroundColor3 = $!color; } }",
+        )
+        .await;
+        assert!(
+            !result.errors.iter().any(|err| err.starts_with("Type Error")),
+            "got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn parent_references_must_begin_every_alternative() {
+        let result = typecheck("TextButton { &:hover, &.Primary { } &:press, Frame { } }").await;
+        let messages: Vec<&String> =
+            result.errors.iter().filter(|err| err.contains("parent reference")).collect();
+        assert_eq!(messages.len(), 1, "got: {:?}", result.errors);
+        assert!(messages[0].contains("must begin every selector"), "got: {:?}", result.errors);
+
+        let result = typecheck("&:hover { }").await;
+        assert!(
+            result.errors.iter().any(|err| err.contains("can only be used in nested rules")),
+            "got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn selector_macros_take_spliced_rest_arguments() {
+        let result = typecheck(
+            "@macro Themed (&first, &second) -> Selector { .&first, #&second }\n\
+             @macro Buttons (&...tags) { Themed!(&...tags) { Visible = false; } }\n\
+             TextButton { Buttons!(\"Primary\", \"Close\"); }\n\
+             TextLabel { Buttons!(\"Primary\"); }",
+        )
+        .await;
+        assert_eq!(
+            result.errors,
+            vec!["Type Error (Wrong Macro Argument Count): Macro `Themed` expects 2 arguments, but 1 was provided."]
+        );
+    }
+
+    #[tokio::test]
+    async fn selector_macros_check_their_arguments() {
+        let result = typecheck(
+            "@macro Themed (&tag) -> Selector { .&tag, #&tag-Icon }\n\
+             Themed!(\"Primary\") { }",
+        )
+        .await;
+        assert!(
+            !result.errors.iter().any(|err| err.contains("argument")),
+            "got: {:?}",
+            result.errors
+        );
+
+        let result = typecheck("@macro Themed (&tag) -> Selector { .p-&size }").await;
+        assert!(
+            result.errors.iter().any(|err| err.contains("No macro argument named \"size\" exists.")),
+            "got: {:?}",
+            result.errors
+        );
+
+        let result = typecheck(
+            "@macro Themed (&tag) -> Selector { .&tag }\n\
+             Themed!(Enum.Font.Arial) { }",
+        )
+        .await;
+        assert!(
+            result.errors.iter().any(|err| err.contains("must be strings, numbers or static tokens")),
+            "got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn macro_arguments_are_not_allowed_in_rule_selectors() {
+        let result = typecheck(".p-&size { }").await;
+        assert!(
+            result.errors.iter().any(|err| err.contains("can only be used in selectors within macros")),
+            "got: {:?}",
+            result.errors
+        );
+    }
+
+    fn has_undefined_token_error(result: &TypecheckResult) -> bool {
+        result.errors.iter().any(|err| err.contains("Undefined Token"))
+    }
+
+    #[tokio::test]
+    async fn undefined_dynamic_token_direct() {
+        let result = typecheck("$A = $nope;").await;
+        assert!(
+            has_undefined_token_error(&result),
+            "expected Undefined Token error, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn undefined_static_token_direct() {
+        let result = typecheck("$!A = $!nope;").await;
+        assert!(
+            has_undefined_token_error(&result),
+            "expected Undefined Token error, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn undefined_token_in_property_assignment() {
+        let result = typecheck("Frame { Size = $nope; }").await;
+        assert!(
+            has_undefined_token_error(&result),
+            "expected Undefined Token error, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn undefined_token_in_annotated_tuple() {
+        let result = typecheck("Frame { Size = udim2(0%, $!Hello, 0%, 0%); }").await;
+        assert!(
+            has_undefined_token_error(&result),
+            "expected Undefined Token error, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn undefined_token_in_math() {
+        let result = typecheck("$!A = 10; $!B = $!A + $!nope;").await;
+        assert!(
+            has_undefined_token_error(&result),
+            "expected Undefined Token error, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn undefined_token_in_table() {
+        let result = typecheck("$A = { $nope };").await;
+        assert!(
+            has_undefined_token_error(&result),
+            "expected Undefined Token error, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn same_statement_self_ref_errors() {
+        let result = typecheck("$A = $A;").await;
+        assert!(
+            has_undefined_token_error(&result),
+            "expected Undefined Token error, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn dynamic_and_static_distinct_keys() {
+        let result = typecheck("$A = 10; $B = $!A;").await;
+        assert!(
+            has_undefined_token_error(&result),
+            "expected Undefined Token error, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn defined_static_token_no_error() {
+        let result = typecheck("$!A = 10; $!B = $!A;").await;
+        assert!(
+            !has_undefined_token_error(&result),
+            "unexpected Undefined Token error, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn defined_dynamic_token_no_error() {
+        let result = typecheck("$A = 10; Frame { Size = $A; }").await;
+        assert!(
+            !has_undefined_token_error(&result),
+            "unexpected Undefined Token error, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn nested_rule_inherits_outer_token() {
+        let result = typecheck("$!A = 10; Frame { $!B = $!A; }").await;
+        assert!(
+            !has_undefined_token_error(&result),
+            "unexpected Undefined Token error, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn inner_shadow_resolves_to_outer_in_same_rhs() {
+        let result = typecheck("$!A = 10; Frame { $!A = $!A; }").await;
+        assert!(
+            !has_undefined_token_error(&result),
+            "unexpected Undefined Token error, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn declared_unknown_static_token_errors_in_annotation_arg() {
+        let result =
+            typecheck("$!Hello = Enum.Hello.world; Frame { Size = udim2(50%, $!Hello); }").await;
+        let errs = annotation_arg_type_errors(&result);
+        assert!(
+            !errs.is_empty(),
+            "expected a Wrong Annotation Argument Type error, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn same_scope_redeclaration_still_declared() {
+        let result = typecheck("$A = 10; $A = 20; Frame { Size = $A; }").await;
+        assert!(
+            !has_undefined_token_error(&result),
+            "unexpected Undefined Token error, got: {:?}",
+            result.errors
+        );
+    }
+
+    fn has_unknown_enum_error(result: &TypecheckResult) -> bool {
+        result.errors.iter().any(|err| err.contains("Unknown Enum"))
+    }
+
+    #[tokio::test]
+    async fn property_shorthand_unknown_enum_name() {
+        let result = typecheck("Frame { Hello = :World; }").await;
+        assert!(
+            has_unknown_enum_error(&result),
+            "expected Unknown Enum error, got: {:?}",
+            result.errors
+        );
+        let dt = find_property(&result, "Hello");
+        assert!(matches!(dt, Datatype::None), "got {:?}", dt);
+    }
+
+    #[tokio::test]
+    async fn property_full_enum_unknown_name() {
+        let result = typecheck("Frame { Foo = Enum.Hello.World; }").await;
+        assert!(
+            has_unknown_enum_error(&result),
+            "expected Unknown Enum error, got: {:?}",
+            result.errors
+        );
+        let dt = find_property(&result, "Foo");
+        assert!(matches!(dt, Datatype::None), "got {:?}", dt);
+    }
+
+    #[tokio::test]
+    async fn token_full_enum_unknown_variant() {
+        let result = typecheck("$X = Enum.Material.NotAVariant;").await;
+        let dt = find_token(&result, "X", false);
+        assert!(matches!(dt, Datatype::None), "got {:?}", dt);
+        assert!(
+            result
+                .errors
+                .iter()
+                .any(|err| err.contains("Unknown Enum Variant")),
+            "expected Unknown Enum Variant error, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn property_full_enum_valid() {
+        let result = typecheck("Frame { Material = Enum.Material.Plastic; }").await;
+        assert!(
+            !has_unknown_enum_error(&result),
+            "unexpected Unknown Enum error, got: {:?}",
+            result.errors
+        );
+        let dt = find_property(&result, "Material");
+        assert!(
+            matches!(
+                dt,
+                Datatype::Variant(rbx_types::Variant::EnumItem(item)) if item.ty == "Material"
+            ),
+            "got {:?}",
+            dt
+        );
+    }
+
+    fn has_unknown_property_error(result: &TypecheckResult) -> bool {
+        result
+            .errors
             .iter()
             .any(|err| err.contains("Unknown Property"))
     }
@@ -2937,6 +4412,231 @@ mod tests {
         );
     }
 
+    fn token_type_mismatch_errors(result: &TypecheckResult) -> Vec<&String> {
+        result
+            .errors
+            .iter()
+            .filter(|err| err.contains("Token Type Mismatch"))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn dynamic_token_mismatched_with_property() {
+        let result = typecheck("$Spacing = 8px;\nFrame { BackgroundColor3 = $Spacing; }").await;
+        let errors = token_type_mismatch_errors(&result);
+        assert_eq!(errors.len(), 1, "got: {:?}", result.errors);
+        assert!(errors[0].contains("`$Spacing`"), "got: {:?}", errors);
+        assert!(errors[0].contains("defined on line 1"), "got: {:?}", errors);
+    }
+
+    #[tokio::test]
+    async fn static_token_mismatched_with_property() {
+        let result = typecheck("$!Spacing = 8px; Frame { BackgroundColor3 = $!Spacing; }").await;
+        let errors = token_type_mismatch_errors(&result);
+        assert_eq!(errors.len(), 1, "got: {:?}", result.errors);
+        assert!(
+            !has_property_type_mismatch_error(&result),
+            "expected the token specific error only, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn token_checked_against_every_usage() {
+        let result = typecheck(
+            "$Gap = 8px; Frame { BackgroundColor3 = $Gap; } TextLabel { TextColor3 = $Gap; }",
+        )
+        .await;
+        assert_eq!(token_type_mismatch_errors(&result).len(), 2, "got: {:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn token_matching_property_type_no_error() {
+        let result = typecheck("$Bg = #ffffff; Frame { $Pad = 4px; BackgroundColor3 = $Bg; }").await;
+        assert!(
+            token_type_mismatch_errors(&result).is_empty(),
+            "unexpected Token Type Mismatch error, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn shadowed_token_checked_against_innermost_declaration() {
+        let result =
+            typecheck("$Bg = 8px; Frame { $Bg = #ffffff; BackgroundColor3 = $Bg; }").await;
+        assert!(
+            token_type_mismatch_errors(&result).is_empty(),
+            "unexpected Token Type Mismatch error, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn token_checked_through_math_and_annotations() {
+        let result = typecheck(
+            "$Gap = 8;\n\
+             Frame { BackgroundColor3 = $Gap * 2; }\n\
+             TextLabel { TextColor3 = udim2($Gap, 0, 0, 0); }",
+        )
+        .await;
+        let errors = token_type_mismatch_errors(&result);
+        assert_eq!(errors.len(), 2, "got: {:?}", result.errors);
+        assert!(errors.iter().all(|err| err.contains("`$Gap`")), "got: {:?}", errors);
+    }
+
+    #[tokio::test]
+    async fn derived_tokens_are_declared_and_checked() {
+        let dir = std::env::temp_dir().join(format!("rsml-derived-tokens-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let base_source = "$Accent = 8px;\n$!Radius = 4;";
+        let main_source = "--!strict\n\
+                           @derive \"base\";\n\
+                           Frame { BackgroundColor3 = $Accent; }\n\
+                           TextLabel { TextSize = $!Radius; }";
+        std::fs::write(dir.join("base.rsml"), base_source).unwrap();
+        std::fs::write(dir.join("main.rsml"), main_source).unwrap();
+
+        let main_path = dir.join("main.rsml").canonicalize().unwrap();
+        let base = RsmlParser::new(RsmlLexer::new(base_source));
+        let main = RsmlParser::new(RsmlLexer::new(main_source));
+
+        let derived = HashMap::from([(dir.join("base.rsml").canonicalize().unwrap(), &base)]);
+        let result = Typechecker::with_derived(&main, &main_path, None, &derived).await;
+        std::fs::remove_dir_all(&dir).ok();
+
+        let codes: Vec<&str> = result.errors.0.iter().map(|err| err.code.as_str()).collect();
+        assert_eq!(codes, vec!["TYPE_ERROR(TOKEN_TYPE_MISMATCH)"], "got: {:?}", codes);
+        let message = &result.errors.0[0].message;
+        assert!(message.contains("base.rsml"), "got: {:?}", message);
+    }
+
+    #[tokio::test]
+    async fn strict_rejects_tuple_property() {
+        let result = typecheck("--!strict\nFrame { Size = (1, 2); }").await;
+        assert!(
+            result.errors.iter().any(|err| err.contains("Implicit Tuple Coercion")),
+            "expected Implicit Tuple Coercion error in strict mode, got: {:?}",
+            result.errors
+        );
+        let dt = find_property(&result, "Size");
+        assert!(matches!(dt, Datatype::None), "got {:?}", dt);
+    }
+
+    #[tokio::test]
+    async fn nonstrict_collapses_tuple_property() {
+        let result = typecheck("Frame { Transparency = (1, 2); }").await;
+        assert!(
+            !result.errors.iter().any(|err| err.contains("Implicit Tuple Coercion")),
+            "unexpected Implicit Tuple Coercion error in nonstrict mode, got: {:?}",
+            result.errors
+        );
+        let dt = find_property(&result, "Transparency");
+        assert!(
+            matches!(dt, Datatype::Variant(rbx_types::Variant::Float64(n)) if *n == 1.0),
+            "got {:?}",
+            dt
+        );
+    }
+
+    #[tokio::test]
+    async fn strict_rejects_tuple_dynamic_token() {
+        let result = typecheck("--!strict\n$Pad = (1, 2);").await;
+        assert!(
+            result.errors.iter().any(|err| err.contains("Implicit Tuple Coercion")),
+            "expected Implicit Tuple Coercion error in strict mode, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn strict_static_token_keeps_tuple() {
+        let result = typecheck("--!strict\n$!Pad = (1, 2);").await;
+        assert!(result.errors.is_empty(), "got: {:?}", result.errors);
+        let dt = find_token(&result, "Pad", true);
+        assert!(matches!(dt, Datatype::TupleData(_)), "got {:?}", dt);
+    }
+
+    #[tokio::test]
+    async fn strict_rejects_nested_enum_shorthand() {
+        let result = typecheck("--!strict\n$!Weights = (:Bold, :Light);").await;
+        assert!(
+            result.errors.iter().any(|err| err.contains("Unpinned Enum Shorthand")),
+            "expected Unpinned Enum Shorthand error in strict mode, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn strict_rejects_unpinned_token_shorthand() {
+        let result = typecheck("--!strict\n$Weight = (:Bold);").await;
+        assert!(
+            result.errors.iter().any(|err| err.contains("Unpinned Enum Shorthand")),
+            "expected Unpinned Enum Shorthand error in strict mode, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn strict_accepts_pinned_token_shorthand() {
+        let result = typecheck("--!strict\n$FontWeight = :Bold;").await;
+        assert!(
+            !result.errors.iter().any(|err| err.contains("Unpinned Enum Shorthand")),
+            "unexpected Unpinned Enum Shorthand error, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn strict_rejects_unresolvable_property() {
+        let result = typecheck("--!strict\n$B = 1; $!A = $B; Frame { Size = $!A; }").await;
+        assert!(
+            result.errors.iter().any(|err| err.contains("Unresolved Property Type")),
+            "expected Unresolved Property Type error in strict mode, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn nonstrict_skips_unresolvable_property() {
+        let result = typecheck("$B = 1; $!A = $B; Frame { Size = $!A; }").await;
+        assert!(
+            !result.errors.iter().any(|err| err.contains("Unresolved Property Type")),
+            "unexpected Unresolved Property Type error in nonstrict mode, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn strict_allows_dynamic_token_property() {
+        let result = typecheck("--!strict\n$Gap = 5; Frame { Size = $Gap; }").await;
+        assert!(
+            !result.errors.iter().any(|err| err.contains("Unresolved Property Type")),
+            "unexpected Unresolved Property Type error for a dynamic token, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn strict_requires_declared_token_in_tween() {
+        let result = typecheck("--!strict\nFrame { @tween Size $!Nope; }").await;
+        assert!(
+            has_undefined_token_error(&result),
+            "expected Undefined Token error in strict mode, got: {:?}",
+            result.errors
+        );
+    }
+
+    #[tokio::test]
+    async fn strict_requires_declared_token_in_macro_call() {
+        let result = typecheck("--!strict\nFrame { Padding!($!Nope); }").await;
+        assert!(
+            has_undefined_token_error(&result),
+            "expected Undefined Token error in strict mode, got: {:?}",
+            result.errors
+        );
+    }
+
     #[tokio::test]
     async fn tween_negative_number_accepted() {
         let result = typecheck("@tween Slide (-0.5, :Quad, :Out);").await;
@@ -2,20 +2,30 @@ use std::{mem::discriminant, slice::Iter};
 
 use indexmap::IndexSet;
 
+use rbx_types::Variant;
+
 use crate::{
-    lexer::{SpannedToken, Token, TokenKind},
+    datatype::{Reference, evaluate_construct, tag_references, variant_type_name},
+    lexer::{AttributeSelector, RsmlLexer, SpannedToken, Token, TokenKind},
     list::TokenKindList,
-    parser::{AstErrors, Construct, Delimited, Node, SelectorNode},
+    parser::{AstErrors, Construct, Delimited, Node, SelectorNode, SpanEnd},
     range_from_span::RangeFromSpan,
     token_kind_list,
 };
 
 use phf_macros::phf_set;
 use ropey::Rope;
-use crate::types::Range;
+use crate::types::{LanguageMode, Range};
 
-use crate::macro_registry::{MacroKey, MacroRegistry, MacroReturnContext};
-use crate::typechecker::{DefinitionKind, ReportTypeError, ResolvedTypes, Typechecker, type_error::*};
+use crate::macro_registry::{
+    MacroRegistry, MacroReturnContext, accepted_arg_counts, collect_macro_call_args, find_macro_in,
+    rest_arg_splice,
+};
+use crate::typechecker::conditional::flatten_conditionals;
+use crate::typechecker::{
+    DefinitionKind, ReportTypeError, ResolvedTypeKey, ResolvedTypes, Typechecker,
+    type_error::*,
+};
 
 impl<'a> Typechecker<'a> {
     pub(super) fn typecheck_rule(
@@ -51,13 +61,47 @@ impl<'a> Typechecker<'a> {
             return;
         };
 
+        self.check_duplicate_assignments(content, ast_errors);
+
+        let enclosing_rule = self.enter_checked_rule(selectors, body_start);
         self.static_scopes.push(std::collections::HashMap::new());
-        self.declared_tokens.push(std::collections::HashSet::new());
+        self.declared_tokens.push(std::collections::HashMap::new());
+
+        self.typecheck_rule_content(content, &current_classes, ast_errors, definitions, resolved_types);
+
+        self.static_scopes.pop();
+        self.declared_tokens.pop();
+        self.current_rule = enclosing_rule;
+    }
 
-        for construct in content {
+    /// Checks the constructs of a rule body, or of a loop within one, against
+    /// the classes the enclosing selectors narrowed down to.
+    pub(super) fn typecheck_rule_content(
+        &mut self,
+        content: &[Construct<'a>],
+        current_classes: &Vec<String>,
+        ast_errors: &mut AstErrors,
+        definitions: &mut crate::typechecker::Definitions,
+        resolved_types: &mut ResolvedTypes,
+    ) {
+        for construct in flatten_conditionals(content) {
             match construct {
                 Construct::Rule { selectors, body } => {
-                    self.typecheck_rule((selectors, body), &current_classes, ast_errors, definitions, resolved_types)
+                    self.typecheck_rule((selectors, body), current_classes, ast_errors, definitions, resolved_types)
+                }
+
+                Construct::If { condition, body, else_body, .. } => {
+                    self.typecheck_if(condition, [body, else_body], ast_errors);
+                }
+
+                Construct::Each { variable, iterable, body, .. } => {
+                    self.typecheck_each(
+                        (variable, iterable, body),
+                        current_classes,
+                        ast_errors,
+                        definitions,
+                        resolved_types,
+                    );
                 }
 
                 Construct::Assignment {
@@ -72,7 +116,8 @@ impl<'a> Typechecker<'a> {
                         if let Construct::MacroCall { name, body, .. } = right.as_ref() {
                             self.validate_macro_call(name, body, MacroReturnContext::Datatype, ast_errors);
                         }
-                        self.resolve_token_assignment(left, right, &current_classes, ast_errors, definitions, resolved_types);
+                        self.resolve_token_assignment(left, right, current_classes, ast_errors, definitions, resolved_types);
+                        self.record_checked_property(left, right);
                     }
                 }
 
@@ -80,9 +125,22 @@ impl<'a> Typechecker<'a> {
                     body: Some(body),
                     ..
                 } => {
+                    if self.language_mode == LanguageMode::Strict {
+                        self.validate_token_refs(body, ast_errors);
+                    }
                     self.typecheck_tween(body, ast_errors);
                 }
 
+                Construct::Priority {
+                    body: Some(body),
+                    ..
+                } => {
+                    if self.language_mode == LanguageMode::Strict {
+                        self.validate_token_refs(body, ast_errors);
+                    }
+                    self.record_checked_priority(body);
+                }
+
                 Construct::Derive { .. } => {
                     ast_errors.report(
                         TypeError::NotAllowedInContext { name: construct.name_plural(), context: "non-global scopes" },
@@ -91,6 +149,9 @@ impl<'a> Typechecker<'a> {
                 }
 
                 Construct::MacroCall { name, body, .. } => {
+                    if self.language_mode == LanguageMode::Strict {
+                        self.validate_token_refs(construct, ast_errors);
+                    }
                     self.validate_macro_call(name, body, MacroReturnContext::Construct, ast_errors);
                 }
 
@@ -105,9 +166,6 @@ impl<'a> Typechecker<'a> {
                 _ => (),
             }
         }
-
-        self.static_scopes.pop();
-        self.declared_tokens.pop();
     }
 
     fn typecheck_selectors(
@@ -117,13 +175,47 @@ impl<'a> Typechecker<'a> {
         ast_errors: &mut AstErrors,
         definitions: &mut crate::typechecker::Definitions,
     ) -> Vec<String> {
+        self.check_parent_references(selectors, ast_errors);
+
+        for selector in selectors {
+            if let SelectorNode::Token(node) = selector
+                && let Token::AttributeSelector(attribute) = node.token.value()
+            {
+                self.typecheck_attribute_selector(attribute, node.token.span(), ast_errors);
+            }
+
+            if let SelectorNode::Token(node) = selector
+                && let Token::TagSelectorOrEnumPart(Some(tag)) | Token::NameSelector(tag) =
+                    node.token.value()
+            {
+                // Skips the `.` or `#` before the tag.
+                let tag_start = node.token.start() + 1;
+
+                for (reference, (start, end)) in tag_references(tag) {
+                    let span = (tag_start + start, tag_start + end);
+                    match reference {
+                        Reference::StaticToken(name) => {
+                            self.validate_token_ref(name, true, span, ast_errors);
+                        }
+                        Reference::MacroArg(_) => ast_errors.report(
+                            TypeError::InvalidMacroArg {
+                                msg: "Macro arguments can only be used in selectors within macros.",
+                            },
+                            self.parsed.range_from_span(span),
+                        ),
+                        Reference::Token(_) => (),
+                    }
+                }
+            }
+        }
+
         TypecheckSelectors::new(
             selectors,
             parent_classes,
             &self.parsed.rope,
             ast_errors,
             definitions,
-            &self.macro_registry,
+            self.macro_registries(),
         )
         .classes
         .into_iter()
@@ -131,6 +223,121 @@ impl<'a> Typechecker<'a> {
     }
 }
 
+impl<'a> Typechecker<'a> {
+    /// A parent reference (`&`) joins the selector it begins onto the parent
+    /// rule's, which only works when it begins every alternative in the list.
+    fn check_parent_references(&self, selectors: &[SelectorNode<'a>], ast_errors: &mut AstErrors) {
+        let alternatives: Vec<&[SelectorNode<'a>]> = selectors
+            .split(|selector| {
+                matches!(selector, SelectorNode::Token(node) if matches!(node.token.value(), Token::Comma))
+            })
+            .collect();
+        let mut joined = 0;
+
+        for alternative in &alternatives {
+            for (idx, selector) in alternative.iter().enumerate() {
+                let SelectorNode::Token(node) = selector else { continue };
+
+                match node.token.value() {
+                    Token::MacroArgIdentifier(None) if idx == 0 => joined += 1,
+
+                    Token::MacroArgIdentifier(None) => ast_errors.report(
+                        TypeError::InvalidSelector {
+                            msg: Some("A parent reference (&) can only begin a selector."),
+                        },
+                        self.parsed.range_from_span(node.token.span()),
+                    ),
+
+                    Token::MacroArgIdentifier(Some(_)) | Token::MacroRestArgIdentifier(_) => ast_errors.report(
+                        TypeError::InvalidMacroArg {
+                            msg: "Macro arguments can only be used in selectors within macros.",
+                        },
+                        self.parsed.range_from_span(node.token.span()),
+                    ),
+
+                    _ => (),
+                }
+            }
+        }
+
+        if joined > 0 && joined < alternatives.len()
+            && let (Some(first), Some(last)) = (selectors.first(), selectors.last())
+        {
+            ast_errors.report(
+                TypeError::InvalidSelector {
+                    msg: Some("A parent reference (&) must begin every selector in the list, or none of them."),
+                },
+                self.parsed.range_from_span((first.start(), last.end())),
+            );
+        }
+    }
+
+    /// Checks that an attribute selector's value is a literal, and that it
+    /// agrees with the type of any `$token` declaring an attribute of the
+    /// same name.
+    fn typecheck_attribute_selector(
+        &self,
+        attribute: &AttributeSelector<'a>,
+        span: (usize, usize),
+        ast_errors: &mut AstErrors,
+    ) {
+        let Some(value) = attribute.value else { return };
+        let range = self.parsed.range_from_span(span);
+
+        let Some(value) = self.evaluate_attribute_value(value) else {
+            ast_errors.report(
+                TypeError::InvalidSelector {
+                    msg: Some(&format!(
+                        "The value of attribute \"{}\" must be a string, number, boolean, UDim or color.",
+                        attribute.name
+                    )),
+                },
+                range,
+            );
+            return;
+        };
+
+        let key = ResolvedTypeKey::Token { name: attribute.name.to_string(), is_static: false };
+        let Some(declaration) = self.token_declaration(&key) else { return };
+        let Some(declared) = declaration.datatype.clone().coerce_to_variant(Some(attribute.name))
+        else {
+            return;
+        };
+
+        let expected = variant_type_name(declared.ty());
+        let got = variant_type_name(value.ty());
+
+        if expected != got {
+            ast_errors.report(
+                TypeError::AttributeTypeMismatch { name: attribute.name, expected, got },
+                range,
+            );
+        }
+    }
+
+    /// Attribute values are kept as raw source by the lexer. They're re-lexed
+    /// here and must be a single literal, optionally negated.
+    fn evaluate_attribute_value(&self, value: &'a str) -> Option<Variant> {
+        let mut nodes = RsmlLexer::new(value).map(|token| Node { token, leading_trivia: None });
+
+        let first = nodes.next()?;
+        let construct = match first.token.value() {
+            Token::OpSub => Construct::UnaryMinus {
+                operator: first,
+                operand: Box::new(Construct::Node { node: nodes.next()? }),
+            },
+            _ => Construct::Node { node: first },
+        };
+
+        if nodes.next().is_some() {
+            return None;
+        }
+
+        let lookup = self.lookup();
+        evaluate_construct(&construct, None, &lookup)?.coerce_to_variant(None)
+    }
+}
+
 static ALLOWED_PSEUDO_SELECTORS: phf::Set<&str> = phf_set! {
     "UICorner",
     "UIGradient",
@@ -162,10 +369,14 @@ struct TypecheckSelectors<'a> {
     classes: IndexSet<String>,
 
     part: Option<&'a Node<'a>>,
+    /// Set while `part` is the name of a `:not(...)` or `:is(...)` selector.
+    functional: bool,
+    /// The classes an `:is(...)` selector narrows down to.
+    narrowing: Option<IndexSet<String>>,
 
     rope: &'a Rope,
     ast_errors: &'a mut AstErrors,
-    macro_registry: &'a MacroRegistry<'a>,
+    macro_registries: [&'a MacroRegistry<'a>; 3],
 }
 
 impl<'a> TypecheckSelectors<'a> {
@@ -175,19 +386,26 @@ impl<'a> TypecheckSelectors<'a> {
         rope: &'a Rope,
         ast_errors: &'a mut AstErrors,
         definitions: &mut crate::typechecker::Definitions,
-        macro_registry: &'a MacroRegistry<'a>,
+        macro_registries: [&'a MacroRegistry<'a>; 3],
     ) -> Self {
         let mut typecheck_selectors = Self {
             iter: selectors.iter(),
             parent_classes,
             classes: IndexSet::new(),
             part: None,
+            functional: false,
+            narrowing: None,
             rope,
             ast_errors,
-            macro_registry,
+            macro_registries,
         };
 
-        typecheck_selectors.begin(definitions);
+        if let Some((span_start, span_end)) = typecheck_selectors.begin() {
+            definitions.insert(
+                span_start..=span_end,
+                DefinitionKind::selector(typecheck_selectors.classes.iter().cloned().collect()),
+            );
+        }
 
         typecheck_selectors
     }
@@ -198,16 +416,48 @@ impl<'a> TypecheckSelectors<'a> {
             match next_item {
                 SelectorNode::Token(node) => {
                     self.part = Some(node);
+                    self.functional = false;
+                    self.narrowing = None;
                     return Some(node);
                 }
                 SelectorNode::MacroCall { name, body } => {
                     self.validate_selector_macro_call(name, body);
                     continue;
                 }
+                SelectorNode::Functional { name, selectors, .. } => {
+                    let classes = self.typecheck_functional(selectors);
+
+                    // Only `:is(...)` narrows down what the selector matches.
+                    let is_grouping =
+                        matches!(name.token.value(), Token::StateSelectorOrEnumPart(Some("is")));
+                    self.narrowing = is_grouping.then_some(classes);
+
+                    self.part = Some(name);
+                    self.functional = true;
+                    return Some(name);
+                }
             }
         }
     }
 
+    /// Checks the selectors wrapped by `:not(...)` or `:is(...)` on their own,
+    /// returning the classes they match.
+    fn typecheck_functional(&mut self, selectors: &'a Vec<SelectorNode<'a>>) -> IndexSet<String> {
+        static NO_CLASSES: Vec<String> = Vec::new();
+
+        let outer_iter = std::mem::replace(&mut self.iter, selectors.iter());
+        let outer_classes = std::mem::take(&mut self.classes);
+        let outer_parent_classes = std::mem::replace(&mut self.parent_classes, &NO_CLASSES);
+        let outer_part = self.part;
+
+        self.begin();
+
+        self.iter = outer_iter;
+        self.parent_classes = outer_parent_classes;
+        self.part = outer_part;
+        std::mem::replace(&mut self.classes, outer_classes)
+    }
+
     fn begin_iteration(&mut self, part: &'a Node<'a>) {
         if self.parent_classes.is_empty() {
             self.from_new(part);
@@ -216,8 +466,9 @@ impl<'a> TypecheckSelectors<'a> {
         }
     }
 
-    fn begin(&mut self, definitions: &mut crate::typechecker::Definitions) {
-        let Some(part) = self.next() else { return };
+    /// Checks every alternative, returning the span they cover.
+    fn begin(&mut self) -> Option<(usize, usize)> {
+        let part = self.next()?;
         let span_start = part.token.start();
 
         self.begin_iteration(part);
@@ -227,15 +478,15 @@ impl<'a> TypecheckSelectors<'a> {
             .map(|x| x.token.end())
             .unwrap_or_else(|| part.token.end());
 
-        definitions.insert(
-            span_start..=span_end,
-            DefinitionKind::selector(self.classes.iter().cloned().collect()),
-        );
+        Some((span_start, span_end))
     }
 
     fn from_new(&mut self, part: &'a Node<'a>) {
         match part.token.value() {
-            Token::TagSelectorOrEnumPart(_) | Token::NameSelector(_) | Token::QuerySelector(_) => {
+            Token::TagSelectorOrEnumPart(_)
+            | Token::NameSelector(_)
+            | Token::QuerySelector(_)
+            | Token::AttributeSelector(_) => {
                 self.classes.insert("Instance".to_string());
                 self.consume_past_comma();
             }
@@ -246,7 +497,7 @@ impl<'a> TypecheckSelectors<'a> {
                 match self.consume_with_error(
                     TokenKind::Identifier,
                     token_kind_list![PseudoSelector, StateSelectorOrEnumPart],
-                    Some(token_kind_list![TagSelectorOrEnumPart, NameSelector]),
+                    Some(token_kind_list![TagSelectorOrEnumPart, NameSelector, AttributeSelector]),
                 ) {
                     ConsumeResult::Some(part) => match part.token.value() {
                         Token::PseudoSelector(class) => {
@@ -287,11 +538,27 @@ impl<'a> TypecheckSelectors<'a> {
             }
 
             Token::StateSelectorOrEnumPart(Some(state)) => {
-                self.classes.insert("Instance".to_string());
+                match self.narrowing.take() {
+                    Some(classes) => self.classes.extend(classes),
+                    None => {
+                        self.classes.insert("Instance".to_string());
+                    }
+                }
                 self.validate_state(state, &part.token);
                 self.consume_past_comma();
             }
 
+            Token::MacroArgIdentifier(None) => {
+                self.ast_errors.report(
+                    TypeError::InvalidSelector {
+                        msg: Some("Parent references (&) can only be used in nested rules."),
+                    },
+                    self.range_from_span(part.token.span()),
+                );
+                self.classes.insert("Instance".to_string());
+                self.consume_past_comma();
+            }
+
             _ => (),
         }
     }
@@ -314,7 +581,7 @@ impl<'a> TypecheckSelectors<'a> {
                 match self.consume_with_error(
                     TokenKind::Identifier,
                     token_kind_list![PseudoSelector, StateSelectorOrEnumPart],
-                    Some(token_kind_list![TagSelectorOrEnumPart, NameSelector]),
+                    Some(token_kind_list![TagSelectorOrEnumPart, NameSelector, AttributeSelector]),
                 ) {
                     ConsumeResult::Some(part) => match part.token.value() {
                         Token::PseudoSelector(class) => {
@@ -354,7 +621,10 @@ impl<'a> TypecheckSelectors<'a> {
                 self.consume_past_comma();
             }
 
-            Token::TagSelectorOrEnumPart(_) | Token::NameSelector(_) | Token::QuerySelector(_) => {
+            Token::TagSelectorOrEnumPart(_)
+            | Token::NameSelector(_)
+            | Token::QuerySelector(_)
+            | Token::AttributeSelector(_) => {
                 self.classes.insert("Instance".to_string());
                 self.consume_past_comma();
             }
@@ -364,10 +634,53 @@ impl<'a> TypecheckSelectors<'a> {
                 self.from_parent(next, true);
             }
 
+            Token::MacroArgIdentifier(None) => self.from_parent_reference(),
+
             _ => (),
         }
     }
 
+    /// `&` continues the parent's selector, so the compound it begins narrows
+    /// down the parent's classes rather than matching its children.
+    fn from_parent_reference(&mut self) {
+        while let Some(part) = self.next() {
+            match part.token.value() {
+                Token::Comma => {
+                    self.classes.extend(self.parent_classes.iter().cloned());
+                    let Some(next) = self.next() else { return };
+                    self.begin_iteration(next);
+                    return;
+                }
+
+                Token::ChildrenSelector | Token::DescendantsSelector => {
+                    let Some(next) = self.next() else { return };
+                    self.from_parent(next, true);
+                    return;
+                }
+
+                Token::StateSelectorOrEnumPart(Some(state)) => {
+                    self.validate_state(state, &part.token);
+                }
+
+                Token::Identifier(_) | Token::PseudoSelector(_) => {
+                    let name = self.selector_name(part.token.value().kind());
+                    self.ast_errors.report(
+                        TypeError::InvalidSelector {
+                            msg: Some(&format!(
+                                "{name} Selectors can't be joined onto a parent reference (&)."
+                            )),
+                        },
+                        self.range_from_span(part.token.span()),
+                    );
+                }
+
+                _ => (),
+            }
+        }
+
+        self.classes.extend(self.parent_classes.iter().cloned());
+    }
+
     fn consume_past_comma(&mut self) {
         let Some(part) = self.next() else { return };
         if matches!(part.token.value(), Token::Comma) {
@@ -376,11 +689,11 @@ impl<'a> TypecheckSelectors<'a> {
         }
     }
 
-    fn consume_with_error<const N: usize>(
+    fn consume_with_error<const N: usize, const M: usize>(
         &mut self,
         origin_kind: TokenKind,
         allow_list: &TokenKindList<N>,
-        error_exclude_list: Option<&TokenKindList<N>>,
+        error_exclude_list: Option<&TokenKindList<M>>,
     ) -> ConsumeResult<'a> {
         self.consume(allow_list, |checker, part| {
             checker.error(
@@ -416,9 +729,9 @@ impl<'a> TypecheckSelectors<'a> {
         ConsumeResult::None
     }
 
-    fn error<const N: usize>(
+    fn error<const M: usize>(
         &mut self,
-        error_exclude_list: Option<&TokenKindList<N>>,
+        error_exclude_list: Option<&TokenKindList<M>>,
         origin_kind: TokenKind,
         subject_kind: TokenKind,
         subject_span: (usize, usize),
@@ -453,7 +766,9 @@ impl<'a> TypecheckSelectors<'a> {
             TokenKind::TagSelectorOrEnumPart => "Tag",
             TokenKind::StateSelectorOrEnumPart => "State",
             TokenKind::NameSelector => "Name",
+            TokenKind::PseudoSelector => "Pseudo",
             TokenKind::QuerySelector => "Query",
+            TokenKind::AttributeSelector => "Attribute",
             TokenKind::ChildrenSelector => "Children",
             TokenKind::DescendantsSelector => "Descendants",
             _ => "Unknown",
@@ -502,7 +817,10 @@ impl<'a> TypecheckSelectors<'a> {
     }
 
     fn validate_state(&mut self, name: &'a str, token: &SpannedToken) -> bool {
-        if ALLOWED_STATE_SELECTORS.contains(name) {
+        // The selectors inside `:not(...)` and `:is(...)` are checked on their
+        // own, so only the name of the functional selector is let through.
+        let is_functional = self.functional && matches!(name, "not" | "is");
+        if is_functional || ALLOWED_STATE_SELECTORS.contains(name) {
             return true;
         }
 
@@ -521,24 +839,11 @@ impl<'a> TypecheckSelectors<'a> {
         name: &Node<'a>,
         body: &Option<Delimited<'a>>,
     ) {
-        use crate::macro_registry::count_macro_call_args;
-
         let Token::MacroCallIdentifier(Some(macro_name)) = name.token.value() else {
             return;
         };
 
-        let local_arities = self
-            .macro_registry
-            .keys()
-            .filter(|k| k.name == *macro_name)
-            .map(|k| k.arity);
-        let builtin_arities = crate::builtins::BUILTINS
-            .registry
-            .keys()
-            .filter(|k| k.name == *macro_name)
-            .map(|k| k.arity);
-
-        let mut expected_counts: Vec<usize> = local_arities.chain(builtin_arities).collect();
+        let expected_counts = accepted_arg_counts(&self.macro_registries, macro_name);
 
         if expected_counts.is_empty() {
             self.ast_errors.report(
@@ -548,32 +853,23 @@ impl<'a> TypecheckSelectors<'a> {
             return;
         }
 
-        let call_arg_count = count_macro_call_args(body);
-        let key = MacroKey {
-            name: *macro_name,
-            arity: call_arg_count,
-        };
+        let call_args = collect_macro_call_args(body);
 
-        let matching_context = self
-            .macro_registry
-            .get(&key)
-            .map(|def| def.return_context)
-            .or_else(|| {
-                crate::builtins::BUILTINS
-                    .registry
-                    .get(&key)
-                    .map(|def| def.return_context)
-            });
+        // A `&...name` splice stands for as many arguments as each call of the
+        // enclosing macro passes, which `validate_rest_splices` checks there.
+        if call_args.positional.iter().any(|arg| rest_arg_splice(arg).is_some()) {
+            return;
+        }
 
-        let Some(matching_context) = matching_context else {
-            expected_counts.sort();
-            expected_counts.dedup();
+        let matching_context = find_macro_in(&self.macro_registries, macro_name, &call_args)
+            .map(|(_, def)| def.return_context);
 
+        let Some(matching_context) = matching_context else {
             self.ast_errors.report(
                 TypeError::WrongMacroArgCount {
                     name: macro_name,
                     expected: expected_counts,
-                    got: call_arg_count,
+                    got: call_args.len(),
                 },
                 self.range_from_span(name.token.span()),
             );
@@ -589,6 +885,20 @@ impl<'a> TypecheckSelectors<'a> {
                 },
                 self.range_from_span(name.token.span()),
             );
+            return;
+        }
+
+        // Arguments are spliced into the selector as text.
+        let args = call_args.positional.iter().chain(call_args.named.iter().map(|(_, arg)| arg));
+        for arg in args {
+            if !is_selector_text(arg) {
+                self.ast_errors.report(
+                    TypeError::InvalidMacroArg {
+                        msg: "Selector macro arguments must be strings, numbers or static tokens.",
+                    },
+                    self.range_from_span(arg.span()),
+                );
+            }
         }
     }
 
@@ -597,6 +907,22 @@ impl<'a> TypecheckSelectors<'a> {
     }
 }
 
+fn is_selector_text(construct: &Construct) -> bool {
+    match construct {
+        Construct::Node { node } => matches!(
+            node.token.value(),
+            Token::StringSingle(_)
+                | Token::StringMulti(_)
+                | Token::StringInterpolated(_)
+                | Token::Number(_)
+                | Token::Boolean(_)
+                | Token::StaticTokenIdentifier(_)
+        ),
+        Construct::MacroCall { .. } => true,
+        _ => false,
+    }
+}
+
 enum ConsumeResult<'a> {
     Some(&'a Node<'a>),
     None,
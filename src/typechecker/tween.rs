@@ -1,12 +1,12 @@
 use rbx_types::Variant;
 
 use crate::{
-    datatype::{Datatype, evaluate_construct},
+    datatype::{Datatype, Unit, evaluate_construct},
     lexer::{SpannedToken, Token},
     parser::{AstErrors, Construct, Delimited, Node},
 };
 
-use crate::typechecker::{ReportTypeError, Typechecker, TypecheckerLookup, type_error::*};
+use crate::typechecker::{ReportTypeError, Typechecker, type_error::*};
 
 fn is_enum(construct: &Construct, expected_name: &str) -> bool {
     match construct {
@@ -102,13 +102,22 @@ fn is_boolean(construct: &Construct) -> bool {
 
 impl<'a> Typechecker<'a> {
     fn is_number(&self, construct: &Construct) -> bool {
-        let lookup = TypecheckerLookup { scopes: &self.static_scopes };
+        let lookup = self.lookup();
         matches!(
             evaluate_construct(construct, None, &lookup),
             Some(Datatype::Variant(Variant::Float64(_)))
         )
     }
 
+    /// A plain number of seconds, or a number written with `s` or `ms`.
+    fn is_duration(&self, construct: &Construct) -> bool {
+        let lookup = self.lookup();
+        matches!(
+            evaluate_construct(construct, None, &lookup),
+            Some(Datatype::Variant(Variant::Float64(_)) | Datatype::Measure(_, Unit::Duration))
+        )
+    }
+
     pub(super) fn typecheck_tween(
         &self,
         body: &Construct<'a>,
@@ -116,7 +125,7 @@ impl<'a> Typechecker<'a> {
     ) {
         match body {
             // Case 1: bare number — `@tween Prop .5;`
-            construct if self.is_number(construct) => (),
+            construct if self.is_duration(construct) => (),
 
             // Case 2: tuple — `@tween Prop (.5, :InOut, :In);`
             Construct::Table {
@@ -132,7 +141,7 @@ impl<'a> Typechecker<'a> {
                     return;
                 }
 
-                if !self.is_number(args[0]) {
+                if !self.is_duration(args[0]) {
                     ast_errors.report(
                         TypeError::InvalidTweenArg { expected: "number", arg_name: Some("time") },
                         self.parsed.range_from_span(args[0].span()),
@@ -190,7 +199,7 @@ impl<'a> Typechecker<'a> {
                 }
 
                 if let Some(arg) = args.get(5) {
-                    if !self.is_number(arg) {
+                    if !self.is_duration(arg) {
                         ast_errors.report(
                             TypeError::InvalidTweenArg { expected: "number", arg_name: Some("delay time") },
                             self.parsed.range_from_span(arg.span()),
@@ -1,8 +1,16 @@
 use std::path::PathBuf;
 
-use crate::types::Severity;
+use serde_json::Value;
+
+use crate::datatype::Unit;
+use crate::types::{Position, Range, Severity};
 use crate::typechecker::normalize_path::NormalizePath;
 
+fn with_article(word: &str) -> String {
+    let article = if word.starts_with(['a', 'e', 'i', 'o', 'u']) { "an" } else { "a" };
+    format!("{} {}", article, word)
+}
+
 /// Joins `items` into an Oxford-comma list with the given `conjunction`
 /// (e.g. `"or"`, `"and"`). Callers pre-format each item (adding backticks,
 /// wrapping in a unit, etc.) so this helper stays punctuation-only.
@@ -18,6 +26,20 @@ fn oxford_join(items: &[String], conjunction: &str) -> String {
     }
 }
 
+/// Serializes `range` as `{prefix}_start` / `{prefix}_end` positions, for
+/// diagnostics which point at a second location.
+fn range_data(prefix: &str, range: &Range) -> Value {
+    let position = |position: Position| Value::Object(collection!{
+        "line".to_string() => Value::Number(position.line.into()),
+        "char".to_string() => Value::Number(position.character.into()),
+    });
+
+    Value::Object(collection!{
+        format!("{}_start", prefix) => position(range.start),
+        format!("{}_end", prefix) => position(range.end),
+    })
+}
+
 pub enum ExpectedDatatype {
     String,
     Number,
@@ -56,10 +78,32 @@ pub enum TypeError<'a> {
     WrongAnnotationArgCount { name: &'a str, expected: Vec<usize>, got: usize },
     WrongAnnotationArgType { arg_index: usize, expected: &'a str },
     UndefinedToken { name: &'a str, is_static: bool },
+    UnknownPaletteColor { name: &'a str },
     UnknownEnum { name: String },
     UnknownEnumVariant { enum_name: String, variant: String },
     UnknownProperty { name: String, missing: Vec<String>, present: Vec<String> },
     PropertyTypeMismatch { name: String, expected: String, got: String },
+    ImplicitTupleCoercion { name: &'a str },
+    UnpinnedEnumShorthand { variant: String },
+    UnresolvedPropertyType { name: String },
+    TokenTypeMismatch {
+        name: String,
+        is_static: bool,
+        property: String,
+        expected: String,
+        got: String,
+        definition: Range,
+        /// The derived stylesheet the token was declared in, if it wasn't
+        /// declared in this one.
+        path: Option<PathBuf>,
+    },
+    AttributeTypeMismatch { name: &'a str, expected: &'a str, got: &'a str },
+    DuplicateAssignment { name: String, previous: Range },
+    OutOfGamutColor,
+    LowContrast { ratio: f64, threshold: f64, background: Range },
+    PropertyConflict { name: String, ambiguous: bool, winner: Range },
+    UnpairedTransparency { name: String },
+    UnitMismatch { name: String, expected: Option<Unit>, got: Unit },
 }
 
 impl<'a> TypeError<'a> {
@@ -81,10 +125,23 @@ impl<'a> TypeError<'a> {
             Self::WrongAnnotationArgCount { .. } |
             Self::WrongAnnotationArgType { .. } |
             Self::UndefinedToken { .. } |
+            Self::UnknownPaletteColor { .. } |
             Self::UnknownEnum { .. } |
             Self::UnknownEnumVariant { .. } |
             Self::UnknownProperty { .. } |
-            Self::PropertyTypeMismatch { .. } => Severity::Error
+            Self::PropertyTypeMismatch { .. } |
+            Self::ImplicitTupleCoercion { .. } |
+            Self::UnpinnedEnumShorthand { .. } |
+            Self::UnresolvedPropertyType { .. } |
+            Self::TokenTypeMismatch { .. } |
+            Self::AttributeTypeMismatch { .. } |
+            Self::UnpairedTransparency { .. } |
+            Self::UnitMismatch { .. } => Severity::Error,
+
+            Self::DuplicateAssignment { .. } |
+            Self::OutOfGamutColor |
+            Self::LowContrast { .. } |
+            Self::PropertyConflict { .. } => Severity::Warning
         }
     }
 
@@ -206,6 +263,12 @@ impl<'a> TypeError<'a> {
                 )
             }
 
+            Self::UnknownPaletteColor { name } =>
+                format!(
+                    "Type Error (Unknown Palette Color): `{}` is not in any palette loaded from the luaurc.",
+                    name
+                ),
+
             Self::UnknownEnum { name } =>
                 format!("Type Error (Unknown Enum): No enum named `{}` exists.", name),
 
@@ -246,11 +309,111 @@ impl<'a> TypeError<'a> {
                     "Type Error (Property Type Mismatch): Property `{}` expects type `{}`, got `{}`.",
                     name, expected, got
                 ),
+
+            Self::ImplicitTupleCoercion { name } =>
+                format!(
+                    "Type Error (Implicit Tuple Coercion): `{}` is assigned a tuple, which strict mode won't collapse to its first element.",
+                    name
+                ),
+
+            Self::UnpinnedEnumShorthand { variant } =>
+                format!(
+                    "Type Error (Unpinned Enum Shorthand): Enum shorthand `:{}` has no enum to resolve against. Use the full `Enum.<Name>.{}` form instead.",
+                    variant, variant
+                ),
+
+            Self::UnresolvedPropertyType { name } =>
+                format!(
+                    "Type Error (Unresolved Property Type): The type of property `{}` could not be resolved.",
+                    name
+                ),
+
+            Self::TokenTypeMismatch { name, is_static, property, expected, got, definition, path } => {
+                let sigil = if *is_static { "$!" } else { "$" };
+                let location = match path {
+                    Some(path) => format!("line {} of {:#?}", definition.start.line + 1, path.normalize()),
+                    None => format!("line {}", definition.start.line + 1),
+                };
+                format!(
+                    "Type Error (Token Type Mismatch): Property `{}` expects type `{}`, but token `{}{}` (defined on {}) has type `{}`.",
+                    property, expected, sigil, name, location, got
+                )
+            }
+
+            Self::AttributeTypeMismatch { name, expected, got } =>
+                format!(
+                    "Type Error (Attribute Type Mismatch): Attribute `{}` is declared as type `{}` by token `${}`, got `{}`.",
+                    name, expected, name, got
+                ),
+
+            Self::DuplicateAssignment { name, previous } =>
+                format!(
+                    "Type Warning (Duplicate Assignment): `{}` is assigned more than once in this rule, overwriting the value from line {}.",
+                    name, previous.start.line + 1
+                ),
+
+            Self::OutOfGamutColor =>
+                "Type Warning (Out Of Gamut Color): This color can't be displayed in sRGB, so its chroma will be reduced until it fits.".to_string(),
+
+            Self::LowContrast { ratio, threshold, background } =>
+                format!(
+                    "Type Warning (Low Contrast): Text has a contrast ratio of {:.2}:1 against the background set on line {}, below the required {}:1.",
+                    ratio, background.start.line + 1, threshold
+                ),
+
+            Self::PropertyConflict { name, ambiguous: true, winner } =>
+                format!(
+                    "Type Warning (Property Conflict): `{}` is overridden by the rule on line {}, which is just as specific and only wins because it comes later.",
+                    name, winner.start.line + 1
+                ),
+
+            Self::UnpairedTransparency { name } =>
+                format!(
+                    "Type Error (Unpaired Transparency): `{}` has no transparency property to hold this colour's alpha.",
+                    name
+                ),
+
+            Self::UnitMismatch { name, expected: Some(expected), got } =>
+                format!(
+                    "Type Error (Unit Mismatch): Property `{}` expects {}, got {}.",
+                    name, with_article(expected.name()), with_article(got.name())
+                ),
+
+            Self::UnitMismatch { name, expected: None, got } =>
+                format!(
+                    "Type Error (Unit Mismatch): Property `{}` doesn't take a unit, got {}.",
+                    name, with_article(got.name())
+                ),
+
+            Self::PropertyConflict { name, ambiguous: false, winner } =>
+                format!(
+                    "Type Warning (Property Conflict): `{}` is overridden by the earlier rule on line {}, which has a higher priority or specificity.",
+                    name, winner.start.line + 1
+                ),
         }
     }
 
-    pub fn data(&self) -> Option<serde_json::Value> {
-        None
+    pub fn data(&self) -> Option<Value> {
+        match self {
+            Self::TokenTypeMismatch { definition, path, .. } => {
+                let mut data = range_data("definition", definition);
+                if let (Value::Object(data), Some(path)) = (&mut data, path) {
+                    data.insert(
+                        "definition_path".to_string(),
+                        Value::String(path.to_string_lossy().into_owned()),
+                    );
+                }
+                Some(data)
+            }
+
+            Self::DuplicateAssignment { previous, .. } => Some(range_data("previous", previous)),
+
+            Self::LowContrast { background, .. } => Some(range_data("background", background)),
+
+            Self::PropertyConflict { winner, .. } => Some(range_data("winner", winner)),
+
+            _ => None,
+        }
     }
 }
 
@@ -273,10 +436,22 @@ impl<'a> ToString for TypeError<'a> {
             Self::WrongAnnotationArgCount { .. } => "WRONG_ANNOTATION_ARG_COUNT",
             Self::WrongAnnotationArgType { .. } => "WRONG_ANNOTATION_ARG_TYPE",
             Self::UndefinedToken { .. } => "UNDEFINED_TOKEN",
+            Self::UnknownPaletteColor { .. } => "UNKNOWN_PALETTE_COLOR",
             Self::UnknownEnum { .. } => "UNKNOWN_ENUM",
             Self::UnknownEnumVariant { .. } => "UNKNOWN_ENUM_VARIANT",
             Self::UnknownProperty { .. } => "UNKNOWN_PROPERTY",
             Self::PropertyTypeMismatch { .. } => "PROPERTY_TYPE_MISMATCH",
+            Self::ImplicitTupleCoercion { .. } => "IMPLICIT_TUPLE_COERCION",
+            Self::UnpinnedEnumShorthand { .. } => "UNPINNED_ENUM_SHORTHAND",
+            Self::UnresolvedPropertyType { .. } => "UNRESOLVED_PROPERTY_TYPE",
+            Self::TokenTypeMismatch { .. } => "TOKEN_TYPE_MISMATCH",
+            Self::AttributeTypeMismatch { .. } => "ATTRIBUTE_TYPE_MISMATCH",
+            Self::DuplicateAssignment { .. } => "DUPLICATE_ASSIGNMENT",
+            Self::OutOfGamutColor => "OUT_OF_GAMUT_COLOR",
+            Self::LowContrast { .. } => "LOW_CONTRAST",
+            Self::PropertyConflict { .. } => "PROPERTY_CONFLICT",
+            Self::UnpairedTransparency { .. } => "UNPAIRED_TRANSPARENCY",
+            Self::UnitMismatch { .. } => "UNIT_MISMATCH",
         })
     }
 }
\ No newline at end of file